@@ -7,26 +7,118 @@ add-bulk = 한꺼번에 여러개 추가하기
 add-quote = 대사 추가하기
 add-quote-one-by-one-guide = 추가할 대사를 입력합니다. 한 대사는 여러 줄로 구성될 수 있습니다.
 add-quote-bulk-guide = 추가할 대사들을 한 줄에 하나씩 입력합니다. 한 대사가 여러 줄로 구성되어있을 경우, '하나씩 추가하기' 기능을 이용하세요.
+duplicate-quotes-skipped = 이미 있는 대사라서 건너뛴 대사
+near-duplicate-quotes-found = 이미 있는 대사와 비슷한 대사가 있어서 아무 대사도 추가하지 않았습니다. 비슷한 대사를 어떻게 할지 고른 뒤 다시 추가해주세요
+near-duplicate-quotes = 이미 있는 대사와 비슷한 대사
+near-duplicate-skip = 비슷한 대사는 건너뛰고 추가하기
+near-duplicate-keep = 비슷한 대사도 모두 추가하기
 add-quote-error = 대사 추가에 실패했습니다.
 
+import-quotes = 다른 계정의 글 가져오기
+import-quotes-guide = 다른 계정의 공개 글을 읽어와 대사로 추가합니다. 답글과 부스트는 제외하며, 최근 글부터 최대 200개까지 가져옵니다.
+import-acct = 계정
+import-hashtag = 해시태그 (선택)
+import-since = 이 날짜 이후의 글만 (선택)
+import-min-length = 최소 글자 수
+import-fetch = 글 불러오기
+import-review-guide = 대사로 추가할 글을 골라주세요.
+import-add = 선택한 글을 대사로 추가하기
+import-quotes-empty = 조건에 맞는 글이 없습니다.
+import-quotes-error = 글을 불러오지 못했습니다.
+
 add-reply = 응답 추가하기
 keyword = 키워드
-keyword-guide = 이 키워드를 포함한 멘션을 받으면, 응답을 포스트합니다. 키워드는 줄바꿈 없이 한 줄로 구성되어야합니다.
+keyword-guide = 이 키워드에 매칭되는 멘션을 받으면, 응답을 포스트합니다. 키워드는 줄바꿈 없이 한 줄로 구성되어야합니다.
 add-reply-one-by-one-guide = 추가할 응답을 입력합니다. 한 응답은 여러 줄로 구성될 수 있습니다.
 add-reply-bulk-guide = 추가할 응답들을 한 줄에 하나씩 입력합니다. 한 응답이 여러 줄로 구성되어있을 경우, '하나씩 추가하기' 기능을 이용하세요.
 add-reply-error = 응답 추가에 실패했습니다.
-restart-reply-guide = 응답 기능이 동작하지 않을시, 재시작을 통해 해결되는 경우가 있습니다. 아래 재시작 버튼을 눌러도 해결되지 않는 경우, 관리자에게 문의해주세요.
+match-type = 키워드 매칭 방식
+match-type-guide = 멘션이 여러 키워드에 해당하면 응답 목록에서 위에 있는 키워드가 우선합니다.
+match-type-substring = 포함
+match-type-whole-word = 단어 단위 일치
+match-type-case-insensitive = 대소문자 무시하고 포함
+match-type-regex = 정규식
+invalid-regex = 올바르지 않은 정규식입니다.
+update-reply-rule-error = 키워드 설정 변경에 실패했습니다.
+move-up = 위로
+move-down = 아래로
 
 configure-schedule = 스케줄 설정하기
 configure-reply = 응답 설정하기
+configure-generation = 글 생성 설정하기
+markov = 마르코프 체인으로 새 문장 만들기
+markov-guide = 켜면 대사를 그대로 올리는 대신, 대사들을 섞어 새로운 문장을 만들어 올립니다. 원래 대사와 너무 비슷한 문장은 올리지 않으며, 새 문장을 만들지 못하면 대사를 그대로 올립니다. 예약한 글에는 적용되지 않습니다.
+markov-order = 차수
+markov-order-guide = 다음 토큰을 고를 때 참고하는 앞 토큰의 수입니다. 클수록 원래 대사에 가깝고, 작을수록 엉뚱한 문장이 나옵니다.
+markov-tokenizer = 토큰 단위
+markov-tokenizer-character = 글자
+markov-tokenizer-morpheme = 단어 (조사 분리)
+feed-url = 피드 주소
+feed-url-guide = RSS나 Atom 피드의 주소를 입력하면, 스케줄마다 피드에 새 글이 있는지 확인하고 가장 오래된 새 글을 올립니다. 새 글이 없으면 대사를 올립니다. 처음에는 가장 최근 글 하나만 새 글로 봅니다.
+feed-template = 피드 글 양식
+feed-template-guide = {"{"}title{"}"}은 글 제목으로, {"{"}link{"}"}는 글 주소로 바뀝니다. 비워두면 제목과 주소를 한 줄씩 올립니다.
+invalid-feed-url = 올바르지 않은 피드 주소입니다. http 또는 https 주소를 입력해주세요.
 
 suspend = 일시정지
 enable = 활성화
 save = 저장
-restart = 재시작
 
 dice-feature = 다이스 기능
-dice-feature-guide = 2d6와 같은 ndm 형태의 멘션을 받으면 주사위를 굴린 결과를 답변합니다.
+reply-cooldown-seconds = 같은 계정에 다시 응답하기까지 기다리는 시간
+seconds = 초
+replies-per-minute = 1분에 보낼 수 있는 최대 응답 수
+zero-means-unlimited = 0이면 제한하지 않습니다.
+ignore-bots = 봇 계정의 멘션에는 응답하지 않기
+reply-visibility-cap = 답글 공개 범위 제한
+reply-visibility-no-cap = 제한 없음
+reply-visibility-guide = 답글은 멘션과 같은 공개 범위로 달리고, 멘션의 열람 주의(CW)도 그대로 따라갑니다. 제한을 정하면 그보다 넓은 범위로는 답글을 달지 않습니다.
+visibility-public = 공개
+visibility-unlisted = 조용한 공개
+visibility-private = 팔로워 전용
+visibility-direct = 다이렉트
+dice-feature-guide = 2d6와 같은 주사위 식이 담긴 멘션을 받으면 주사위를 굴린 결과를 답변합니다. 2d6+3처럼 수를 더하거나 1d20+1d4처럼 여러 주사위를 더할 수 있고, 4d6kh3(높은 3개만 남기기), 4d6dl1(낮은 1개 버리기), 3d6!(최댓값이 나오면 한 번 더), 4dF(퍼지 주사위), d%(100면체)도 쓸 수 있습니다.
+coin-feature = 동전 던지기
+coin-feature-guide = "동전" 또는 "coin"이 들어간 멘션을 받으면 앞면이나 뒷면으로 답변합니다.
+choose-feature = 골라주기
+choose-feature-guide = "골라줘: 짜장, 짬뽕" 또는 "choose A or B"와 같은 멘션을 받으면 쉼표, 슬래시, or로 나눈 항목 중 하나를 골라 답변합니다.
+eight-ball-feature = 매직 8볼
+eight-ball-feature-guide = "8ball" 또는 🎱이 들어간 멘션을 받으면 매직 8볼의 답변 중 하나로 답변합니다.
+tarot-feature = 타로 카드
+tarot-feature-guide = "타로" 또는 "tarot"이 들어간 멘션을 받으면 78장의 타로 카드 중 한 장을 정방향이나 역방향으로 뽑아 답변합니다.
+random-number-feature = 랜덤 숫자
+random-number-feature-guide = "랜덤 1~100" 또는 "random 6"과 같은 멘션을 받으면 범위 안의 숫자 하나를 답변합니다.
+configure-reactions = 알림에 반응하기
+configure-reactions-guide = 멘션 외의 알림에 반응합니다. 팔로우 되돌려주기와 반응 기능은 다시 로그인한 뒤 저장해야 동작할 수 있습니다.
+reaction-follow = 팔로우
+reaction-follow-welcome = 팔로우한 계정에 환영 메시지를 다이렉트 메시지로 보내기
+reaction-follow-back = 팔로우 되돌려주기
+reaction-follow-messages = 환영 메시지들을 한 줄에 하나씩 입력합니다.
+reaction-reblog = 부스트
+reaction-favourite = 좋아요
+reaction-messages = 감사 메시지나 이모지들을 한 줄에 하나씩 입력합니다.
+reaction-messages-guide = 답글 모드에서는 메시지 중 하나로 답글을 답니다. 반응 모드에서는 상대의 최근 글에 이모지 중 하나로 반응하며, Mastodon에서는 좋아요를 누릅니다.
+
+scripts = 대화 스크립트
+scripts-guide = 키워드로 시작하는 여러 단계의 대화입니다. 퀴즈처럼 질문을 보내고, 같은 스레드에 달린 답글을 확인해 다음 질문으로 넘어갑니다. 스크립트는 키워드 응답보다 우선합니다.
+add-script = 스크립트 추가하기
+add-script-error = 스크립트 추가에 실패했습니다.
+invalid-script = 올바르지 않은 스크립트입니다. 모든 단계에 질문이 있어야 합니다.
+script-expiry-minutes = 답을 기다리는 시간
+script-steps = 단계
+script-steps-guide = 단계들은 빈 줄로 구분합니다. 각 단계에서 'Q:'로 시작하는 줄은 질문, 'A:'는 쉼표로 구분한 정답들, 'O:'는 맞았을 때의 답글, 'X:'는 틀렸을 때의 답글입니다. 정답을 비워두면 어떤 답이든 맞은 것으로 칩니다.
+script-steps-count = 단계
+response-mode-off = 반응하지 않기
+response-mode-reply = 감사 답글 달기
+response-mode-react = 반응하기
+streaming-status = 연결 상태
+streaming-state-connecting = 연결 중
+streaming-state-connected = 연결됨
+streaming-state-reconnecting = 재연결 대기 중
+streaming-state-stopped = 중지됨
+streaming-state-not-running = 실행 중이 아님
+streaming-status-updated-at = 마지막 갱신
+streaming-status-error = 마지막 오류
+streaming-outdated-scopes = 봇에 필요한 권한이 늘어났습니다. 로그아웃한 뒤 다시 로그인하고 응답 설정을 저장해주세요.
 
 cron-expression = cron 표현식
 what-is-cron-expression = cron 표현식이 뭔가요?
@@ -44,13 +136,57 @@ cron-expression-warning-utc = cron 표현식에 적는 시각은 UTC(세계표
 
 configure-schedule-error = 스케줄 설정에 실패했습니다.
 
+post-now = 지금 포스트하기
+post-now-guide = 스케줄을 기다리지 않고 대사 중 하나를 랜덤으로 지금 바로 포스트합니다. 먼저 스케줄이 설정되어 있어야 합니다.
+post-now-error = 포스트 요청에 실패했습니다.
+preview-post = 다음 포스트 미리보기
+preview-post-guide = 실제로 포스트하지 않고 다음에 포스트될 대사를 골라 미리 보여줍니다. 결과가 나타나기까지 잠시 걸릴 수 있으니 잠시 후 새로고침 해주세요.
+preview-post-error = 미리보기 요청에 실패했습니다.
+preview = 미리보기
+preview-payload = 전송될 요청 내용
+preview-dedup-until = 중복 방지 만료 시각
+schedule-post = 예약 포스트하기
+scheduled-at = 포스트할 시각
+scheduled-at-warning-utc = 포스트할 시각은 UTC(세계표준시) 기준이라는 것을 주의하세요.
+scheduled-quote = 포스트할 대사
+ad-hoc-text = 직접 입력하기
+ad-hoc-text-guide = 대사 목록에 없는 내용을 포스트하려면, '직접 입력하기'를 선택하고 아래에 내용을 입력합니다.
+schedule-post-error = 포스트 예약에 실패했습니다.
+invalid-datetime = 올바른 시각이 아닙니다.
+scheduled-at-must-be-future = 포스트할 시각은 현재 이후여야 합니다.
+scheduled-at-too-far = 포스트할 시각은 1년 이내여야 합니다.
+quote-not-found = 삭제된 대사
+
+post-history = 포스트 기록
+post-history-guide = 최근에 포스트한 대사들입니다. 포스트에 실패한 경우 실패한 이유를 확인할 수 있습니다.
+post-failed = 포스트 실패
+view-post = 포스트 보기
+
 dedup-duration-minutes = 한 번 보낸 대사를 중복해서 다시 보내지 않는 시간 간격 (단위: 분) (중복 방지 기능을 끄려면 0)
+dedup-fallback = 모든 대사가 중복 방지 기간 안에 있을 때
+dedup-fallback-skip = 이번 포스트를 건너뛰기
+dedup-fallback-least-recently-posted = 가장 오래 전에 보낸 대사를 보내기
+dedup-fallback-shrink-window = 중복 방지 기간을 줄여가며 대사를 고르기
+dedup-pool-exhausted-warning = 중복 방지 기간 동안 스케줄에 따라 포스트되는 횟수가 대사 수보다 많아서, 보낼 수 있는 대사가 바닥나게 됩니다. 대사를 더 추가하거나, 중복 방지 기간을 줄이거나, 스케줄 간격을 늘려주세요.
+posts-per-dedup-window = 중복 방지 기간 동안의 포스트 횟수
+quote-count = 대사 수
 minutes = 분
 
 quote-list = 대사 목록
 quote-post-guide = 지정된 스케줄에 대사 중 하나가 랜덤으로 포스트됩니다.
+quote-search = 검색
+quote-search-guide = #으로 시작하면 해당 해시태그가 달린 대사를, 그 외에는 검색어가 포함된 대사를 찾습니다.
+quote-sort-newest = 최근 추가한 순
+quote-sort-oldest = 오래전 추가한 순
+quote-sort-recently-posted = 최근 보낸 순
+quote-sort-text = 가나다순
+delete-selected-quotes = 선택한 대사 삭제
 reply-list = 응답 목록
-reply-post-guide = 한 키워드에 대한 응답은 추가한 여러 응답 중 하나가 랜덤으로 포스트됩니다.
+reply-post-guide = 한 키워드에 대한 응답은 추가한 여러 응답 중 하나가 랜덤으로 포스트됩니다. 이모지를 지정하면 멘션에 이모지로 반응하고, 반응만 하기를 선택하면 답글 없이 반응만 합니다. Mastodon에서는 이모지 반응을 지원하는 서버(Pleroma, Akkoma, glitch-soc)에서만 이모지로 반응하고, 그 외에는 좋아요를 누릅니다.
+reply-reaction = 이모지
+reply-react-only = 반응만 하기
 empty = 비어있음
+stats = 통계
 sent-recently = 최근에 보냄
+delete = 삭제
 delete-all = 전체 삭제
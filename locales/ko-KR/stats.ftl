@@ -0,0 +1,17 @@
+dashboard = 대시보드
+daily-stats = 일별 통계
+daily-stats-guide = 최근 30일 동안 포스트한 횟수와 멘션에 답글을 단 횟수입니다. 날짜는 UTC 기준입니다.
+date = 날짜
+posts = 포스트
+replies = 답글
+top-triggers = 자주 불린 키워드
+top-triggers-guide = 멘션에 반응한 키워드, 스크립트, 기본 기능의 횟수입니다.
+trigger = 키워드
+trigger-count = 횟수
+quote-stats = 대사별 통계
+quote-stats-guide = 답글, 좋아요, 부스트는 봇이 실행 중일 때 받은 것만 셉니다.
+quote = 대사
+times-posted = 포스트 횟수
+last-posted = 마지막 포스트
+favourites = 좋아요
+boosts = 부스트
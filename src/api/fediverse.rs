@@ -4,12 +4,14 @@ use eyre::{OptionExt, WrapErr};
 use http::HeaderMap;
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 use url::Url;
 
 use crate::{
     api::kube::{load_fediverse_app, save_fediverse_app},
     config::CONFIG,
     handler::auth::FediverseUser,
+    normalize,
 };
 
 static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
@@ -30,8 +32,22 @@ static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
 pub struct FediverseApp {
     pub client_id: String,
     pub client_secret: String,
+    /// Scopes the app was registered with. Apps registered with fewer scopes than we need now are
+    /// registered again.
+    pub scopes: String,
 }
 
+/// Tokens granted before a scope was added here lack it, so their users have to log in again.
+pub const MASTODON_SCOPES: &str =
+    "read:accounts read:statuses write:statuses read:notifications write:follows write:favourites";
+const MISSKEY_PERMISSIONS: [&str; 5] = [
+    "read:account",
+    "write:notes",
+    "read:notifications",
+    "write:following",
+    "write:reactions",
+];
+
 #[derive(Deserialize)]
 struct NodeInfoSoftware {
     name: String,
@@ -101,6 +117,7 @@ struct MastodonCreateAppResp {
 }
 
 async fn get_auth_redirect_url_mastodon(domain: &str) -> eyre::Result<Url> {
+    let scopes = MASTODON_SCOPES;
     let redirect_url = CONFIG
         .public_url
         .join(&format!("./auth/callback/mastodon/{domain}"))
@@ -108,6 +125,7 @@ async fn get_auth_redirect_url_mastodon(domain: &str) -> eyre::Result<Url> {
     let app = if let Some(app) = load_fediverse_app(domain)
         .await
         .wrap_err("failed to load fediverse app from Kubernetes")?
+        .filter(|app| app.scopes == scopes)
     {
         app
     } else {
@@ -118,7 +136,7 @@ async fn get_auth_redirect_url_mastodon(domain: &str) -> eyre::Result<Url> {
                 "fediq.pbzweihander.dev"
             },
             redirect_uris: &redirect_url,
-            scopes: "read:accounts write:statuses read:notifications",
+            scopes: MASTODON_SCOPES,
             website: CONFIG.public_url.clone(),
         };
         let url = format!("https://{domain}/api/v1/apps");
@@ -137,6 +155,7 @@ async fn get_auth_redirect_url_mastodon(domain: &str) -> eyre::Result<Url> {
         let app = FediverseApp {
             client_id: resp.client_id,
             client_secret: resp.client_secret,
+            scopes: scopes.to_string(),
         };
         save_fediverse_app(domain, &app)
             .await
@@ -146,7 +165,8 @@ async fn get_auth_redirect_url_mastodon(domain: &str) -> eyre::Result<Url> {
 
     let client_id = app.client_id;
 
-    let url = Url::from_str(&format!("https://{domain}/oauth/authorize?response_type=code&client_id={client_id}&redirect_uri={redirect_url}&scope=read:accounts+write:statuses+read:notifications"))
+    let scope = MASTODON_SCOPES.replace(' ', "+");
+    let url = Url::from_str(&format!("https://{domain}/oauth/authorize?response_type=code&client_id={client_id}&redirect_uri={redirect_url}&scope={scope}"))
         .wrap_err("failed to generate URL")?;
     Ok(url)
 }
@@ -178,9 +198,11 @@ struct MisskeyGenerateSessionResp {
 }
 
 async fn get_auth_redirect_url_misskey(domain: &str) -> eyre::Result<Url> {
+    let scopes = MISSKEY_PERMISSIONS.join(" ");
     let app = if let Some(app) = load_fediverse_app(domain)
         .await
         .wrap_err("failed to load fediverse app from Kubernetes")?
+        .filter(|app| app.scopes == scopes)
     {
         app
     } else {
@@ -195,7 +217,7 @@ async fn get_auth_redirect_url_misskey(domain: &str) -> eyre::Result<Url> {
                 "fediq.pbzweihander.dev"
             },
             description: CONFIG.public_url.to_string(),
-            permission: &["read:account", "write:notes", "read:notifications"],
+            permission: &MISSKEY_PERMISSIONS,
             callback_url: redirect_url,
         };
         let url = format!("https://{domain}/api/app/create");
@@ -214,6 +236,7 @@ async fn get_auth_redirect_url_misskey(domain: &str) -> eyre::Result<Url> {
         let app = FediverseApp {
             client_id: resp.id,
             client_secret: resp.secret,
+            scopes,
         };
         save_fediverse_app(domain, &app)
             .await
@@ -296,7 +319,7 @@ pub async fn login_mastodon(domain: &str, code: &str) -> eyre::Result<FediverseU
         client_id: &app.client_id,
         client_secret: &app.client_secret,
         redirect_uri: &redirect_url,
-        scope: "read:accounts read:statuses read:notifications write:statuses",
+        scope: MASTODON_SCOPES,
     };
     let url = format!("https://{domain}/oauth/token");
     let resp_text = HTTP_CLIENT
@@ -385,3 +408,243 @@ pub async fn login_misskey(domain: &str, token: &str) -> eyre::Result<FediverseU
         "misskey".to_string(),
     ))
 }
+
+/// How many pages of posts the importer reads at most.
+const MAX_IMPORT_PAGES: usize = 10;
+const MAX_IMPORT_CANDIDATES: usize = 200;
+
+/// Which posts of an account to offer as quotes.
+pub struct ImportFilter {
+    /// Without the leading `#`, compared case-insensitively.
+    pub hashtag: Option<String>,
+    pub since: Option<time::Date>,
+    /// In characters.
+    pub min_length: usize,
+}
+
+impl ImportFilter {
+    fn matches(&self, text: &str, tags: &[String]) -> bool {
+        text.chars().count() >= self.min_length.max(1)
+            && self
+                .hashtag
+                .as_ref()
+                .is_none_or(|hashtag| tags.iter().any(|tag| tag.eq_ignore_ascii_case(hashtag)))
+    }
+
+    fn is_before_since(&self, created_at: OffsetDateTime) -> bool {
+        self.since.is_some_and(|since| created_at.date() < since)
+    }
+}
+
+#[derive(Deserialize)]
+struct MastodonLookupResp {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct MastodonTag {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct MastodonStatus {
+    id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    content: String,
+    #[serde(default)]
+    tags: Vec<MastodonTag>,
+}
+
+async fn import_posts_mastodon(
+    domain: &str,
+    acct: &str,
+    filter: &ImportFilter,
+) -> eyre::Result<Vec<String>> {
+    // Public statuses need no token, and the importing user's may be of another instance.
+    let url = format!("https://{domain}/api/v1/accounts/lookup");
+    let resp_text = HTTP_CLIENT
+        .get(&url)
+        .query(&[("acct", acct)])
+        .send()
+        .await
+        .wrap_err_with(|| format!("failed to request to `{url}`"))?
+        .text()
+        .await
+        .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+    let account = serde_json::from_str::<MastodonLookupResp>(&resp_text)
+        .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+
+    let url = format!("https://{domain}/api/v1/accounts/{}/statuses", account.id);
+    let mut posts = Vec::new();
+    let mut max_id = None;
+    for _ in 0..MAX_IMPORT_PAGES {
+        let mut query = vec![
+            ("limit", "40".to_string()),
+            ("exclude_reblogs", "true".to_string()),
+            ("exclude_replies", "true".to_string()),
+        ];
+        if let Some(hashtag) = &filter.hashtag {
+            query.push(("tagged", hashtag.clone()));
+        }
+        if let Some(max_id) = max_id.take() {
+            query.push(("max_id", max_id));
+        }
+        let resp_text = HTTP_CLIENT
+            .get(&url)
+            .query(&query)
+            .send()
+            .await
+            .wrap_err_with(|| format!("failed to request to `{url}`"))?
+            .text()
+            .await
+            .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+        let statuses = serde_json::from_str::<Vec<MastodonStatus>>(&resp_text)
+            .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+        let Some(last) = statuses.last() else {
+            break;
+        };
+        max_id = Some(last.id.clone());
+        let reached_since = filter.is_before_since(last.created_at);
+        for status in statuses {
+            if filter.is_before_since(status.created_at) {
+                continue;
+            }
+            let text = normalize::html_to_text(&status.content);
+            let tags = status
+                .tags
+                .into_iter()
+                .map(|tag| tag.name)
+                .collect::<Vec<_>>();
+            if filter.matches(&text, &tags) {
+                posts.push(text);
+            }
+        }
+        if reached_since || posts.len() >= MAX_IMPORT_CANDIDATES {
+            break;
+        }
+    }
+    posts.truncate(MAX_IMPORT_CANDIDATES);
+    Ok(posts)
+}
+
+#[derive(Serialize)]
+struct MisskeyUsersShowReq<'a> {
+    username: &'a str,
+    host: Option<&'a str>,
+}
+
+#[derive(Deserialize)]
+struct MisskeyUsersShowResp {
+    id: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct MisskeyUsersNotesReq<'a> {
+    user_id: &'a str,
+    limit: u32,
+    with_replies: bool,
+    with_renotes: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    until_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MisskeyNote {
+    id: String,
+    #[serde(with = "time::serde::rfc3339")]
+    created_at: OffsetDateTime,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    renote_id: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+async fn import_posts_misskey(
+    domain: &str,
+    acct: &str,
+    filter: &ImportFilter,
+) -> eyre::Result<Vec<String>> {
+    let (username, host) = match acct.split_once('@') {
+        Some((username, host)) if host != domain => (username, Some(host)),
+        Some((username, _)) => (username, None),
+        None => (acct, None),
+    };
+    let url = format!("https://{domain}/api/users/show");
+    let resp_text = HTTP_CLIENT
+        .post(&url)
+        .json(&MisskeyUsersShowReq { username, host })
+        .send()
+        .await
+        .wrap_err_with(|| format!("failed to request to `{url}`"))?
+        .text()
+        .await
+        .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+    let user = serde_json::from_str::<MisskeyUsersShowResp>(&resp_text)
+        .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+
+    let url = format!("https://{domain}/api/users/notes");
+    let mut posts = Vec::new();
+    let mut until_id = None;
+    for _ in 0..MAX_IMPORT_PAGES {
+        let req = MisskeyUsersNotesReq {
+            user_id: &user.id,
+            limit: 100,
+            with_replies: false,
+            with_renotes: false,
+            until_id: until_id.take(),
+        };
+        let resp_text = HTTP_CLIENT
+            .post(&url)
+            .json(&req)
+            .send()
+            .await
+            .wrap_err_with(|| format!("failed to request to `{url}`"))?
+            .text()
+            .await
+            .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+        let notes = serde_json::from_str::<Vec<MisskeyNote>>(&resp_text)
+            .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+        let Some(last) = notes.last() else {
+            break;
+        };
+        until_id = Some(last.id.clone());
+        let reached_since = filter.is_before_since(last.created_at);
+        for note in notes {
+            if note.renote_id.is_some() || filter.is_before_since(note.created_at) {
+                continue;
+            }
+            let Some(text) = note.text else {
+                continue;
+            };
+            let text = normalize::strip_mentions(&text);
+            if filter.matches(&text, &note.tags) {
+                posts.push(text);
+            }
+        }
+        if reached_since || posts.len() >= MAX_IMPORT_CANDIDATES {
+            break;
+        }
+    }
+    posts.truncate(MAX_IMPORT_CANDIDATES);
+    Ok(posts)
+}
+
+/// Reads the public posts of `acct`, as seen from `domain`, newest first.
+pub async fn import_posts(
+    domain: &str,
+    software: &str,
+    acct: &str,
+    filter: &ImportFilter,
+) -> eyre::Result<Vec<String>> {
+    let acct = acct.trim().trim_start_matches('@');
+    match software {
+        "mastodon" => import_posts_mastodon(domain, acct, filter).await,
+        "misskey" => import_posts_misskey(domain, acct, filter).await,
+        software => Err(eyre::eyre!("unsupported software `{software}`")),
+    }
+}
@@ -5,27 +5,49 @@ use eyre::WrapErr;
 use k8s_openapi::{
     api::{
         apps::v1::{Deployment, DeploymentSpec},
-        batch::v1::{CronJob, CronJobSpec, JobSpec, JobTemplateSpec},
-        core::v1::{ConfigMap, Container, EnvVar, PodSpec, PodTemplateSpec, Secret},
+        batch::v1::{CronJob, CronJobSpec, Job, JobSpec, JobTemplateSpec},
+        core::v1::{
+            ConfigMap, Container, EnvVar, EnvVarSource, PodSpec, PodTemplateSpec, Secret,
+            SecretKeySelector,
+        },
     },
     apimachinery::pkg::apis::meta::v1::LabelSelector,
     ByteString,
 };
 use kube::{
-    api::{Patch, PatchParams},
+    api::{ListParams, Patch, PatchParams, PostParams},
     core::ObjectMeta,
-    Api, ResourceExt,
+    Api, Resource, ResourceExt,
 };
 use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
 use time::OffsetDateTime;
 use ulid::Ulid;
 
-use crate::config::CONFIG;
+use crate::{
+    config::CONFIG,
+    similarity::{Duplicate, DuplicateChecker},
+};
 
-use super::fediverse::FediverseApp;
+use super::fediverse::{FediverseApp, MASTODON_SCOPES};
 
 const DEDUP_DURATION_MINUTES_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/dedup-duration-minutes";
-const DICE_FEATURE_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/dice-feature";
+const DEDUP_FALLBACK_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/dedup-fallback";
+const FEED_URL_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/feed-url";
+const FEED_TEMPLATE_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/feed-template";
+const GENERATION_MODE_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/generation-mode";
+const MARKOV_ORDER_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/markov-order";
+const MARKOV_TOKENIZER_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/markov-tokenizer";
+const REPLY_COOLDOWN_SECONDS_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/reply-cooldown-seconds";
+const REPLIES_PER_MINUTE_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/replies-per-minute";
+const IGNORE_BOTS_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/ignore-bots";
+const REPLY_VISIBILITY_CAP_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/reply-visibility-cap";
+const SCHEDULED_POST_ID_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/scheduled-post-id";
+const SCHEDULED_AT_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/scheduled-at";
+const SCHEDULED_QUOTE_ID_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/scheduled-quote-id";
+const SCHEDULED_TEXT_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/scheduled-text";
+const POSTER_CRONJOB_LABEL_KEY: &str = "fediq.pbzweihander.dev/poster-cronjob";
+const STREAMING_CONFIG_LABEL_KEY: &str = "fediq.pbzweihander.dev/streaming-config";
 
 async fn client() -> eyre::Result<kube::Client> {
     static CLIENT: OnceCell<kube::Client> = OnceCell::new();
@@ -51,6 +73,10 @@ fn fediverse_app_secret_name(domain: &str) -> String {
         .replace('_', "-")
 }
 
+fn is_not_found(error: &kube::Error) -> bool {
+    matches!(error, kube::Error::Api(kube::error::ErrorResponse { reason, .. }) if reason == "NotFound")
+}
+
 fn quotes_configmap_name(domain: &str, handle: &str) -> String {
     format!("{FEDIQ_NAME}-quotes-{domain}-{handle}")
         .to_ascii_lowercase()
@@ -69,12 +95,40 @@ fn quote_dedup_configmap_name(domain: &str, handle: &str) -> String {
         .replace('_', "-")
 }
 
+fn post_history_configmap_name(domain: &str, handle: &str) -> String {
+    format!("{FEDIQ_NAME}-post-history-{domain}-{handle}")
+        .to_ascii_lowercase()
+        .replace('_', "-")
+}
+
 fn poster_cronjob_name(domain: &str, handle: &str) -> String {
     format!("{FEDIQ_NAME}-poster-{domain}-{handle}")
         .to_ascii_lowercase()
         .replace('_', "-")
 }
 
+fn poster_config_secret_name(domain: &str, handle: &str) -> String {
+    format!("{FEDIQ_NAME}-poster-config-{domain}-{handle}")
+        .to_ascii_lowercase()
+        .replace('_', "-")
+}
+
+fn scheduled_post_cronjob_name(id: Ulid) -> String {
+    format!("{FEDIQ_NAME}-scheduled-{id}").to_ascii_lowercase()
+}
+
+fn streaming_state_configmap_name(domain: &str, handle: &str) -> String {
+    format!("{FEDIQ_NAME}-streaming-state-{domain}-{handle}")
+        .to_ascii_lowercase()
+        .replace('_', "-")
+}
+
+fn streaming_config_secret_name(domain: &str, handle: &str) -> String {
+    format!("{FEDIQ_NAME}-streaming-config-{domain}-{handle}")
+        .to_ascii_lowercase()
+        .replace('_', "-")
+}
+
 fn streaming_deployment_name(domain: &str, handle: &str) -> String {
     format!("{FEDIQ_NAME}-streaming-{domain}-{handle}")
         .to_ascii_lowercase()
@@ -114,10 +168,24 @@ pub async fn load_fediverse_app(domain: &str) -> eyre::Result<Option<FediverseAp
         String::from_utf8(client_id).wrap_err("failed to decode client id as UTF-8 string")?;
     let client_secret = String::from_utf8(client_secret)
         .wrap_err("failed to decode client secret as UTF-8 string")?;
+    // Apps saved before scopes were recorded have none, and get registered again.
+    let scopes = data
+        .get("scopes")
+        .map(|scopes| {
+            base64::engine::general_purpose::STANDARD
+                .decode(&scopes.0)
+                .wrap_err("failed to decode scopes secret data")
+                .and_then(|scopes| {
+                    String::from_utf8(scopes).wrap_err("failed to decode scopes as UTF-8 string")
+                })
+        })
+        .transpose()?
+        .unwrap_or_default();
 
     Ok(Some(FediverseApp {
         client_id,
         client_secret,
+        scopes,
     }))
 }
 
@@ -137,6 +205,8 @@ pub async fn save_fediverse_app(domain: &str, app: &FediverseApp) -> eyre::Resul
         "client_secret".to_string(),
         ByteString(client_secret.into_bytes()),
     );
+    let scopes = base64::engine::general_purpose::STANDARD.encode(app.scopes.as_bytes());
+    data.insert("scopes".to_string(), ByteString(scopes.into_bytes()));
 
     secret_api
         .patch(
@@ -157,38 +227,66 @@ pub async fn save_fediverse_app(domain: &str, app: &FediverseApp) -> eyre::Resul
     Ok(())
 }
 
+pub struct Quote {
+    pub text: String,
+    pub added_at: OffsetDateTime,
+    /// When the dedup window of the last post of this quote ends, if it was ever posted.
+    pub dedup_until: Option<OffsetDateTime>,
+    pub last_posted_at: Option<OffsetDateTime>,
+}
+
+impl Quote {
+    pub fn sent_recently(&self) -> bool {
+        self.dedup_until
+            .is_some_and(|dedup_until| dedup_until > OffsetDateTime::now_utc())
+    }
+}
+
 fn quote_map_to_template_map(
     quotes_map: &BTreeMap<String, String>,
     quote_dedup_map: &BTreeMap<String, String>,
-) -> BTreeMap<Ulid, (String, bool)> {
-    let now = OffsetDateTime::now_utc();
-
+    post_stats: &PostStats,
+) -> BTreeMap<Ulid, Quote> {
     let quotes = quotes_map
         .iter()
         .filter_map(|(key, value)| {
             let id = Ulid::from_string(key).ok()?;
-            let sent_recently = quote_dedup_map
-                .get(key)
-                .and_then(|value| {
-                    OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
-                        .ok()
-                })
-                .map(|timestamp| timestamp > now)
-                .unwrap_or(false);
-            Some((id, (value.clone(), sent_recently)))
+            let dedup_until = quote_dedup_map.get(key).and_then(|value| {
+                OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339).ok()
+            });
+            Some((
+                id,
+                Quote {
+                    text: value.clone(),
+                    added_at: OffsetDateTime::from(id.datetime()),
+                    dedup_until,
+                    last_posted_at: post_stats
+                        .quotes
+                        .get(&id)
+                        .and_then(|stats| stats.last_posted_at),
+                },
+            ))
         })
         .collect::<BTreeMap<_, _>>();
 
     quotes
 }
 
-pub async fn load_quotes(
-    domain: &str,
-    handle: &str,
-) -> eyre::Result<BTreeMap<Ulid, (String, bool)>> {
+pub async fn load_quotes(domain: &str, handle: &str) -> eyre::Result<BTreeMap<Ulid, Quote>> {
     let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
+    let post_stats = load_post_stats(&configmap_api, domain, handle).await?;
+    load_quotes_with_post_stats(&configmap_api, domain, handle, &post_stats).await
+}
+
+/// Loads the quotes with `post_stats` already read from the post history ConfigMap.
+async fn load_quotes_with_post_stats(
+    configmap_api: &Api<ConfigMap>,
+    domain: &str,
+    handle: &str,
+    post_stats: &PostStats,
+) -> eyre::Result<BTreeMap<Ulid, Quote>> {
     let quotes_configmap_name = quotes_configmap_name(domain, handle);
     let quotes_configmap = configmap_api
         .get_opt(&quotes_configmap_name)
@@ -219,14 +317,37 @@ pub async fn load_quotes(
     Ok(quote_map_to_template_map(
         &quotes_configmap_data,
         &quote_dedup_configmap_data,
+        post_stats,
     ))
 }
 
+/// What to do with quotes that are similar to, but not the same as, a quote in the list.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NearDuplicateChoice {
+    /// Add nothing and report the near duplicates, so that the user can choose.
+    #[default]
+    Ask,
+    Skip,
+    Keep,
+}
+
+#[derive(Default)]
+pub struct AddQuotesReport {
+    /// Quotes skipped for being already in the list, up to whitespace.
+    pub duplicates: Vec<String>,
+    /// Quotes similar to one in the list, with the similar quote.
+    pub near_duplicates: Vec<(String, String)>,
+    /// Whether nothing was added since near duplicates are waiting for a choice.
+    pub pending: bool,
+}
+
 pub async fn add_quotes(
     domain: &str,
     handle: &str,
     quotes: Vec<String>,
-) -> eyre::Result<BTreeMap<Ulid, (String, bool)>> {
+    near_duplicate_choice: NearDuplicateChoice,
+) -> eyre::Result<(BTreeMap<Ulid, Quote>, AddQuotesReport)> {
     let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
@@ -239,10 +360,44 @@ pub async fn add_quotes(
                 "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
             )
         })?;
+    let annotations = quotes_configmap
+        .as_ref()
+        .and_then(|cm| cm.metadata.annotations.clone())
+        .unwrap_or_default();
     let mut quotes_configmap_data = quotes_configmap.and_then(|cm| cm.data).unwrap_or_default();
 
-    let mut quote_id = Ulid::new();
+    let mut checker = DuplicateChecker::new(quotes_configmap_data.values().map(String::as_str));
+    let mut report = AddQuotesReport::default();
+    let mut new_quotes = Vec::new();
     for quote in quotes {
+        match checker.check(&quote) {
+            Some(Duplicate::Exact) => {
+                report.duplicates.push(quote);
+                continue;
+            }
+            Some(Duplicate::Near(similar)) => {
+                report
+                    .near_duplicates
+                    .push((quote.clone(), similar.to_string()));
+                if let NearDuplicateChoice::Ask | NearDuplicateChoice::Skip = near_duplicate_choice
+                {
+                    continue;
+                }
+            }
+            None => {}
+        }
+        checker.insert(&quote);
+        new_quotes.push(quote);
+    }
+    if let NearDuplicateChoice::Ask = near_duplicate_choice {
+        if !report.near_duplicates.is_empty() {
+            report.pending = true;
+            new_quotes.clear();
+        }
+    }
+
+    let mut quote_id = Ulid::new();
+    for quote in new_quotes {
         quote_id = quote_id.increment().unwrap_or_default();
         quotes_configmap_data.insert(quote_id.to_string(), quote);
     }
@@ -258,7 +413,13 @@ pub async fn add_quotes(
         .and_then(|cm| cm.data)
         .unwrap_or_default();
 
-    let quotes = quote_map_to_template_map(&quotes_configmap_data, &quote_dedup_configmap_data);
+    let post_stats = load_post_stats(&configmap_api, domain, handle).await?;
+
+    let quotes = quote_map_to_template_map(
+        &quotes_configmap_data,
+        &quote_dedup_configmap_data,
+        &post_stats,
+    );
 
     configmap_api
         .patch(
@@ -267,6 +428,7 @@ pub async fn add_quotes(
             &Patch::Apply(ConfigMap {
                 metadata: ObjectMeta {
                     name: Some(quotes_configmap_name.clone()),
+                    annotations: Some(annotations),
                     ..Default::default()
                 },
                 data: Some(quotes_configmap_data),
@@ -278,14 +440,14 @@ pub async fn add_quotes(
             format!("failed to patch Kubernetes ConfigMap `{quotes_configmap_name}`")
         })?;
 
-    Ok(quotes)
+    Ok((quotes, report))
 }
 
-pub async fn delete_quote(
+pub async fn delete_quotes(
     domain: &str,
     handle: &str,
-    id: Ulid,
-) -> eyre::Result<BTreeMap<Ulid, (String, bool)>> {
+    ids: &[Ulid],
+) -> eyre::Result<BTreeMap<Ulid, Quote>> {
     let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
@@ -298,9 +460,15 @@ pub async fn delete_quote(
                 "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
             )
         })?;
+    let annotations = quotes_configmap
+        .as_ref()
+        .and_then(|cm| cm.metadata.annotations.clone())
+        .unwrap_or_default();
     let mut quotes_configmap_data = quotes_configmap.and_then(|cm| cm.data).unwrap_or_default();
 
-    quotes_configmap_data.remove(&id.to_string());
+    for id in ids {
+        quotes_configmap_data.remove(&id.to_string());
+    }
 
     let quote_dedup_configmap_name = quote_dedup_configmap_name(domain, handle);
     let quote_dedup_configmap = configmap_api.get_opt(&quote_dedup_configmap_name).await
@@ -313,7 +481,13 @@ pub async fn delete_quote(
         .and_then(|cm| cm.data)
         .unwrap_or_default();
 
-    let quotes = quote_map_to_template_map(&quotes_configmap_data, &quote_dedup_configmap_data);
+    let post_stats = load_post_stats(&configmap_api, domain, handle).await?;
+
+    let quotes = quote_map_to_template_map(
+        &quotes_configmap_data,
+        &quote_dedup_configmap_data,
+        &post_stats,
+    );
 
     configmap_api
         .patch(
@@ -322,6 +496,7 @@ pub async fn delete_quote(
             &Patch::Apply(ConfigMap {
                 metadata: ObjectMeta {
                     name: Some(quotes_configmap_name.clone()),
+                    annotations: Some(annotations),
                     ..Default::default()
                 },
                 data: Some(quotes_configmap_data),
@@ -336,7 +511,163 @@ pub async fn delete_quote(
     Ok(quotes)
 }
 
-pub async fn load_cronjob(domain: &str, handle: &str) -> eyre::Result<(String, u32, bool)> {
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub enum MarkovTokenizer {
+    Character,
+    #[default]
+    Morpheme,
+}
+
+impl MarkovTokenizer {
+    pub const ALL: [Self; 2] = [Self::Character, Self::Morpheme];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Character => "character",
+            Self::Morpheme => "morpheme",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|tokenizer| tokenizer.as_str() == s)
+    }
+}
+
+/// Whether the poster generates posts with a Markov chain over the quotes instead of posting them
+/// verbatim, and the feed whose new entries it posts before quotes.
+pub struct GenerationSettings {
+    pub markov: bool,
+    pub markov_order: u32,
+    pub markov_tokenizer: MarkovTokenizer,
+    pub feed_url: String,
+    pub feed_template: String,
+}
+
+impl Default for GenerationSettings {
+    fn default() -> Self {
+        Self {
+            markov: false,
+            markov_order: 2,
+            markov_tokenizer: MarkovTokenizer::default(),
+            feed_url: String::new(),
+            feed_template: String::new(),
+        }
+    }
+}
+
+pub async fn get_generation_settings(
+    domain: &str,
+    handle: &str,
+) -> eyre::Result<GenerationSettings> {
+    let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let quotes_configmap_name = quotes_configmap_name(domain, handle);
+    let quotes_configmap = configmap_api
+        .get_opt(&quotes_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get quotes Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?;
+    let annotations = quotes_configmap
+        .and_then(|cm| cm.metadata.annotations)
+        .unwrap_or_default();
+
+    let default = GenerationSettings::default();
+    Ok(GenerationSettings {
+        markov: annotations
+            .get(GENERATION_MODE_ANNOTATION_KEY)
+            .map(String::as_str)
+            == Some("markov"),
+        markov_order: annotations
+            .get(MARKOV_ORDER_ANNOTATION_KEY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default.markov_order),
+        markov_tokenizer: annotations
+            .get(MARKOV_TOKENIZER_ANNOTATION_KEY)
+            .and_then(|v| MarkovTokenizer::parse(v))
+            .unwrap_or(default.markov_tokenizer),
+        feed_url: annotations
+            .get(FEED_URL_ANNOTATION_KEY)
+            .cloned()
+            .unwrap_or_default(),
+        feed_template: annotations
+            .get(FEED_TEMPLATE_ANNOTATION_KEY)
+            .cloned()
+            .unwrap_or_default(),
+    })
+}
+
+pub async fn save_generation_settings(
+    domain: &str,
+    handle: &str,
+    settings: &GenerationSettings,
+) -> eyre::Result<()> {
+    let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let quotes_configmap_name = quotes_configmap_name(domain, handle);
+    let quotes_configmap = configmap_api
+        .get_opt(&quotes_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get quotes Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?;
+    let mut annotations = quotes_configmap
+        .as_ref()
+        .and_then(|cm| cm.metadata.annotations.clone())
+        .unwrap_or_default();
+    let quotes_configmap_data = quotes_configmap.and_then(|cm| cm.data).unwrap_or_default();
+    annotations.insert(
+        GENERATION_MODE_ANNOTATION_KEY.to_string(),
+        if settings.markov { "markov" } else { "quote" }.to_string(),
+    );
+    annotations.insert(
+        MARKOV_ORDER_ANNOTATION_KEY.to_string(),
+        settings.markov_order.to_string(),
+    );
+    annotations.insert(
+        MARKOV_TOKENIZER_ANNOTATION_KEY.to_string(),
+        settings.markov_tokenizer.as_str().to_string(),
+    );
+    annotations.insert(
+        FEED_URL_ANNOTATION_KEY.to_string(),
+        settings.feed_url.clone(),
+    );
+    annotations.insert(
+        FEED_TEMPLATE_ANNOTATION_KEY.to_string(),
+        settings.feed_template.clone(),
+    );
+
+    configmap_api
+        .patch(
+            &quotes_configmap_name,
+            &PatchParams::apply("fediq.pbzweihander.dev").force(),
+            &Patch::Apply(ConfigMap {
+                metadata: ObjectMeta {
+                    name: Some(quotes_configmap_name.clone()),
+                    annotations: Some(annotations),
+                    ..Default::default()
+                },
+                data: Some(quotes_configmap_data),
+                ..Default::default()
+            }),
+        )
+        .await
+        .wrap_err_with(|| {
+            format!("failed to patch Kubernetes ConfigMap `{quotes_configmap_name}`")
+        })?;
+
+    Ok(())
+}
+
+pub async fn load_cronjob(domain: &str, handle: &str) -> eyre::Result<(String, u32, String, bool)> {
     let client = client().await?;
     let cronjob_api = Api::<CronJob>::default_namespaced(client);
 
@@ -348,10 +679,10 @@ pub async fn load_cronjob(domain: &str, handle: &str) -> eyre::Result<(String, u
             format!("failed to get quotes Kubernetes Cronjob for domain `{domain}` and handle `{handle}`")
         })?;
     let Some(poster_cronjob) = poster_cronjob else {
-        return Ok((String::new(), 0, false));
+        return Ok((String::new(), 0, String::new(), false));
     };
     let Some(poster_cronjob_spec) = &poster_cronjob.spec else {
-        return Ok((String::new(), 0, false));
+        return Ok((String::new(), 0, String::new(), false));
     };
 
     let dedup_duration_minutes = poster_cronjob
@@ -359,14 +690,21 @@ pub async fn load_cronjob(domain: &str, handle: &str) -> eyre::Result<(String, u
         .get(DEDUP_DURATION_MINUTES_ANNOTATION_KEY)
         .and_then(|v| v.parse::<u32>().ok())
         .unwrap_or(0);
+    let dedup_fallback = poster_cronjob
+        .annotations()
+        .get(DEDUP_FALLBACK_ANNOTATION_KEY)
+        .cloned()
+        .unwrap_or_else(|| "skip".to_string());
 
     Ok((
         poster_cronjob_spec.schedule.clone(),
         dedup_duration_minutes,
+        dedup_fallback,
         poster_cronjob_spec.suspend.unwrap_or(false),
     ))
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn save_cronjob(
     domain: &str,
     handle: &str,
@@ -374,18 +712,25 @@ pub async fn save_cronjob(
     software: &str,
     cron: &str,
     dedup_duration_minutes: u32,
+    dedup_fallback: &str,
     suspend: bool,
 ) -> eyre::Result<()> {
     let client = client().await?;
-    let cronjob_api = Api::<CronJob>::default_namespaced(client);
+    let cronjob_api = Api::<CronJob>::default_namespaced(client.clone());
+    let secret_api = Api::<Secret>::default_namespaced(client);
 
     let mut poster_cronjob_annotations = BTreeMap::<String, String>::new();
     poster_cronjob_annotations.insert(
         DEDUP_DURATION_MINUTES_ANNOTATION_KEY.to_string(),
         dedup_duration_minutes.to_string(),
     );
+    poster_cronjob_annotations.insert(
+        DEDUP_FALLBACK_ANNOTATION_KEY.to_string(),
+        dedup_fallback.to_string(),
+    );
 
     let poster_cronjob_name = poster_cronjob_name(domain, handle);
+    let secret_name = poster_config_secret_name(domain, handle);
     let poster_cronjob = CronJob {
         metadata: ObjectMeta {
             name: Some(poster_cronjob_name.clone()),
@@ -418,10 +763,19 @@ pub async fn save_cronjob(
                                         value: Some(domain.to_string()),
                                         value_from: None,
                                     },
+                                    // Referenced, so that scheduled posts copying this template
+                                    // pick up a new token too.
                                     EnvVar {
                                         name: "ACCESS_TOKEN".to_string(),
-                                        value: Some(access_token.to_string()),
-                                        value_from: None,
+                                        value: None,
+                                        value_from: Some(EnvVarSource {
+                                            secret_key_ref: Some(SecretKeySelector {
+                                                name: secret_name.clone(),
+                                                key: "ACCESS_TOKEN".to_string(),
+                                                optional: None,
+                                            }),
+                                            ..Default::default()
+                                        }),
                                     },
                                     EnvVar {
                                         name: "SOFTWARE".to_string(),
@@ -443,6 +797,16 @@ pub async fn save_cronjob(
                                         value: Some(dedup_duration_minutes.to_string()),
                                         value_from: None,
                                     },
+                                    EnvVar {
+                                        name: "DEDUP_FALLBACK".to_string(),
+                                        value: Some(dedup_fallback.to_string()),
+                                        value_from: None,
+                                    },
+                                    EnvVar {
+                                        name: "POST_HISTORY_CONFIGMAP_NAME".to_string(),
+                                        value: Some(post_history_configmap_name(domain, handle)),
+                                        value_from: None,
+                                    },
                                 ]),
                                 ..Default::default()
                             }],
@@ -457,7 +821,7 @@ pub async fn save_cronjob(
         ..Default::default()
     };
 
-    cronjob_api
+    let poster_cronjob = cronjob_api
         .patch(
             &poster_cronjob_name,
             &PatchParams::apply("fediq.pbzweihander.dev").force(),
@@ -466,177 +830,677 @@ pub async fn save_cronjob(
         .await
         .wrap_err_with(|| format!("failed to patch Kubernetes CronJob `{poster_cronjob_name}`"))?;
 
+    let mut data = BTreeMap::new();
+    data.insert(
+        "ACCESS_TOKEN".to_string(),
+        ByteString(access_token.as_bytes().to_vec()),
+    );
+    secret_api
+        .patch(
+            &secret_name,
+            &PatchParams::apply("fediq.pbzweihander.dev").force(),
+            &Patch::Apply(Secret {
+                metadata: ObjectMeta {
+                    name: Some(secret_name.clone()),
+                    owner_references: poster_cronjob.owner_ref(&()).map(|r| vec![r]),
+                    ..Default::default()
+                },
+                data: Some(data),
+                ..Default::default()
+            }),
+        )
+        .await
+        .wrap_err_with(|| format!("failed to patch Kubernetes Secret `{secret_name}`"))?;
+
     Ok(())
 }
 
-fn deserialize_reply_map(
-    data: BTreeMap<String, String>,
-) -> BTreeMap<String, BTreeMap<Ulid, String>> {
-    data.get("data")
-        .and_then(|v| serde_json::from_str::<BTreeMap<String, BTreeMap<Ulid, String>>>(v).ok())
-        .unwrap_or_default()
+#[derive(Deserialize)]
+pub struct PostHistoryEntry {
+    #[serde(with = "time::serde::rfc3339")]
+    pub posted_at: OffsetDateTime,
+    pub text: String,
+    pub url: Option<String>,
+    pub error: Option<String>,
 }
 
-fn serialize_reply_map(
-    reply_map: &BTreeMap<String, BTreeMap<Ulid, String>>,
-) -> BTreeMap<String, String> {
-    let data = serde_json::to_string(reply_map).unwrap();
-    let mut output = BTreeMap::new();
-    output.insert("data".to_string(), data);
-    output
+#[derive(Deserialize)]
+pub struct PostPreview {
+    #[serde(with = "time::serde::rfc3339")]
+    pub generated_at: OffsetDateTime,
+    pub text: String,
+    pub payload: String,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub dedup_until: Option<OffsetDateTime>,
 }
 
-pub async fn load_replies(
-    domain: &str,
-    handle: &str,
-) -> eyre::Result<BTreeMap<String, BTreeMap<Ulid, String>>> {
+#[derive(Default)]
+pub struct PostHistory {
+    pub entries: Vec<PostHistoryEntry>,
+    pub preview: Option<PostPreview>,
+}
+
+pub async fn load_post_history(domain: &str, handle: &str) -> eyre::Result<PostHistory> {
     let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
-    let replies_configmap_name = replies_configmap_name(domain, handle);
-    let replies_configmap = configmap_api
-        .get_opt(&replies_configmap_name)
+    let post_history_configmap_name = post_history_configmap_name(domain, handle);
+    let post_history_configmap = configmap_api
+        .get_opt(&post_history_configmap_name)
         .await
         .wrap_err_with(|| {
             format!(
-                "failed to get replies Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+                "failed to get post history Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
             )
         })?;
-    let replies_configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
-
-    let reply_map = deserialize_reply_map(replies_configmap_data);
 
-    Ok(reply_map)
+    let data = post_history_configmap
+        .and_then(|cm| cm.data)
+        .unwrap_or_default();
+    Ok(PostHistory {
+        entries: data
+            .get("data")
+            .and_then(|v| serde_json::from_str::<Vec<PostHistoryEntry>>(v).ok())
+            .unwrap_or_default(),
+        preview: data
+            .get("preview")
+            .and_then(|v| serde_json::from_str::<PostPreview>(v).ok()),
+    })
 }
 
-pub async fn add_replies(
-    domain: &str,
-    handle: &str,
-    keyword: String,
-    replies: Vec<String>,
-) -> eyre::Result<BTreeMap<String, BTreeMap<Ulid, String>>> {
-    let client = client().await?;
-    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+#[derive(Default, Deserialize)]
+pub struct QuotePostStats {
+    pub count: u64,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub last_posted_at: Option<OffsetDateTime>,
+}
 
-    let replies_configmap_name = replies_configmap_name(domain, handle);
-    let replies_configmap = configmap_api
-        .get_opt(&replies_configmap_name)
-        .await
-        .wrap_err_with(|| {
-            format!(
-                "failed to get replies Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
-            )
-        })?;
-    let annotations = replies_configmap
-        .as_ref()
-        .and_then(|cm| cm.metadata.annotations.clone())
-        .unwrap_or_else(|| {
-            let mut annotation = BTreeMap::new();
-            annotation.insert(DICE_FEATURE_ANNOTATION_KEY.to_string(), "false".to_string());
-            annotation
-        });
-    let replies_configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
+#[derive(Default, Deserialize)]
+struct PostStats {
+    #[serde(default)]
+    quotes: BTreeMap<Ulid, QuotePostStats>,
+    #[serde(default)]
+    daily: BTreeMap<String, u64>,
+}
 
-    let mut reply_map = deserialize_reply_map(replies_configmap_data);
+#[derive(Default, Deserialize)]
+pub struct Engagement {
+    pub replies: u64,
+    pub favourites: u64,
+    pub reblogs: u64,
+}
 
-    reply_map
-        .entry(keyword)
-        .or_default()
-        .extend(replies.into_iter().map({
-            let mut reply_id = Ulid::new();
-            move |reply| {
-                reply_id = reply_id.increment().unwrap_or_default();
-                (reply_id, reply)
-            }
-        }));
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct StreamingStats {
+    daily_replies: BTreeMap<String, u64>,
+    triggers: BTreeMap<String, u64>,
+    quotes: BTreeMap<Ulid, Engagement>,
+}
 
-    let data_to_save = serialize_reply_map(&reply_map);
+pub struct QuoteStats {
+    pub text: String,
+    pub posts: QuotePostStats,
+    pub engagement: Engagement,
+}
 
-    configmap_api
-        .patch(
-            &replies_configmap_name,
-            &PatchParams::apply("fediq.pbzweihander.dev").force(),
-            &Patch::Apply(ConfigMap {
-                metadata: ObjectMeta {
-                    name: Some(replies_configmap_name.clone()),
-                    annotations: Some(annotations),
-                    ..Default::default()
-                },
-                data: Some(data_to_save),
-                ..Default::default()
-            }),
-        )
-        .await
-        .wrap_err_with(|| {
-            format!("failed to patch Kubernetes ConfigMap `{replies_configmap_name}`")
-        })?;
+#[derive(Default)]
+pub struct DailyStats {
+    /// UTC date, as `YYYY-MM-DD`.
+    pub date: String,
+    pub posts: u64,
+    pub replies: u64,
+}
 
-    Ok(reply_map)
+#[derive(Default)]
+pub struct Stats {
+    /// Every quote, most posted first.
+    pub quotes: Vec<QuoteStats>,
+    /// Newest first.
+    pub daily: Vec<DailyStats>,
+    /// Most triggered first.
+    pub triggers: Vec<(String, u64)>,
 }
 
-pub async fn delete_reply(
+/// Post stats the poster keeps in the post history ConfigMap.
+async fn load_post_stats(
+    configmap_api: &Api<ConfigMap>,
     domain: &str,
     handle: &str,
-    keyword: String,
-    id: Ulid,
-) -> eyre::Result<BTreeMap<String, BTreeMap<Ulid, String>>> {
-    let client = client().await?;
+) -> eyre::Result<PostStats> {
+    let post_history_configmap_name = post_history_configmap_name(domain, handle);
+    Ok(configmap_api
+        .get_opt(&post_history_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!("failed to get Kubernetes ConfigMap `{post_history_configmap_name}`")
+        })?
+        .and_then(|cm| cm.data)
+        .and_then(|data| {
+            data.get("stats")
+                .and_then(|v| serde_json::from_str::<PostStats>(v).ok())
+        })
+        .unwrap_or_default())
+}
+
+/// Gathers the counters kept by the poster in the post history ConfigMap and by the streaming
+/// bot in its state ConfigMap.
+pub async fn load_stats(domain: &str, handle: &str) -> eyre::Result<Stats> {
+    let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
-    let replies_configmap_name = replies_configmap_name(domain, handle);
-    let replies_configmap = configmap_api
-        .get_opt(&replies_configmap_name)
+    let mut post_stats = load_post_stats(&configmap_api, domain, handle).await?;
+    let state_configmap_name = streaming_state_configmap_name(domain, handle);
+    let mut streaming_stats = configmap_api
+        .get_opt(&state_configmap_name)
+        .await
+        .wrap_err_with(|| format!("failed to get Kubernetes ConfigMap `{state_configmap_name}`"))?
+        .and_then(|cm| cm.data)
+        .and_then(|data| {
+            data.get("stats")
+                .and_then(|v| serde_json::from_str::<StreamingStats>(v).ok())
+        })
+        .unwrap_or_default();
+
+    let mut quotes = load_quotes_with_post_stats(&configmap_api, domain, handle, &post_stats)
+        .await?
+        .into_iter()
+        .map(|(id, quote)| QuoteStats {
+            text: quote.text,
+            posts: post_stats.quotes.remove(&id).unwrap_or_default(),
+            engagement: streaming_stats.quotes.remove(&id).unwrap_or_default(),
+        })
+        .collect::<Vec<_>>();
+    quotes.sort_by(|a, b| {
+        b.posts
+            .count
+            .cmp(&a.posts.count)
+            .then_with(|| b.posts.last_posted_at.cmp(&a.posts.last_posted_at))
+    });
+
+    let mut daily = BTreeMap::<String, DailyStats>::new();
+    for (date, posts) in post_stats.daily {
+        daily.entry(date).or_default().posts = posts;
+    }
+    for (date, replies) in streaming_stats.daily_replies {
+        daily.entry(date).or_default().replies = replies;
+    }
+    let daily = daily
+        .into_iter()
+        .rev()
+        .map(|(date, stats)| DailyStats { date, ..stats })
+        .collect();
+
+    let mut triggers = streaming_stats.triggers.into_iter().collect::<Vec<_>>();
+    triggers.sort_by(|(_, a), (_, b)| b.cmp(a));
+
+    Ok(Stats {
+        quotes,
+        daily,
+        triggers,
+    })
+}
+
+async fn get_poster_cronjob(
+    cronjob_api: &Api<CronJob>,
+    domain: &str,
+    handle: &str,
+) -> eyre::Result<CronJob> {
+    let poster_cronjob_name = poster_cronjob_name(domain, handle);
+    cronjob_api
+        .get_opt(&poster_cronjob_name)
+        .await
+        .wrap_err_with(|| {
+            format!("failed to get quotes Kubernetes Cronjob for domain `{domain}` and handle `{handle}`")
+        })?
+        .ok_or_else(|| {
+            eyre::eyre!("Kubernetes CronJob `{poster_cronjob_name}` not found. configure schedule first")
+        })
+}
+
+fn poster_job_template_with_env(
+    poster_cronjob: &CronJob,
+    env: Vec<EnvVar>,
+) -> eyre::Result<JobTemplateSpec> {
+    let mut job_template = poster_cronjob
+        .spec
+        .as_ref()
+        .map(|spec| spec.job_template.clone())
+        .ok_or_else(|| eyre::eyre!("poster Kubernetes CronJob has no spec"))?;
+    let container = job_template
+        .spec
+        .as_mut()
+        .and_then(|spec| spec.template.spec.as_mut())
+        .and_then(|spec| spec.containers.first_mut())
+        .ok_or_else(|| eyre::eyre!("poster Kubernetes CronJob has no container"))?;
+    container.env.get_or_insert_with(Vec::new).extend(env);
+    Ok(job_template)
+}
+
+async fn create_poster_job(
+    domain: &str,
+    handle: &str,
+    name_suffix: &str,
+    env: Vec<EnvVar>,
+) -> eyre::Result<()> {
+    let client = client().await?;
+    let cronjob_api = Api::<CronJob>::default_namespaced(client.clone());
+    let job_api = Api::<Job>::default_namespaced(client);
+
+    let poster_cronjob = get_poster_cronjob(&cronjob_api, domain, handle).await?;
+    let job_template = poster_job_template_with_env(&poster_cronjob, env)?;
+
+    let mut job_annotations = job_template
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.annotations.clone())
+        .unwrap_or_default();
+    job_annotations.insert(
+        "cronjob.kubernetes.io/instantiate".to_string(),
+        "manual".to_string(),
+    );
+    let job = Job {
+        metadata: ObjectMeta {
+            generate_name: Some(format!("{}-{name_suffix}-", poster_cronjob.name_any())),
+            labels: job_template
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.labels.clone()),
+            annotations: Some(job_annotations),
+            owner_references: poster_cronjob.controller_owner_ref(&()).map(|r| vec![r]),
+            ..Default::default()
+        },
+        spec: job_template.spec,
+        ..Default::default()
+    };
+
+    job_api
+        .create(&PostParams::default(), &job)
         .await
         .wrap_err_with(|| {
             format!(
-                "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+                "failed to create Kubernetes Job from CronJob `{}`",
+                poster_cronjob.name_any()
             )
         })?;
-    let annotations = replies_configmap
-        .as_ref()
-        .and_then(|cm| cm.metadata.annotations.clone())
-        .unwrap_or_else(|| {
-            let mut annotation = BTreeMap::new();
-            annotation.insert(DICE_FEATURE_ANNOTATION_KEY.to_string(), "false".to_string());
-            annotation
-        });
-    let replies_configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
 
-    let mut reply_map = deserialize_reply_map(replies_configmap_data);
+    Ok(())
+}
 
-    reply_map.entry(keyword).and_modify(|m| {
-        m.remove(&id);
-    });
+pub async fn post_now(domain: &str, handle: &str) -> eyre::Result<()> {
+    create_poster_job(domain, handle, "manual", Vec::new()).await
+}
 
-    let data_to_save = serialize_reply_map(&reply_map);
+pub async fn preview_post(domain: &str, handle: &str) -> eyre::Result<()> {
+    create_poster_job(
+        domain,
+        handle,
+        "preview",
+        vec![EnvVar {
+            name: "DRY_RUN".to_string(),
+            value: Some("true".to_string()),
+            ..Default::default()
+        }],
+    )
+    .await
+}
 
-    configmap_api
+pub struct ScheduledPost {
+    pub id: Ulid,
+    pub scheduled_at: OffsetDateTime,
+    pub text: Option<String>,
+}
+
+pub async fn load_scheduled_posts(domain: &str, handle: &str) -> eyre::Result<Vec<ScheduledPost>> {
+    let client = client().await?;
+    let cronjob_api = Api::<CronJob>::default_namespaced(client.clone());
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let poster_cronjob_name = poster_cronjob_name(domain, handle);
+    let cronjobs = cronjob_api
+        .list(&ListParams::default().labels(&format!(
+            "{POSTER_CRONJOB_LABEL_KEY}={poster_cronjob_name}"
+        )))
+        .await
+        .wrap_err_with(|| {
+            format!("failed to list scheduled post Kubernetes CronJobs for domain `{domain}` and handle `{handle}`")
+        })?;
+
+    let quotes_configmap_name = quotes_configmap_name(domain, handle);
+    let quotes_configmap_data = configmap_api
+        .get_opt(&quotes_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get quotes Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?
+        .and_then(|cm| cm.data)
+        .unwrap_or_default();
+
+    let mut scheduled_posts = cronjobs
+        .into_iter()
+        .filter_map(|cronjob| {
+            let annotations = cronjob.annotations();
+            let id = Ulid::from_string(annotations.get(SCHEDULED_POST_ID_ANNOTATION_KEY)?).ok()?;
+            let scheduled_at = OffsetDateTime::parse(
+                annotations.get(SCHEDULED_AT_ANNOTATION_KEY)?,
+                &time::format_description::well_known::Rfc3339,
+            )
+            .ok()?;
+            let quote_id = annotations
+                .get(SCHEDULED_QUOTE_ID_ANNOTATION_KEY)
+                .and_then(|v| Ulid::from_string(v).ok());
+            let text = if let Some(quote_id) = quote_id {
+                quotes_configmap_data.get(&quote_id.to_string()).cloned()
+            } else {
+                annotations.get(SCHEDULED_TEXT_ANNOTATION_KEY).cloned()
+            };
+            Some(ScheduledPost {
+                id,
+                scheduled_at,
+                text,
+            })
+        })
+        .collect::<Vec<_>>();
+    scheduled_posts.sort_by_key(|post| post.scheduled_at);
+
+    Ok(scheduled_posts)
+}
+
+pub async fn schedule_post(
+    domain: &str,
+    handle: &str,
+    scheduled_at: OffsetDateTime,
+    quote_id: Option<Ulid>,
+    text: Option<String>,
+) -> eyre::Result<()> {
+    let client = client().await?;
+    let cronjob_api = Api::<CronJob>::default_namespaced(client);
+
+    let poster_cronjob = get_poster_cronjob(&cronjob_api, domain, handle).await?;
+
+    let id = Ulid::new();
+    let scheduled_post_cronjob_name = scheduled_post_cronjob_name(id);
+    let scheduled_at = scheduled_at.to_offset(time::UtcOffset::UTC);
+
+    let mut annotations = BTreeMap::<String, String>::new();
+    annotations.insert(SCHEDULED_POST_ID_ANNOTATION_KEY.to_string(), id.to_string());
+    let scheduled_at_rfc3339 = scheduled_at
+        .format(&time::format_description::well_known::Rfc3339)
+        .wrap_err("failed to format scheduled time")?;
+    annotations.insert(
+        SCHEDULED_AT_ANNOTATION_KEY.to_string(),
+        scheduled_at_rfc3339.clone(),
+    );
+    // The CronJob repeats yearly, so the poster uses this to skip runs long past the schedule.
+    let mut env = vec![
+        EnvVar {
+            name: "SCHEDULED_CRONJOB_NAME".to_string(),
+            value: Some(scheduled_post_cronjob_name.clone()),
+            value_from: None,
+        },
+        EnvVar {
+            name: "SCHEDULED_AT".to_string(),
+            value: Some(scheduled_at_rfc3339),
+            value_from: None,
+        },
+    ];
+    if let Some(quote_id) = quote_id {
+        annotations.insert(
+            SCHEDULED_QUOTE_ID_ANNOTATION_KEY.to_string(),
+            quote_id.to_string(),
+        );
+        env.push(EnvVar {
+            name: "QUOTE_ID".to_string(),
+            value: Some(quote_id.to_string()),
+            value_from: None,
+        });
+    } else if let Some(text) = text {
+        annotations.insert(SCHEDULED_TEXT_ANNOTATION_KEY.to_string(), text.clone());
+        env.push(EnvVar {
+            name: "QUOTE_TEXT".to_string(),
+            value: Some(text),
+            value_from: None,
+        });
+    } else {
+        return Err(eyre::eyre!("either quote ID or text is required"));
+    }
+
+    let mut labels = BTreeMap::<String, String>::new();
+    labels.insert(
+        POSTER_CRONJOB_LABEL_KEY.to_string(),
+        poster_cronjob.name_any(),
+    );
+
+    let scheduled_post_cronjob = CronJob {
+        metadata: ObjectMeta {
+            name: Some(scheduled_post_cronjob_name.clone()),
+            labels: Some(labels),
+            annotations: Some(annotations),
+            owner_references: poster_cronjob.owner_ref(&()).map(|r| vec![r]),
+            ..Default::default()
+        },
+        spec: Some(CronJobSpec {
+            schedule: format!(
+                "{} {} {} {} *",
+                scheduled_at.minute(),
+                scheduled_at.hour(),
+                scheduled_at.day(),
+                u8::from(scheduled_at.month()),
+            ),
+            time_zone: Some("Etc/UTC".to_string()),
+            starting_deadline_seconds: Some(360),
+            successful_jobs_history_limit: Some(0),
+            failed_jobs_history_limit: Some(1),
+            job_template: poster_job_template_with_env(&poster_cronjob, env)?,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    cronjob_api
         .patch(
-            &replies_configmap_name,
+            &scheduled_post_cronjob_name,
             &PatchParams::apply("fediq.pbzweihander.dev").force(),
-            &Patch::Apply(ConfigMap {
-                metadata: ObjectMeta {
-                    name: Some(replies_configmap_name.clone()),
-                    annotations: Some(annotations),
-                    ..Default::default()
-                },
-                data: Some(data_to_save),
-                ..Default::default()
-            }),
+            &Patch::Apply(scheduled_post_cronjob),
         )
         .await
         .wrap_err_with(|| {
-            format!("failed to patch Kubernetes ConfigMap `{replies_configmap_name}`")
+            format!("failed to patch Kubernetes CronJob `{scheduled_post_cronjob_name}`")
+        })?;
+
+    Ok(())
+}
+
+pub async fn delete_scheduled_post(domain: &str, handle: &str, id: Ulid) -> eyre::Result<()> {
+    let client = client().await?;
+    let cronjob_api = Api::<CronJob>::default_namespaced(client);
+
+    let scheduled_post_cronjob_name = scheduled_post_cronjob_name(id);
+    let scheduled_post_cronjob = cronjob_api
+        .get_opt(&scheduled_post_cronjob_name)
+        .await
+        .wrap_err_with(|| {
+            format!("failed to get Kubernetes CronJob `{scheduled_post_cronjob_name}`")
         })?;
+    let Some(scheduled_post_cronjob) = scheduled_post_cronjob else {
+        return Ok(());
+    };
+    if scheduled_post_cronjob
+        .labels()
+        .get(POSTER_CRONJOB_LABEL_KEY)
+        != Some(&poster_cronjob_name(domain, handle))
+    {
+        return Err(eyre::eyre!(
+            "scheduled post `{id}` does not belong to domain `{domain}` and handle `{handle}`"
+        ));
+    }
+
+    cronjob_api
+        .delete(&scheduled_post_cronjob_name, &Default::default())
+        .await
+        .wrap_err_with(|| {
+            format!("failed to delete Kubernetes CronJob `{scheduled_post_cronjob_name}`")
+        })?;
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchType {
+    #[default]
+    Substring,
+    WholeWord,
+    CaseInsensitive,
+    Regex,
+}
+
+impl MatchType {
+    pub const ALL: [Self; 4] = [
+        Self::Substring,
+        Self::WholeWord,
+        Self::CaseInsensitive,
+        Self::Regex,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Substring => "substring",
+            Self::WholeWord => "whole_word",
+            Self::CaseInsensitive => "case_insensitive",
+            Self::Regex => "regex",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|match_type| match_type.as_str() == s)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+struct KeywordRule {
+    keyword: String,
+    #[serde(default)]
+    match_type: MatchType,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reaction: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    react_only: bool,
+}
+
+/// A keyword and its replies. Rules are kept in priority order, highest first.
+pub struct ReplyRule {
+    pub keyword: String,
+    pub match_type: MatchType,
+    /// Emoji to react to the mention with, in addition to replying unless `react_only` is set.
+    pub reaction: Option<String>,
+    pub react_only: bool,
+    pub replies: BTreeMap<Ulid, String>,
+}
+
+fn deserialize_reply_map(data: BTreeMap<String, String>) -> Vec<ReplyRule> {
+    let mut reply_map = data
+        .get("data")
+        .and_then(|v| serde_json::from_str::<BTreeMap<String, BTreeMap<Ulid, String>>>(v).ok())
+        .unwrap_or_default();
+    let keyword_rules = data
+        .get("rules")
+        .and_then(|v| serde_json::from_str::<Vec<KeywordRule>>(v).ok())
+        .unwrap_or_default();
+
+    let mut rules = keyword_rules
+        .into_iter()
+        .filter_map(|rule| {
+            let replies = reply_map.remove(&rule.keyword)?;
+            Some(ReplyRule {
+                keyword: rule.keyword,
+                match_type: rule.match_type,
+                reaction: rule.reaction,
+                react_only: rule.react_only,
+                replies,
+            })
+        })
+        .collect::<Vec<_>>();
+    // Keywords saved before rules existed keep their old priority, which was reverse
+    // alphabetical.
+    rules.extend(
+        reply_map
+            .into_iter()
+            .rev()
+            .map(|(keyword, replies)| ReplyRule {
+                keyword,
+                match_type: MatchType::Substring,
+                reaction: None,
+                react_only: false,
+                replies,
+            }),
+    );
+    rules
+}
+
+fn serialize_reply_map(rules: &[ReplyRule]) -> BTreeMap<String, String> {
+    let reply_map = rules
+        .iter()
+        .map(|rule| (rule.keyword.clone(), rule.replies.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let keyword_rules = rules
+        .iter()
+        .map(|rule| KeywordRule {
+            keyword: rule.keyword.clone(),
+            match_type: rule.match_type,
+            reaction: rule.reaction.clone(),
+            react_only: rule.react_only,
+        })
+        .collect::<Vec<_>>();
+    let mut output = BTreeMap::new();
+    output.insert(
+        "data".to_string(),
+        serde_json::to_string(&reply_map).unwrap(),
+    );
+    output.insert(
+        "rules".to_string(),
+        serde_json::to_string(&keyword_rules).unwrap(),
+    );
+    output
+}
+
+/// Checks that `keyword` can be matched with `match_type`, i.e. that regexes compile.
+pub fn validate_keyword(keyword: &str, match_type: MatchType) -> Result<(), regex::Error> {
+    if match_type == MatchType::Regex {
+        regex::Regex::new(keyword)?;
+    }
+    Ok(())
+}
+
+pub async fn load_replies(domain: &str, handle: &str) -> eyre::Result<Vec<ReplyRule>> {
+    let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let replies_configmap_name = replies_configmap_name(domain, handle);
+    let replies_configmap = configmap_api
+        .get_opt(&replies_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get replies Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?;
+    let replies_configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
+
+    let reply_map = deserialize_reply_map(replies_configmap_data);
 
     Ok(reply_map)
 }
 
-pub async fn delete_reply_all(
+async fn modify_replies(
     domain: &str,
     handle: &str,
-    keyword: String,
-) -> eyre::Result<BTreeMap<String, BTreeMap<Ulid, String>>> {
+    f: impl FnOnce(&mut Vec<ReplyRule>),
+) -> eyre::Result<Vec<ReplyRule>> {
     let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
@@ -646,7 +1510,7 @@ pub async fn delete_reply_all(
         .await
         .wrap_err_with(|| {
             format!(
-                "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+                "failed to get replies Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
             )
         })?;
     let annotations = replies_configmap
@@ -654,16 +1518,16 @@ pub async fn delete_reply_all(
         .and_then(|cm| cm.metadata.annotations.clone())
         .unwrap_or_else(|| {
             let mut annotation = BTreeMap::new();
-            annotation.insert(DICE_FEATURE_ANNOTATION_KEY.to_string(), "false".to_string());
+            annotation.insert(Responder::Dice.annotation_key(), "false".to_string());
             annotation
         });
-    let replies_configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
+    let mut replies_configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
 
-    let mut reply_map = deserialize_reply_map(replies_configmap_data);
+    let mut reply_map = deserialize_reply_map(replies_configmap_data.clone());
 
-    reply_map.remove(&keyword);
+    f(&mut reply_map);
 
-    let data_to_save = serialize_reply_map(&reply_map);
+    replies_configmap_data.extend(serialize_reply_map(&reply_map));
 
     configmap_api
         .patch(
@@ -675,7 +1539,7 @@ pub async fn delete_reply_all(
                     annotations: Some(annotations),
                     ..Default::default()
                 },
-                data: Some(data_to_save),
+                data: Some(replies_configmap_data),
                 ..Default::default()
             }),
         )
@@ -687,8 +1551,104 @@ pub async fn delete_reply_all(
     Ok(reply_map)
 }
 
+pub async fn add_replies(
+    domain: &str,
+    handle: &str,
+    keyword: String,
+    match_type: MatchType,
+    replies: Vec<String>,
+) -> eyre::Result<Vec<ReplyRule>> {
+    modify_replies(domain, handle, move |reply_map| {
+        let index = if let Some(index) = reply_map.iter().position(|rule| rule.keyword == keyword) {
+            index
+        } else {
+            reply_map.push(ReplyRule {
+                keyword,
+                match_type,
+                reaction: None,
+                react_only: false,
+                replies: BTreeMap::new(),
+            });
+            reply_map.len() - 1
+        };
+        let rule = &mut reply_map[index];
+        rule.match_type = match_type;
+        rule.replies.extend(replies.into_iter().map({
+            let mut reply_id = Ulid::new();
+            move |reply| {
+                reply_id = reply_id.increment().unwrap_or_default();
+                (reply_id, reply)
+            }
+        }));
+    })
+    .await
+}
+
+pub async fn delete_reply(
+    domain: &str,
+    handle: &str,
+    keyword: String,
+    id: Ulid,
+) -> eyre::Result<Vec<ReplyRule>> {
+    modify_replies(domain, handle, move |reply_map| {
+        if let Some(rule) = reply_map.iter_mut().find(|rule| rule.keyword == keyword) {
+            rule.replies.remove(&id);
+        }
+    })
+    .await
+}
+
+pub async fn delete_reply_all(
+    domain: &str,
+    handle: &str,
+    keyword: String,
+) -> eyre::Result<Vec<ReplyRule>> {
+    modify_replies(domain, handle, move |reply_map| {
+        reply_map.retain(|rule| rule.keyword != keyword);
+    })
+    .await
+}
+
+pub async fn update_reply_rule(
+    domain: &str,
+    handle: &str,
+    keyword: String,
+    match_type: MatchType,
+    reaction: Option<String>,
+    react_only: bool,
+) -> eyre::Result<Vec<ReplyRule>> {
+    modify_replies(domain, handle, move |reply_map| {
+        if let Some(rule) = reply_map.iter_mut().find(|rule| rule.keyword == keyword) {
+            rule.match_type = match_type;
+            rule.reaction = reaction;
+            rule.react_only = react_only;
+        }
+    })
+    .await
+}
+
+pub async fn move_reply_rule(
+    domain: &str,
+    handle: &str,
+    keyword: String,
+    up: bool,
+) -> eyre::Result<Vec<ReplyRule>> {
+    modify_replies(domain, handle, move |reply_map| {
+        let Some(index) = reply_map.iter().position(|rule| rule.keyword == keyword) else {
+            return;
+        };
+        if up && index > 0 {
+            reply_map.swap(index, index - 1);
+        } else if !up && index + 1 < reply_map.len() {
+            reply_map.swap(index, index + 1);
+        }
+    })
+    .await
+}
+
 pub async fn get_reply_enabled(domain: &str, handle: &str) -> eyre::Result<bool> {
     let client = client().await?;
+    let secret_api = Api::<Secret>::default_namespaced(client.clone());
     let deployment_api = Api::<Deployment>::default_namespaced(client);
 
     let deployment_name = streaming_deployment_name(domain, handle);
@@ -696,8 +1656,16 @@ pub async fn get_reply_enabled(domain: &str, handle: &str) -> eyre::Result<bool>
         .get_opt(&deployment_name)
         .await
         .wrap_err_with(|| format!("failed to get Kubernetes Deployment `{deployment_name}`"))?;
+    if deployment.is_some() || !CONFIG.streaming_multiplexed {
+        return Ok(deployment.is_some());
+    }
 
-    Ok(deployment.is_some())
+    let secret_name = streaming_config_secret_name(domain, handle);
+    let secret = secret_api
+        .get_opt(&secret_name)
+        .await
+        .wrap_err_with(|| format!("failed to get Kubernetes Secret `{secret_name}`"))?;
+    Ok(secret.is_some_and(|secret| secret.labels().contains_key(STREAMING_CONFIG_LABEL_KEY)))
 }
 
 pub async fn enable_reply(
@@ -707,9 +1675,79 @@ pub async fn enable_reply(
     software: &str,
 ) -> eyre::Result<()> {
     let client = client().await?;
+    let secret_api = Api::<Secret>::default_namespaced(client.clone());
     let deployment_api = Api::<Deployment>::default_namespaced(client);
 
+    // The streaming bot watches this Secret, so a new token applies without restarting it.
+    let secret_name = streaming_config_secret_name(domain, handle);
+    let mut data = BTreeMap::new();
+    data.insert("DOMAIN".to_string(), ByteString(domain.as_bytes().to_vec()));
+    data.insert(
+        "ACCESS_TOKEN".to_string(),
+        ByteString(access_token.as_bytes().to_vec()),
+    );
+    data.insert(
+        "SOFTWARE".to_string(),
+        ByteString(software.as_bytes().to_vec()),
+    );
+    data.insert(
+        "REPLIES_CONFIGMAP_NAME".to_string(),
+        ByteString(replies_configmap_name(domain, handle).into_bytes()),
+    );
+    data.insert(
+        "STREAMING_STATE_CONFIGMAP_NAME".to_string(),
+        ByteString(streaming_state_configmap_name(domain, handle).into_bytes()),
+    );
+    data.insert(
+        "POST_HISTORY_CONFIGMAP_NAME".to_string(),
+        ByteString(post_history_configmap_name(domain, handle).into_bytes()),
+    );
+    // Recorded to tell users whose token lacks newer scopes to log in again.
+    if software == "mastodon" {
+        data.insert(
+            "SCOPES".to_string(),
+            ByteString(MASTODON_SCOPES.as_bytes().to_vec()),
+        );
+    }
+    // The multiplexed streaming process picks up every Secret with this label.
+    let mut labels = BTreeMap::new();
+    if CONFIG.streaming_multiplexed {
+        labels.insert(STREAMING_CONFIG_LABEL_KEY.to_string(), "true".to_string());
+    }
+    secret_api
+        .patch(
+            &secret_name,
+            &PatchParams::apply("fediq.pbzweihander.dev").force(),
+            &Patch::Apply(Secret {
+                metadata: ObjectMeta {
+                    name: Some(secret_name.clone()),
+                    labels: Some(labels),
+                    ..Default::default()
+                },
+                data: Some(data),
+                ..Default::default()
+            }),
+        )
+        .await
+        .wrap_err_with(|| format!("failed to patch Kubernetes Secret `{secret_name}`"))?;
+
     let deployment_name = streaming_deployment_name(domain, handle);
+    if CONFIG.streaming_multiplexed {
+        // Remove the Deployment from before switching to multiplexed streaming, so that the bot
+        // does not reply twice.
+        if let Err(error) = deployment_api
+            .delete(&deployment_name, &Default::default())
+            .await
+        {
+            if !is_not_found(&error) {
+                return Err(eyre::Report::new(error).wrap_err(format!(
+                    "failed to delete Kubernetes Deployment `{deployment_name}`"
+                )));
+            }
+        }
+        return Ok(());
+    }
+
     let mut pod_labels = BTreeMap::new();
     pod_labels.insert(
         "app.kubernetes.io/name".to_string(),
@@ -743,23 +1781,23 @@ pub async fn enable_reply(
                         command: Some(vec!["fediq-streaming".to_string()]),
                         env: Some(vec![
                             EnvVar {
-                                name: "DOMAIN".to_string(),
-                                value: Some(domain.to_string()),
+                                name: "REPLIES_CONFIGMAP_NAME".to_string(),
+                                value: Some(replies_configmap_name(domain, handle)),
                                 value_from: None,
                             },
                             EnvVar {
-                                name: "ACCESS_TOKEN".to_string(),
-                                value: Some(access_token.to_string()),
+                                name: "STREAMING_STATE_CONFIGMAP_NAME".to_string(),
+                                value: Some(streaming_state_configmap_name(domain, handle)),
                                 value_from: None,
                             },
                             EnvVar {
-                                name: "SOFTWARE".to_string(),
-                                value: Some(software.to_string()),
+                                name: "POST_HISTORY_CONFIGMAP_NAME".to_string(),
+                                value: Some(post_history_configmap_name(domain, handle)),
                                 value_from: None,
                             },
                             EnvVar {
-                                name: "REPLIES_CONFIGMAP_NAME".to_string(),
-                                value: Some(replies_configmap_name(domain, handle)),
+                                name: "STREAMING_CONFIG_SECRET_NAME".to_string(),
+                                value: Some(secret_name.clone()),
                                 value_from: None,
                             },
                         ]),
@@ -787,6 +1825,7 @@ pub async fn enable_reply(
 
 pub async fn disable_reply(domain: &str, handle: &str) -> eyre::Result<()> {
     let client = client().await?;
+    let secret_api = Api::<Secret>::default_namespaced(client.clone());
     let deployment_api = Api::<Deployment>::default_namespaced(client);
 
     let deployment_name = streaming_deployment_name(domain, handle);
@@ -794,20 +1833,66 @@ pub async fn disable_reply(domain: &str, handle: &str) -> eyre::Result<()> {
         .delete(&deployment_name, &Default::default())
         .await
     {
-        if let kube::Error::Api(kube::error::ErrorResponse { reason, .. }) = &error {
-            if reason == "NotFound" {
-                return Ok(());
-            }
+        if !is_not_found(&error) {
+            return Err(eyre::Report::new(error).wrap_err(format!(
+                "failed to delete Kubernetes Deployment `{deployment_name}`"
+            )));
+        }
+    }
+
+    let secret_name = streaming_config_secret_name(domain, handle);
+    if let Err(error) = secret_api.delete(&secret_name, &Default::default()).await {
+        if !is_not_found(&error) {
+            return Err(eyre::Report::new(error).wrap_err(format!(
+                "failed to delete Kubernetes Secret `{secret_name}`"
+            )));
         }
-        return Err(eyre::Report::new(error).wrap_err(format!(
-            "failed to delete Kubernetes Deployment `{deployment_name}`"
-        )));
     }
 
     Ok(())
 }
 
-pub async fn get_dice_feature_enabled(domain: &str, handle: &str) -> eyre::Result<bool> {
+/// Built-in commands of the streaming bot, each toggled by its own annotation.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Responder {
+    Dice,
+    Coin,
+    Choose,
+    EightBall,
+    Tarot,
+    RandomNumber,
+}
+
+impl Responder {
+    pub const ALL: [Self; 6] = [
+        Self::Dice,
+        Self::Coin,
+        Self::Choose,
+        Self::EightBall,
+        Self::Tarot,
+        Self::RandomNumber,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Dice => "dice",
+            Self::Coin => "coin",
+            Self::Choose => "choose",
+            Self::EightBall => "eight_ball",
+            Self::Tarot => "tarot",
+            Self::RandomNumber => "random_number",
+        }
+    }
+
+    fn annotation_key(&self) -> String {
+        format!(
+            "fediq.pbzweihander.dev/{}-feature",
+            self.as_str().replace('_', "-")
+        )
+    }
+}
+
+pub async fn get_enabled_responders(domain: &str, handle: &str) -> eyre::Result<Vec<Responder>> {
     let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
@@ -820,13 +1905,25 @@ pub async fn get_dice_feature_enabled(domain: &str, handle: &str) -> eyre::Resul
                 "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
             )
         })?;
-    Ok(replies_configmap
+    let annotations = replies_configmap
         .and_then(|cm| cm.metadata.annotations)
-        .map(|an| an.get(DICE_FEATURE_ANNOTATION_KEY).map(String::as_str) == Some("true"))
-        .unwrap_or_default())
+        .unwrap_or_default();
+    Ok(Responder::ALL
+        .into_iter()
+        .filter(|responder| {
+            annotations
+                .get(&responder.annotation_key())
+                .map(String::as_str)
+                == Some("true")
+        })
+        .collect())
 }
 
-pub async fn enable_dice_feature(domain: &str, handle: &str) -> eyre::Result<()> {
+pub async fn save_enabled_responders(
+    domain: &str,
+    handle: &str,
+    responders: &[Responder],
+) -> eyre::Result<()> {
     let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
@@ -844,7 +1941,12 @@ pub async fn enable_dice_feature(domain: &str, handle: &str) -> eyre::Result<()>
         .and_then(|cm| cm.metadata.annotations.clone())
         .unwrap_or_default();
     let configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
-    annotations.insert(DICE_FEATURE_ANNOTATION_KEY.to_string(), "true".to_string());
+    for responder in Responder::ALL {
+        annotations.insert(
+            responder.annotation_key(),
+            responders.contains(&responder).to_string(),
+        );
+    }
 
     configmap_api
         .patch(
@@ -868,7 +1970,85 @@ pub async fn enable_dice_feature(domain: &str, handle: &str) -> eyre::Result<()>
     Ok(())
 }
 
-pub async fn disable_dice_feature(domain: &str, handle: &str) -> eyre::Result<()> {
+/// Post visibility, ordered from the most open to the most restricted.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Visibility {
+    Public,
+    Unlisted,
+    Private,
+    Direct,
+}
+
+impl Visibility {
+    pub const ALL: [Self; 4] = [Self::Public, Self::Unlisted, Self::Private, Self::Direct];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Unlisted => "unlisted",
+            Self::Private => "private",
+            Self::Direct => "direct",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|visibility| visibility.as_str() == s)
+    }
+}
+
+/// Limits on how often the streaming bot replies. Zero means unlimited.
+#[derive(Default)]
+pub struct ReplySettings {
+    pub cooldown_seconds: u32,
+    pub replies_per_minute: u32,
+    pub ignore_bots: bool,
+    /// The most open visibility replies may have. Replies otherwise mirror the mention.
+    pub visibility_cap: Option<Visibility>,
+}
+
+pub async fn get_reply_settings(domain: &str, handle: &str) -> eyre::Result<ReplySettings> {
+    let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let replies_configmap_name = replies_configmap_name(domain, handle);
+    let replies_configmap = configmap_api
+        .get_opt(&replies_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?;
+    let annotations = replies_configmap
+        .and_then(|cm| cm.metadata.annotations)
+        .unwrap_or_default();
+
+    Ok(ReplySettings {
+        cooldown_seconds: annotations
+            .get(REPLY_COOLDOWN_SECONDS_ANNOTATION_KEY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0),
+        replies_per_minute: annotations
+            .get(REPLIES_PER_MINUTE_ANNOTATION_KEY)
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(0),
+        ignore_bots: annotations
+            .get(IGNORE_BOTS_ANNOTATION_KEY)
+            .map(String::as_str)
+            == Some("true"),
+        visibility_cap: annotations
+            .get(REPLY_VISIBILITY_CAP_ANNOTATION_KEY)
+            .and_then(|v| Visibility::parse(v)),
+    })
+}
+
+pub async fn save_reply_settings(
+    domain: &str,
+    handle: &str,
+    settings: &ReplySettings,
+) -> eyre::Result<()> {
     let client = client().await?;
     let configmap_api = Api::<ConfigMap>::default_namespaced(client);
 
@@ -886,7 +2066,142 @@ pub async fn disable_dice_feature(domain: &str, handle: &str) -> eyre::Result<()
         .and_then(|cm| cm.metadata.annotations.clone())
         .unwrap_or_default();
     let configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
-    annotations.insert(DICE_FEATURE_ANNOTATION_KEY.to_string(), "false".to_string());
+    annotations.insert(
+        REPLY_COOLDOWN_SECONDS_ANNOTATION_KEY.to_string(),
+        settings.cooldown_seconds.to_string(),
+    );
+    annotations.insert(
+        REPLIES_PER_MINUTE_ANNOTATION_KEY.to_string(),
+        settings.replies_per_minute.to_string(),
+    );
+    annotations.insert(
+        IGNORE_BOTS_ANNOTATION_KEY.to_string(),
+        settings.ignore_bots.to_string(),
+    );
+    if let Some(visibility_cap) = settings.visibility_cap {
+        annotations.insert(
+            REPLY_VISIBILITY_CAP_ANNOTATION_KEY.to_string(),
+            visibility_cap.as_str().to_string(),
+        );
+    } else {
+        annotations.remove(REPLY_VISIBILITY_CAP_ANNOTATION_KEY);
+    }
+
+    configmap_api
+        .patch(
+            &replies_configmap_name,
+            &PatchParams::apply("fediq.pbzweihander.dev").force(),
+            &Patch::Apply(ConfigMap {
+                metadata: ObjectMeta {
+                    name: Some(replies_configmap_name.clone()),
+                    annotations: Some(annotations),
+                    ..Default::default()
+                },
+                data: Some(configmap_data),
+                ..Default::default()
+            }),
+        )
+        .await
+        .wrap_err_with(|| {
+            format!("failed to patch Kubernetes ConfigMap `{replies_configmap_name}`")
+        })?;
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResponseMode {
+    #[default]
+    Off,
+    Reply,
+    React,
+}
+
+impl ResponseMode {
+    pub const ALL: [Self; 3] = [Self::Off, Self::Reply, Self::React];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Reply => "reply",
+            Self::React => "react",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|mode| mode.as_str() == s)
+    }
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct FollowReaction {
+    pub welcome: bool,
+    pub follow_back: bool,
+    pub messages: Vec<String>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NotificationReaction {
+    pub mode: ResponseMode,
+    pub messages: Vec<String>,
+}
+
+/// How the streaming bot responds to notifications other than mentions.
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Reactions {
+    pub follow: FollowReaction,
+    pub reblog: NotificationReaction,
+    pub favourite: NotificationReaction,
+}
+
+pub async fn load_reactions(domain: &str, handle: &str) -> eyre::Result<Reactions> {
+    let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let replies_configmap_name = replies_configmap_name(domain, handle);
+    let replies_configmap = configmap_api
+        .get_opt(&replies_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?;
+    Ok(replies_configmap
+        .and_then(|cm| cm.data)
+        .and_then(|data| {
+            data.get("reactions")
+                .and_then(|v| serde_json::from_str::<Reactions>(v).ok())
+        })
+        .unwrap_or_default())
+}
+
+pub async fn save_reactions(domain: &str, handle: &str, reactions: &Reactions) -> eyre::Result<()> {
+    let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let replies_configmap_name = replies_configmap_name(domain, handle);
+    let replies_configmap = configmap_api
+        .get_opt(&replies_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?;
+    let annotations = replies_configmap
+        .as_ref()
+        .and_then(|cm| cm.metadata.annotations.clone())
+        .unwrap_or_default();
+    let mut configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
+    configmap_data.insert(
+        "reactions".to_string(),
+        serde_json::to_string(reactions).unwrap(),
+    );
 
     configmap_api
         .patch(
@@ -910,22 +2225,196 @@ pub async fn disable_dice_feature(domain: &str, handle: &str) -> eyre::Result<()
     Ok(())
 }
 
-pub async fn restart_reply(domain: &str, handle: &str) -> eyre::Result<()> {
+fn default_script_expiry_minutes() -> u64 {
+    10
+}
+
+/// A multi-turn dialogue started by a keyword. The streaming bot sends each step's prompt and
+/// checks the reply in the same thread against its answers.
+#[derive(Deserialize, Serialize)]
+pub struct Script {
+    pub keyword: String,
+    #[serde(default)]
+    pub match_type: MatchType,
+    #[serde(default = "default_script_expiry_minutes")]
+    pub expiry_minutes: u64,
+    pub steps: Vec<ScriptStep>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScriptStep {
+    pub prompt: String,
+    pub answers: Vec<String>,
+    pub correct: String,
+    pub wrong: String,
+}
+
+pub async fn load_scripts(domain: &str, handle: &str) -> eyre::Result<Vec<Script>> {
     let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let replies_configmap_name = replies_configmap_name(domain, handle);
+    let replies_configmap = configmap_api
+        .get_opt(&replies_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?;
+    Ok(replies_configmap
+        .and_then(|cm| cm.data)
+        .and_then(|data| {
+            data.get("scripts")
+                .and_then(|v| serde_json::from_str::<Vec<Script>>(v).ok())
+        })
+        .unwrap_or_default())
+}
+
+/// Adds a script, replacing the one with the same keyword if any.
+pub async fn add_script(domain: &str, handle: &str, script: Script) -> eyre::Result<()> {
+    let mut scripts = load_scripts(domain, handle).await?;
+    scripts.retain(|s| s.keyword != script.keyword);
+    scripts.push(script);
+    save_scripts(domain, handle, &scripts).await
+}
+
+pub async fn delete_script(domain: &str, handle: &str, keyword: &str) -> eyre::Result<()> {
+    let mut scripts = load_scripts(domain, handle).await?;
+    scripts.retain(|s| s.keyword != keyword);
+    save_scripts(domain, handle, &scripts).await
+}
+
+async fn save_scripts(domain: &str, handle: &str, scripts: &[Script]) -> eyre::Result<()> {
+    let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client);
+
+    let replies_configmap_name = replies_configmap_name(domain, handle);
+    let replies_configmap = configmap_api
+        .get_opt(&replies_configmap_name)
+        .await
+        .wrap_err_with(|| {
+            format!(
+                "failed to get Kubernetes ConfigMap for domain `{domain}` and handle `{handle}`"
+            )
+        })?;
+    let annotations = replies_configmap
+        .as_ref()
+        .and_then(|cm| cm.metadata.annotations.clone())
+        .unwrap_or_default();
+    let mut configmap_data = replies_configmap.and_then(|cm| cm.data).unwrap_or_default();
+    configmap_data.insert(
+        "scripts".to_string(),
+        serde_json::to_string(scripts).unwrap(),
+    );
+
+    configmap_api
+        .patch(
+            &replies_configmap_name,
+            &PatchParams::apply("fediq.pbzweihander.dev").force(),
+            &Patch::Apply(ConfigMap {
+                metadata: ObjectMeta {
+                    name: Some(replies_configmap_name.clone()),
+                    annotations: Some(annotations),
+                    ..Default::default()
+                },
+                data: Some(configmap_data),
+                ..Default::default()
+            }),
+        )
+        .await
+        .wrap_err_with(|| {
+            format!("failed to patch Kubernetes ConfigMap `{replies_configmap_name}`")
+        })?;
+
+    Ok(())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamingState {
+    Connecting,
+    Connected,
+    Reconnecting,
+    Stopped,
+    /// The Deployment has no available pod, so nothing is reporting.
+    #[serde(skip)]
+    NotRunning,
+}
+
+/// Connection status the streaming bot reports to its state ConfigMap.
+#[derive(Deserialize)]
+pub struct StreamingStatus {
+    pub state: StreamingState,
+    #[serde(with = "time::serde::rfc3339::option", default)]
+    pub updated_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    pub error: Option<String>,
+    /// The bot's access token was granted before scopes it now needs.
+    #[serde(skip)]
+    pub outdated_scopes: bool,
+}
+
+pub async fn get_streaming_status(
+    domain: &str,
+    handle: &str,
+) -> eyre::Result<Option<StreamingStatus>> {
+    let client = client().await?;
+    let configmap_api = Api::<ConfigMap>::default_namespaced(client.clone());
+    let secret_api = Api::<Secret>::default_namespaced(client.clone());
     let deployment_api = Api::<Deployment>::default_namespaced(client);
 
+    if !get_reply_enabled(domain, handle).await? {
+        return Ok(None);
+    }
+    let secret_name = streaming_config_secret_name(domain, handle);
+    let secret_data = secret_api
+        .get_opt(&secret_name)
+        .await
+        .wrap_err_with(|| format!("failed to get Kubernetes Secret `{secret_name}`"))?
+        .and_then(|secret| secret.data)
+        .unwrap_or_default();
+    let outdated_scopes = secret_data
+        .get("SOFTWARE")
+        .is_some_and(|software| software.0 == b"mastodon")
+        && secret_data
+            .get("SCOPES")
+            .is_none_or(|scopes| scopes.0 != MASTODON_SCOPES.as_bytes());
     let deployment_name = streaming_deployment_name(domain, handle);
+    let deployment = deployment_api
+        .get_opt(&deployment_name)
+        .await
+        .wrap_err_with(|| format!("failed to get Kubernetes Deployment `{deployment_name}`"))?;
 
-    if let Err(error) = deployment_api.restart(&deployment_name).await {
-        if let kube::Error::Api(kube::error::ErrorResponse { reason, .. }) = &error {
-            if reason == "NotFound" {
-                return Ok(());
-            }
+    let state_configmap_name = streaming_state_configmap_name(domain, handle);
+    let status = configmap_api
+        .get_opt(&state_configmap_name)
+        .await
+        .wrap_err_with(|| format!("failed to get Kubernetes ConfigMap `{state_configmap_name}`"))?
+        .and_then(|cm| cm.data)
+        .and_then(|data| {
+            data.get("status")
+                .and_then(|v| serde_json::from_str::<StreamingStatus>(v).ok())
+        });
+    let mut status = status.unwrap_or(StreamingStatus {
+        state: StreamingState::Connecting,
+        updated_at: None,
+        error: None,
+        outdated_scopes: false,
+    });
+    status.outdated_scopes = outdated_scopes;
+
+    // A multiplexed process serves the bot when there is no Deployment.
+    if let Some(deployment) = deployment {
+        let available_replicas = deployment
+            .status
+            .and_then(|status| status.available_replicas)
+            .unwrap_or_default();
+        if available_replicas == 0 {
+            status.state = StreamingState::NotRunning;
         }
-        return Err(eyre::Report::new(error).wrap_err(format!(
-            "failed to delete Kubernetes Deployment `{deployment_name}`"
-        )));
     }
 
-    Ok(())
+    Ok(Some(status))
 }
@@ -0,0 +1,593 @@
+//! Dice expressions like `2d6+3`, `1d20+1d4`, `4d6kh3`, `3d6!`, `4dF` and `d%`.
+
+use std::fmt;
+
+use itertools::Itertools;
+
+const MAX_DICE: u64 = 100;
+const MAX_SIDES: u64 = 1000;
+const MAX_TERMS: usize = 10;
+const MAX_CONSTANT: u64 = 1_000_000;
+const MAX_EXPLOSIONS: usize = 100;
+const MAX_SHOWN_ROLLS: usize = 20;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum DiceError {
+    InvalidCount,
+    InvalidSides,
+    TooManyTerms,
+    TooLargeNumber,
+    InvalidSelect,
+    InvalidExplode,
+}
+
+impl fmt::Display for DiceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InvalidCount => write!(f, "주사위는 1개부터 {MAX_DICE}개까지 굴릴 수 있어요."),
+            Self::InvalidSides => write!(f, "주사위 면의 수는 1부터 {MAX_SIDES}까지만 돼요."),
+            Self::TooManyTerms => write!(f, "식에는 항을 {MAX_TERMS}개까지 쓸 수 있어요."),
+            Self::TooLargeNumber => write!(f, "더하거나 빼는 수는 {MAX_CONSTANT}까지만 돼요."),
+            Self::InvalidSelect => write!(
+                f,
+                "남기거나 버릴 주사위 수가 굴리는 주사위 수와 맞지 않아요."
+            ),
+            Self::InvalidExplode => write!(f, "1면체나 F 주사위는 폭발시킬 수 없어요."),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Sides {
+    Number(u64),
+    Fudge,
+}
+
+/// Which dice count towards the total, e.g. `kh3` keeps the highest three.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Select {
+    KeepHighest(u64),
+    KeepLowest(u64),
+    DropHighest(u64),
+    DropLowest(u64),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Dice {
+    count: u64,
+    sides: Sides,
+    select: Option<Select>,
+    explode: bool,
+}
+
+impl Dice {
+    fn validate(&self) -> Result<(), DiceError> {
+        if !(1..=MAX_DICE).contains(&self.count) {
+            return Err(DiceError::InvalidCount);
+        }
+        if let Sides::Number(sides) = self.sides {
+            if !(1..=MAX_SIDES).contains(&sides) {
+                return Err(DiceError::InvalidSides);
+            }
+        }
+        match self.select {
+            Some(Select::KeepHighest(n) | Select::KeepLowest(n)) if n == 0 || n > self.count => {
+                return Err(DiceError::InvalidSelect);
+            }
+            Some(Select::DropHighest(n) | Select::DropLowest(n)) if n >= self.count => {
+                return Err(DiceError::InvalidSelect);
+            }
+            _ => {}
+        }
+        if self.explode && matches!(self.sides, Sides::Fudge | Sides::Number(1)) {
+            return Err(DiceError::InvalidExplode);
+        }
+        Ok(())
+    }
+
+    /// Returns the sides of a plain `NdM` without any modifiers.
+    fn plain_sides(&self) -> Option<u64> {
+        match self.sides {
+            Sides::Number(sides) if self.select.is_none() && !self.explode => Some(sides),
+            _ => None,
+        }
+    }
+
+    fn roll(&self, rng: &mut impl rand::Rng) -> Vec<DieRoll> {
+        let mut rolls = Vec::new();
+        for _ in 0..self.count {
+            for explosions in 0.. {
+                let (value, max) = match self.sides {
+                    Sides::Number(sides) => (rng.random_range(1..=sides as i64), sides as i64),
+                    Sides::Fudge => (rng.random_range(-1..=1), 1),
+                };
+                let exploded = self.explode && value == max && explosions < MAX_EXPLOSIONS;
+                rolls.push(DieRoll {
+                    value,
+                    dropped: false,
+                    exploded,
+                });
+                if !exploded {
+                    break;
+                }
+            }
+        }
+
+        if let Some(select) = self.select {
+            let mut order = (0..rolls.len()).collect_vec();
+            order.sort_by_key(|&i| rolls[i].value);
+            let len = order.len();
+            let dropped = match select {
+                Select::KeepHighest(n) => &order[..len - n as usize],
+                Select::KeepLowest(n) => &order[n as usize..],
+                Select::DropHighest(n) => &order[len - n as usize..],
+                Select::DropLowest(n) => &order[..n as usize],
+            };
+            for &i in dropped {
+                rolls[i].dropped = true;
+            }
+        }
+        rolls
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Term {
+    Dice(Dice),
+    Constant(u64),
+}
+
+/// A sum of dice and constants. Each term is paired with whether it is subtracted.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Expr {
+    terms: Vec<(bool, Term)>,
+}
+
+struct DieRoll {
+    value: i64,
+    dropped: bool,
+    exploded: bool,
+}
+
+enum TermRoll {
+    Dice {
+        plain_sides: Option<u64>,
+        rolls: Vec<DieRoll>,
+    },
+    Constant(u64),
+}
+
+impl TermRoll {
+    fn total(&self) -> i64 {
+        match self {
+            Self::Dice { rolls, .. } => rolls
+                .iter()
+                .filter(|roll| !roll.dropped)
+                .map(|roll| roll.value)
+                .sum(),
+            Self::Constant(value) => *value as i64,
+        }
+    }
+}
+
+pub struct Roll {
+    terms: Vec<(bool, TermRoll)>,
+}
+
+impl Roll {
+    pub fn total(&self) -> i64 {
+        self.terms
+            .iter()
+            .map(|(negative, term)| {
+                if *negative {
+                    -term.total()
+                } else {
+                    term.total()
+                }
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for Roll {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let total = self.total();
+
+        // A lone `NdM` keeps the terse format it always had.
+        if let [(
+            false,
+            TermRoll::Dice {
+                plain_sides: Some(sides),
+                rolls,
+            },
+        )] = self.terms.as_slice()
+        {
+            if rolls.len() == 1 {
+                return write!(f, "{total}");
+            }
+            if rolls.len() <= 10 && *sides <= 100 {
+                return write!(
+                    f,
+                    "{} = {total}",
+                    rolls.iter().map(|roll| roll.value).join(" + ")
+                );
+            }
+            return write!(f, "{total}");
+        }
+
+        for (i, (negative, term)) in self.terms.iter().enumerate() {
+            match (i, negative) {
+                (0, false) => {}
+                (0, true) => write!(f, "-")?,
+                (_, false) => write!(f, " + ")?,
+                (_, true) => write!(f, " - ")?,
+            }
+            match term {
+                TermRoll::Dice { rolls, .. } if rolls.len() <= MAX_SHOWN_ROLLS => {
+                    let rolls = rolls.iter().map(|roll| match roll {
+                        DieRoll { dropped: true, .. } => format!("~{}~", roll.value),
+                        DieRoll { exploded: true, .. } => format!("{}!", roll.value),
+                        _ => roll.value.to_string(),
+                    });
+                    write!(f, "[{}]", rolls.format(", "))?;
+                }
+                term => write!(f, "{}", term.total())?,
+            }
+        }
+        write!(f, " = {total}")
+    }
+}
+
+impl Expr {
+    pub fn roll(&self, rng: &mut impl rand::Rng) -> Roll {
+        let terms = self
+            .terms
+            .iter()
+            .map(|(negative, term)| {
+                let term = match term {
+                    Term::Dice(dice) => TermRoll::Dice {
+                        plain_sides: dice.plain_sides(),
+                        rolls: dice.roll(rng),
+                    },
+                    Term::Constant(value) => TermRoll::Constant(*value),
+                };
+                (*negative, term)
+            })
+            .collect();
+        Roll { terms }
+    }
+}
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn eat(&mut self, b: u8) -> bool {
+        if self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|c| c.eq_ignore_ascii_case(&b))
+        {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.eat(b' ') {}
+    }
+
+    /// Reads digits, saturating so that huge numbers are rejected by the limits later.
+    fn number(&mut self) -> Option<u64> {
+        let start = self.pos;
+        let mut value = 0u64;
+        while let Some(digit) = self.bytes.get(self.pos).filter(|b| b.is_ascii_digit()) {
+            value = value
+                .saturating_mul(10)
+                .saturating_add(u64::from(digit - b'0'));
+            self.pos += 1;
+        }
+        (self.pos > start).then_some(value)
+    }
+
+    fn term(&mut self) -> Result<Option<Term>, DiceError> {
+        let start = self.pos;
+        let count = self.number();
+        if !self.eat(b'd') {
+            return Ok(count.map(Term::Constant));
+        }
+        let sides = if self.eat(b'%') {
+            Sides::Number(100)
+        } else if self.eat(b'f') {
+            Sides::Fudge
+        } else if let Some(sides) = self.number() {
+            Sides::Number(sides)
+        } else {
+            // Something like `2days`, which is not a dice at all.
+            self.pos = start;
+            return Ok(None);
+        };
+
+        let mut select = None;
+        let mut explode = false;
+        loop {
+            let modifier = if self.eat(b'!') {
+                if explode {
+                    self.pos = start;
+                    return Ok(None);
+                }
+                explode = true;
+                continue;
+            } else if self.eat(b'k') {
+                if self.eat(b'l') {
+                    Select::KeepLowest as fn(u64) -> Select
+                } else {
+                    self.eat(b'h');
+                    Select::KeepHighest
+                }
+            } else if self.eat(b'd') {
+                if self.eat(b'h') {
+                    Select::DropHighest
+                } else {
+                    self.eat(b'l');
+                    Select::DropLowest
+                }
+            } else {
+                break;
+            };
+            let Some(n) = self.number() else {
+                self.pos = start;
+                return Ok(None);
+            };
+            if select.replace(modifier(n)).is_some() {
+                self.pos = start;
+                return Ok(None);
+            }
+        }
+
+        Ok(Some(Term::Dice(Dice {
+            count: count.unwrap_or(1),
+            sides,
+            select,
+            explode,
+        })))
+    }
+
+    fn expr(&mut self) -> Result<Option<Expr>, DiceError> {
+        let Some(first) = self.term()? else {
+            return Ok(None);
+        };
+        let mut terms = vec![(false, first)];
+        loop {
+            let before = self.pos;
+            self.skip_spaces();
+            let negative = if self.eat(b'+') {
+                false
+            } else if self.eat(b'-') {
+                true
+            } else {
+                self.pos = before;
+                break;
+            };
+            self.skip_spaces();
+            match self.term()? {
+                Some(term) => terms.push((negative, term)),
+                None => {
+                    self.pos = before;
+                    break;
+                }
+            }
+        }
+
+        if !terms.iter().any(|(_, term)| matches!(term, Term::Dice(_))) {
+            return Ok(None);
+        }
+        // Only a whole word is dice notation, not the start of one like `dfoo`.
+        if self
+            .bytes
+            .get(self.pos)
+            .is_some_and(|b| b.is_ascii_alphanumeric() || *b == b'_')
+        {
+            return Ok(None);
+        }
+        if terms.len() > MAX_TERMS {
+            return Err(DiceError::TooManyTerms);
+        }
+        let mut count = 0;
+        for (_, term) in &terms {
+            match term {
+                Term::Dice(dice) => {
+                    dice.validate()?;
+                    count += dice.count;
+                }
+                Term::Constant(value) if *value > MAX_CONSTANT => {
+                    return Err(DiceError::TooLargeNumber);
+                }
+                Term::Constant(_) => {}
+            }
+        }
+        if count > MAX_DICE {
+            return Err(DiceError::InvalidCount);
+        }
+        Ok(Some(Expr { terms }))
+    }
+}
+
+/// Finds the first dice expression in `text`. Returns `None` if there is none at all, and an error
+/// if there is one but it cannot be rolled.
+pub fn parse(text: &str) -> Option<Result<Expr, DiceError>> {
+    let bytes = text.as_bytes();
+    for start in 0..bytes.len() {
+        if !bytes[start].is_ascii_digit() && !bytes[start].eq_ignore_ascii_case(&b'd') {
+            continue;
+        }
+        // Skip the middle of words, handles and domains like `hold20` or `@bot@d20.social`.
+        if start > 0
+            && (bytes[start - 1].is_ascii_alphanumeric() || b"@._/:#".contains(&bytes[start - 1]))
+        {
+            continue;
+        }
+        let mut parser = Parser { bytes, pos: start };
+        match parser.expr() {
+            Ok(None) => continue,
+            Ok(Some(expr)) => return Some(Ok(expr)),
+            Err(error) => return Some(Err(error)),
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn dice(count: u64, sides: Sides, select: Option<Select>, explode: bool) -> Term {
+        Term::Dice(Dice {
+            count,
+            sides,
+            select,
+            explode,
+        })
+    }
+
+    #[test]
+    fn parses_expressions() {
+        assert_eq!(
+            parse("2d6+3 굴려줘"),
+            Some(Ok(Expr {
+                terms: vec![
+                    (false, dice(2, Sides::Number(6), None, false)),
+                    (false, Term::Constant(3)),
+                ],
+            }))
+        );
+        assert_eq!(
+            parse("주사위 1d20 + 1d4 - 1"),
+            Some(Ok(Expr {
+                terms: vec![
+                    (false, dice(1, Sides::Number(20), None, false)),
+                    (false, dice(1, Sides::Number(4), None, false)),
+                    (true, Term::Constant(1)),
+                ],
+            }))
+        );
+        assert_eq!(
+            parse("4d6kh3"),
+            Some(Ok(Expr {
+                terms: vec![(
+                    false,
+                    dice(4, Sides::Number(6), Some(Select::KeepHighest(3)), false)
+                )],
+            }))
+        );
+        assert_eq!(
+            parse("2d20kl1 3d6! 4dF d%"),
+            Some(Ok(Expr {
+                terms: vec![(
+                    false,
+                    dice(2, Sides::Number(20), Some(Select::KeepLowest(1)), false)
+                )],
+            }))
+        );
+        assert_eq!(
+            parse("3d6!").and_then(Result::ok).map(|expr| expr.terms),
+            Some(vec![(false, dice(3, Sides::Number(6), None, true))])
+        );
+        assert_eq!(
+            parse("4DF").and_then(Result::ok).map(|expr| expr.terms),
+            Some(vec![(false, dice(4, Sides::Fudge, None, false))])
+        );
+        assert_eq!(
+            parse("d%").and_then(Result::ok).map(|expr| expr.terms),
+            Some(vec![(false, dice(1, Sides::Number(100), None, false))])
+        );
+        assert_eq!(
+            parse("5d6d2").and_then(Result::ok).map(|expr| expr.terms),
+            Some(vec![(
+                false,
+                dice(5, Sides::Number(6), Some(Select::DropLowest(2)), false)
+            )])
+        );
+    }
+
+    #[test]
+    fn ignores_text_without_dice() {
+        assert_eq!(parse("고양이 3마리"), None);
+        assert_eq!(parse("2days ago"), None);
+        assert_eq!(parse("hold20"), None);
+        assert_eq!(parse("@bot@d20.social 안녕"), None);
+        assert_eq!(parse("3 + 4"), None);
+        assert_eq!(parse("dfoo"), None);
+        assert_eq!(parse("d6foo"), None);
+        assert_eq!(parse("4d6kx3"), None);
+        assert_eq!(parse("4d6k"), None);
+        assert_eq!(parse("3d6!!"), None);
+        assert_eq!(
+            parse("dfoo 2d6")
+                .and_then(Result::ok)
+                .map(|expr| expr.terms),
+            Some(vec![(false, dice(2, Sides::Number(6), None, false))])
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        assert_eq!(parse("0d6"), Some(Err(DiceError::InvalidCount)));
+        assert_eq!(parse("101d6"), Some(Err(DiceError::InvalidCount)));
+        assert_eq!(parse("60d6+60d6"), Some(Err(DiceError::InvalidCount)));
+        assert_eq!(parse("1d0"), Some(Err(DiceError::InvalidSides)));
+        assert_eq!(parse("1d1001"), Some(Err(DiceError::InvalidSides)));
+        assert_eq!(
+            parse("99999999999999999999999d6"),
+            Some(Err(DiceError::InvalidCount))
+        );
+        assert_eq!(parse("1d6+9999999"), Some(Err(DiceError::TooLargeNumber)));
+        assert_eq!(parse("4d6kh5"), Some(Err(DiceError::InvalidSelect)));
+        assert_eq!(parse("4d6d4"), Some(Err(DiceError::InvalidSelect)));
+        assert_eq!(parse("1d1!"), Some(Err(DiceError::InvalidExplode)));
+        assert_eq!(parse("4dF!"), Some(Err(DiceError::InvalidExplode)));
+        assert_eq!(
+            parse(&"+1d6".repeat(11)[1..]),
+            Some(Err(DiceError::TooManyTerms))
+        );
+    }
+
+    #[test]
+    fn rolls_within_bounds() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let roll = parse("4d6kh3+2").unwrap().unwrap().roll(&mut rng);
+            assert!((5..=20).contains(&roll.total()));
+            let roll = parse("4dF").unwrap().unwrap().roll(&mut rng);
+            assert!((-4..=4).contains(&roll.total()));
+            let roll = parse("d%-1d4").unwrap().unwrap().roll(&mut rng);
+            assert!((-3..=99).contains(&roll.total()));
+            let roll = parse("1d2!").unwrap().unwrap().roll(&mut rng);
+            assert!(roll.total() >= 1);
+        }
+    }
+
+    #[test]
+    fn formats_rolls() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let roll = parse("1d6").unwrap().unwrap().roll(&mut rng);
+        assert_eq!(roll.to_string(), roll.total().to_string());
+
+        let roll = parse("3d6").unwrap().unwrap().roll(&mut rng);
+        let text = roll.to_string();
+        assert_eq!(text.matches(" + ").count(), 2);
+        assert!(text.ends_with(&format!(" = {}", roll.total())));
+
+        let roll = parse("4d6dl1+3").unwrap().unwrap().roll(&mut rng);
+        let text = roll.to_string();
+        assert!(text.starts_with('['));
+        assert_eq!(text.matches('~').count(), 2);
+        assert!(text.ends_with(&format!("] + 3 = {}", roll.total())));
+    }
+}
@@ -0,0 +1,295 @@
+//! Just enough RSS 2.0 and Atom parsing to post new feed entries.
+
+use eyre::WrapErr;
+use time::OffsetDateTime;
+
+use crate::post;
+
+pub const DEFAULT_TEMPLATE: &str = "{title}\n{link}";
+
+#[derive(Debug, PartialEq)]
+pub struct Entry {
+    pub id: String,
+    pub title: String,
+    pub link: String,
+    published: Option<OffsetDateTime>,
+}
+
+impl Entry {
+    /// Fills `{title}` and `{link}` in the template.
+    pub fn render(&self, template: &str) -> String {
+        template
+            .replace("{title}", &self.title)
+            .replace("{link}", &self.link)
+            .trim()
+            .to_string()
+    }
+}
+
+/// Reads the feed at `url`. `file://` URLs are read from the local disk, for trying out feeds,
+/// only if `allow_file` is set.
+pub async fn fetch(url: &str, allow_file: bool) -> eyre::Result<String> {
+    if let Some(path) = url.strip_prefix("file://") {
+        if !allow_file {
+            eyre::bail!("feed file `{path}` is only allowed from the feed URL override");
+        }
+        return std::fs::read_to_string(path)
+            .wrap_err_with(|| format!("failed to read feed file `{path}`"));
+    }
+    let (status, body) = post::send_with_retry(url, true, || post::HTTP_CLIENT.get(url))
+        .await
+        .wrap_err_with(|| format!("failed to fetch feed `{url}`"))?;
+    if !status.is_success() {
+        eyre::bail!("failed to fetch feed `{url}`: {status}");
+    }
+    Ok(body)
+}
+
+fn decode_entities(s: &str) -> String {
+    let mut decoded = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(index) = rest.find('&') {
+        decoded.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let c = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => {
+                let code = entity.strip_prefix('#')?;
+                let code = match code.strip_prefix(['x', 'X']) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                    None => code.parse().ok()?,
+                };
+                char::from_u32(code)
+            }
+        });
+        match (entity, c) {
+            (Some(entity), Some(c)) => {
+                decoded.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    decoded
+}
+
+/// Returns the text of an element, which is either CDATA or escaped text. Atom text of type
+/// `html` is escaped markup, so its tags are stripped after unescaping.
+fn element_text(attributes: &str, inner: &str) -> String {
+    let inner = inner.trim();
+    let text = match inner
+        .strip_prefix("<![CDATA[")
+        .and_then(|s| s.strip_suffix("]]>"))
+    {
+        Some(cdata) => cdata.to_string(),
+        None => decode_entities(inner),
+    };
+    let text = if attribute(attributes, "type") == Some("html") {
+        let mut stripped = String::with_capacity(text.len());
+        let mut in_tag = false;
+        for c in text.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' if in_tag => in_tag = false,
+                c if !in_tag => stripped.push(c),
+                _ => {}
+            }
+        }
+        decode_entities(&stripped)
+    } else {
+        text
+    };
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Finds the elements named `name` that are direct or nested children of `xml`, returning their
+/// attributes and inner text.
+fn elements<'a>(xml: &'a str, name: &str) -> Vec<(&'a str, &'a str)> {
+    let mut elements = Vec::new();
+    let open = format!("<{name}");
+    let close = format!("</{name}>");
+    let mut rest = xml;
+    while let Some(index) = rest.find(&open) {
+        rest = &rest[index + open.len()..];
+        // Skip longer names that merely start with `name`, like `<linkedin>` for `<link`.
+        if !rest.starts_with(|c: char| c == '>' || c == '/' || c.is_whitespace()) {
+            continue;
+        }
+        let Some(tag_end) = rest.find('>') else {
+            break;
+        };
+        let attributes = &rest[..tag_end];
+        if let Some(attributes) = attributes.strip_suffix('/') {
+            elements.push((attributes, ""));
+            rest = &rest[tag_end + 1..];
+            continue;
+        }
+        rest = &rest[tag_end + 1..];
+        let Some(inner_end) = rest.find(&close) else {
+            break;
+        };
+        elements.push((attributes, &rest[..inner_end]));
+        rest = &rest[inner_end + close.len()..];
+    }
+    elements
+}
+
+fn attribute<'a>(attributes: &'a str, name: &str) -> Option<&'a str> {
+    let mut rest = attributes;
+    while let Some(index) = rest.find(name) {
+        let before = rest[..index].chars().last();
+        rest = rest[index + name.len()..].trim_start();
+        if before.is_some_and(|c| !c.is_whitespace()) {
+            continue;
+        }
+        let Some(value) = rest.strip_prefix('=') else {
+            continue;
+        };
+        let value = value.trim_start();
+        let quote = value.chars().next()?;
+        if quote != '"' && quote != '\'' {
+            return None;
+        }
+        let value = &value[1..];
+        return Some(&value[..value.find(quote)?]);
+    }
+    None
+}
+
+fn first_text(xml: &str, name: &str) -> Option<String> {
+    elements(xml, name)
+        .first()
+        .map(|(attributes, inner)| element_text(attributes, inner))
+        .filter(|text| !text.is_empty())
+}
+
+fn parse_date(s: &str) -> Option<OffsetDateTime> {
+    OffsetDateTime::parse(s, &time::format_description::well_known::Rfc3339)
+        .or_else(|_| OffsetDateTime::parse(s, &time::format_description::well_known::Rfc2822))
+        .ok()
+}
+
+fn parse_rss_item(item: &str) -> Option<Entry> {
+    let link = first_text(item, "link").unwrap_or_default();
+    let id = first_text(item, "guid").unwrap_or_else(|| link.clone());
+    let title = first_text(item, "title").unwrap_or_default();
+    if id.is_empty() || (title.is_empty() && link.is_empty()) {
+        return None;
+    }
+    Some(Entry {
+        id,
+        title,
+        link,
+        published: first_text(item, "pubDate").and_then(|date| parse_date(&date)),
+    })
+}
+
+fn parse_atom_entry(entry: &str) -> Option<Entry> {
+    let links = elements(entry, "link");
+    let link = links
+        .iter()
+        .find(|(attributes, _)| attribute(attributes, "rel").is_none_or(|rel| rel == "alternate"))
+        .or(links.first())
+        .and_then(|(attributes, _)| attribute(attributes, "href"))
+        .map(decode_entities)
+        .unwrap_or_default();
+    let id = first_text(entry, "id").unwrap_or_else(|| link.clone());
+    let title = first_text(entry, "title").unwrap_or_default();
+    if id.is_empty() || (title.is_empty() && link.is_empty()) {
+        return None;
+    }
+    Some(Entry {
+        id,
+        title,
+        link,
+        published: first_text(entry, "published")
+            .or_else(|| first_text(entry, "updated"))
+            .and_then(|date| parse_date(&date)),
+    })
+}
+
+/// Parses the entries of an RSS or Atom feed, oldest first.
+pub fn parse(xml: &str) -> Vec<Entry> {
+    let items = elements(xml, "item");
+    let mut entries = if items.is_empty() {
+        elements(xml, "entry")
+            .into_iter()
+            .filter_map(|(_, inner)| parse_atom_entry(inner))
+            .collect::<Vec<_>>()
+    } else {
+        items
+            .into_iter()
+            .filter_map(|(_, inner)| parse_rss_item(inner))
+            .collect::<Vec<_>>()
+    };
+    if entries.iter().all(|entry| entry.published.is_some()) {
+        entries.sort_by_key(|entry| entry.published);
+    } else {
+        // Feeds list the newest entry first by convention.
+        entries.reverse();
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_rss() {
+        let entries = parse(include_str!("../fixtures/feed-rss.xml"));
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.id.as_str(), entry.title.as_str(), entry.link.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                (
+                    "https://blog.example/posts/1",
+                    "첫 글",
+                    "https://blog.example/posts/1"
+                ),
+                (
+                    "tag:blog.example,2025:2",
+                    "Cats & Dogs",
+                    "https://blog.example/posts/2?a=1&b=2"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_atom() {
+        let entries = parse(include_str!("../fixtures/feed-atom.xml"));
+        assert_eq!(
+            entries
+                .iter()
+                .map(|entry| (entry.id.as_str(), entry.title.as_str(), entry.link.as_str()))
+                .collect::<Vec<_>>(),
+            [
+                ("urn:uuid:1", "오래된 소식", "https://news.example/1"),
+                ("urn:uuid:2", "굵은 소식", "https://news.example/2"),
+            ]
+        );
+        assert_eq!(
+            entries[0].render(DEFAULT_TEMPLATE),
+            "오래된 소식\nhttps://news.example/1"
+        );
+        assert_eq!(
+            entries[0].render("새 글: {title} ({link})"),
+            "새 글: 오래된 소식 (https://news.example/1)"
+        );
+    }
+}
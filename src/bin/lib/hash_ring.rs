@@ -0,0 +1,101 @@
+//! Consistent hashing, so that changing the number of replicas moves as few bots as possible.
+
+use std::collections::BTreeMap;
+
+const VIRTUAL_NODES: usize = 128;
+
+/// Maps keys to nodes `0..node_count`. The hash is fixed, so that replicas running different
+/// builds, as in a rolling update, still agree on where each bot goes.
+pub struct HashRing {
+    ring: BTreeMap<u64, usize>,
+}
+
+/// FNV-1a, finished with the MurmurHash3 mix to spread similar keys over the ring.
+fn hash(value: &str) -> u64 {
+    let mut hash = 0xcbf29ce484222325_u64;
+    for byte in value.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51afd7ed558ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ceb9fe1a85ec53);
+    hash ^ (hash >> 33)
+}
+
+impl HashRing {
+    pub fn new(node_count: usize) -> Self {
+        let mut ring = BTreeMap::new();
+        for node in 0..node_count {
+            for virtual_node in 0..VIRTUAL_NODES {
+                ring.insert(hash(&format!("{node}-{virtual_node}")), node);
+            }
+        }
+        Self { ring }
+    }
+
+    pub fn node(&self, key: &str) -> Option<usize> {
+        let hash = hash(key);
+        self.ring
+            .range(hash..)
+            .next()
+            .or_else(|| self.ring.iter().next())
+            .map(|(_, node)| *node)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys() -> Vec<String> {
+        (0..1000)
+            .map(|i| format!("fediq-streaming-config-example{i}.com-bot"))
+            .collect()
+    }
+
+    #[test]
+    fn spreads_keys_over_every_node() {
+        let ring = HashRing::new(4);
+        let mut counts = [0; 4];
+        for key in keys() {
+            counts[ring.node(&key).unwrap()] += 1;
+        }
+        for count in counts {
+            assert!((150..350).contains(&count), "{counts:?}");
+        }
+    }
+
+    #[test]
+    fn adding_node_only_moves_keys_to_it() {
+        let before = HashRing::new(3);
+        let after = HashRing::new(4);
+        let mut moved = 0;
+        for key in keys() {
+            let (from, to) = (before.node(&key).unwrap(), after.node(&key).unwrap());
+            if from != to {
+                assert_eq!(to, 3);
+                moved += 1;
+            }
+        }
+        assert!(moved < 400, "{moved}");
+    }
+
+    #[test]
+    fn hashes_are_stable() {
+        assert_eq!(hash(""), 0xefd01f60ba992926);
+        assert_eq!(hash("a"), 0x82a2a958a9bece5b);
+        let ring = HashRing::new(4);
+        let nodes = keys()[..8]
+            .iter()
+            .map(|key| ring.node(key).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, [3, 3, 3, 3, 1, 1, 1, 1]);
+    }
+
+    #[test]
+    fn empty_ring_has_no_node() {
+        assert_eq!(HashRing::new(0).node("bot"), None);
+    }
+}
@@ -0,0 +1,187 @@
+//! Follows, favourites and reactions, which only the streaming bot does.
+
+use eyre::WrapErr;
+use http::StatusCode;
+use serde::{Deserialize, Serialize};
+
+use crate::post::{send_with_retry, HTTP_CLIENT};
+
+#[derive(Deserialize)]
+struct Id {
+    id: String,
+}
+
+pub async fn follow_mastodon(
+    domain: &str,
+    access_token: &str,
+    account_id: &str,
+) -> eyre::Result<()> {
+    let url = format!("https://{domain}/api/v1/accounts/{account_id}/follow");
+    let (resp_status, resp_text) = send_with_retry(&url, true, || {
+        HTTP_CLIENT.post(&url).bearer_auth(access_token)
+    })
+    .await?;
+    if !resp_status.is_success() {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    Ok(())
+}
+
+/// Returns the id of the latest status the account wrote itself, if any.
+pub async fn latest_status_mastodon(
+    domain: &str,
+    access_token: &str,
+    account_id: &str,
+) -> eyre::Result<Option<String>> {
+    let url = format!("https://{domain}/api/v1/accounts/{account_id}/statuses");
+    let (resp_status, resp_text) = send_with_retry(&url, true, || {
+        HTTP_CLIENT
+            .get(&url)
+            .query(&[
+                ("limit", "1"),
+                ("exclude_replies", "true"),
+                ("exclude_reblogs", "true"),
+            ])
+            .bearer_auth(access_token)
+    })
+    .await?;
+    if !resp_status.is_success() {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    let statuses = serde_json::from_str::<Vec<Id>>(&resp_text)
+        .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+    Ok(statuses.into_iter().next().map(|status| status.id))
+}
+
+pub async fn favourite_mastodon(
+    domain: &str,
+    access_token: &str,
+    status_id: &str,
+) -> eyre::Result<()> {
+    let url = format!("https://{domain}/api/v1/statuses/{status_id}/favourite");
+    let (resp_status, resp_text) = send_with_retry(&url, true, || {
+        HTTP_CLIENT.post(&url).bearer_auth(access_token)
+    })
+    .await?;
+    if !resp_status.is_success() {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    Ok(())
+}
+
+/// Reacts to a status with an emoji on servers that support it, trying the Pleroma/Akkoma API
+/// first and then the glitch-soc one. Returns `false` if neither is available.
+pub async fn react_mastodon(
+    domain: &str,
+    access_token: &str,
+    status_id: &str,
+    emoji: &str,
+) -> eyre::Result<bool> {
+    let url = format!("https://{domain}/api/v1/pleroma/statuses/{status_id}/reactions/{emoji}");
+    let (resp_status, resp_text) = send_with_retry(&url, true, || {
+        HTTP_CLIENT.put(&url).bearer_auth(access_token)
+    })
+    .await?;
+    if resp_status.is_success() {
+        return Ok(true);
+    }
+    if resp_status != StatusCode::NOT_FOUND {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+
+    let url = format!("https://{domain}/api/v1/statuses/{status_id}/react/{emoji}");
+    let (resp_status, resp_text) = send_with_retry(&url, true, || {
+        HTTP_CLIENT.post(&url).bearer_auth(access_token)
+    })
+    .await?;
+    if resp_status.is_success() {
+        return Ok(true);
+    }
+    if resp_status != StatusCode::NOT_FOUND {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    Ok(false)
+}
+
+pub async fn follow_misskey(domain: &str, access_token: &str, user_id: &str) -> eyre::Result<()> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Req<'a> {
+        i: &'a str,
+        user_id: &'a str,
+    }
+
+    let req = Req {
+        i: access_token,
+        user_id,
+    };
+    let url = format!("https://{domain}/api/following/create");
+    let (resp_status, resp_text) =
+        send_with_retry(&url, true, || HTTP_CLIENT.post(&url).json(&req)).await?;
+    if !resp_status.is_success() {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    Ok(())
+}
+
+/// Returns the id of the latest note the user wrote itself, if any.
+pub async fn latest_note_misskey(
+    domain: &str,
+    access_token: &str,
+    user_id: &str,
+) -> eyre::Result<Option<String>> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Req<'a> {
+        i: &'a str,
+        user_id: &'a str,
+        limit: u32,
+        with_replies: bool,
+        with_renotes: bool,
+    }
+
+    let req = Req {
+        i: access_token,
+        user_id,
+        limit: 1,
+        with_replies: false,
+        with_renotes: false,
+    };
+    let url = format!("https://{domain}/api/users/notes");
+    let (resp_status, resp_text) =
+        send_with_retry(&url, true, || HTTP_CLIENT.post(&url).json(&req)).await?;
+    if !resp_status.is_success() {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    let notes = serde_json::from_str::<Vec<Id>>(&resp_text)
+        .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+    Ok(notes.into_iter().next().map(|note| note.id))
+}
+
+pub async fn react_misskey(
+    domain: &str,
+    access_token: &str,
+    note_id: &str,
+    reaction: &str,
+) -> eyre::Result<()> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Req<'a> {
+        i: &'a str,
+        note_id: &'a str,
+        reaction: &'a str,
+    }
+
+    let req = Req {
+        i: access_token,
+        note_id,
+        reaction,
+    };
+    let url = format!("https://{domain}/api/notes/reactions/create");
+    let (resp_status, resp_text) =
+        send_with_retry(&url, true, || HTTP_CLIENT.post(&url).json(&req)).await?;
+    if !resp_status.is_success() {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    Ok(())
+}
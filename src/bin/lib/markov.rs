@@ -0,0 +1,252 @@
+//! Markov chain text generation over the quotes, for "ebooks" style bots.
+
+use std::collections::HashMap;
+
+use rand::seq::IndexedRandom;
+
+/// How many times to walk the chain before giving up on a novel enough text.
+const MAX_TRIES: usize = 100;
+const MAX_LENGTH: usize = 500;
+/// A generated text is rejected if it contains a run of this ratio of its tokens from a quote.
+const MAX_OVERLAP_RATIO: f64 = 0.7;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tokenizer {
+    /// One syllable or letter per token.
+    Character,
+    /// One word per token, with common Korean particles and trailing punctuation split off.
+    #[default]
+    Morpheme,
+}
+
+impl Tokenizer {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "character" => Some(Self::Character),
+            "morpheme" => Some(Self::Morpheme),
+            _ => None,
+        }
+    }
+
+    /// Upper bound of the overlapping run, so long texts are not held to a ratio of their length.
+    fn max_overlap(&self) -> usize {
+        match self {
+            Self::Character => 20,
+            Self::Morpheme => 10,
+        }
+    }
+}
+
+/// Longest first, so that `에서` is split before `에`. Particles that often end a noun, like `이`
+/// and `가`, are left out.
+const PARTICLES: [&str; 24] = [
+    "에서는",
+    "에게서",
+    "으로는",
+    "한테서",
+    "까지",
+    "부터",
+    "에서",
+    "에게",
+    "한테",
+    "으로",
+    "이랑",
+    "처럼",
+    "보다",
+    "마저",
+    "조차",
+    "이라",
+    "은",
+    "는",
+    "을",
+    "를",
+    "에",
+    "의",
+    "랑",
+    "와",
+];
+
+fn is_hangul_syllable(c: char) -> bool {
+    ('\u{AC00}'..='\u{D7A3}').contains(&c)
+}
+
+/// Splits a word into its stem, a trailing particle and trailing punctuation, each if any.
+fn split_word(word: &str) -> [&str; 3] {
+    let stem = word.trim_end_matches(|c: char| c.is_ascii_punctuation() || c == '…');
+    if stem.is_empty() {
+        return [word, "", ""];
+    }
+    let punctuation = &word[stem.len()..];
+    if !stem.chars().all(is_hangul_syllable) {
+        return [stem, "", punctuation];
+    }
+    PARTICLES
+        .iter()
+        .find_map(|particle| {
+            let rest = stem.strip_suffix(particle)?;
+            (!rest.is_empty()).then_some([rest, &stem[rest.len()..], punctuation])
+        })
+        .unwrap_or([stem, "", punctuation])
+}
+
+/// Splits the text into tokens, each carrying the whitespace before it, so that concatenating
+/// them gives back the text.
+pub fn tokenize(text: &str, tokenizer: Tokenizer) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        let end = match tokenizer {
+            Tokenizer::Character => chars.peek().map_or(text.len(), |(index, _)| *index),
+            Tokenizer::Morpheme => {
+                while chars.next_if(|(_, c)| !c.is_whitespace()).is_some() {}
+                chars.peek().map_or(text.len(), |(index, _)| *index)
+            }
+        };
+        let token = &text[start..end];
+        match tokenizer {
+            Tokenizer::Character => tokens.push(token),
+            Tokenizer::Morpheme => {
+                let word_start = token.len() - token.trim_start().len();
+                let [stem, particle, punctuation] = split_word(&token[word_start..]);
+                tokens.push(&token[..word_start + stem.len()]);
+                tokens.extend(
+                    [particle, punctuation]
+                        .into_iter()
+                        .filter(|s| !s.is_empty()),
+                );
+            }
+        }
+        start = end;
+    }
+    tokens
+}
+
+pub struct Chain<'a> {
+    order: usize,
+    /// The empty string stands for the start of a quote, and `None` for its end.
+    transitions: HashMap<Vec<&'a str>, Vec<Option<&'a str>>>,
+}
+
+impl<'a> Chain<'a> {
+    pub fn new(quotes: &[&'a str], order: usize, tokenizer: Tokenizer) -> Self {
+        let order = order.max(1);
+        let mut transitions = HashMap::<_, Vec<_>>::new();
+        for quote in quotes {
+            let tokens = tokenize(quote.trim(), tokenizer);
+            if tokens.is_empty() {
+                continue;
+            }
+            let mut state = vec![""; order];
+            for token in tokens.into_iter().map(Some).chain([None]) {
+                transitions.entry(state.clone()).or_default().push(token);
+                if let Some(token) = token {
+                    state.remove(0);
+                    state.push(token);
+                }
+            }
+        }
+        Self { order, transitions }
+    }
+
+    fn walk(&self, rng: &mut impl rand::Rng) -> String {
+        let mut state = vec![""; self.order];
+        let mut text = String::new();
+        while let Some(Some(token)) = self
+            .transitions
+            .get(&state)
+            .and_then(|next| next.choose(rng))
+        {
+            text.push_str(token);
+            if text.len() > MAX_LENGTH {
+                break;
+            }
+            state.remove(0);
+            state.push(token);
+        }
+        text
+    }
+}
+
+/// Whether the text shares too long a run of tokens with one of the quotes.
+fn overlaps(text: &str, quotes: &[&str], tokenizer: Tokenizer) -> bool {
+    let tokens = tokenize(text, tokenizer);
+    let run = ((tokens.len() as f64 * MAX_OVERLAP_RATIO).ceil() as usize)
+        .min(tokenizer.max_overlap())
+        .max(1);
+    tokens.windows(run.min(tokens.len())).any(|window| {
+        let run = window.concat();
+        let run = run.trim();
+        quotes.iter().any(|quote| quote.contains(run))
+    })
+}
+
+/// Walks the chain until it makes a text that does not reproduce any of the quotes.
+pub fn generate(
+    quotes: &[&str],
+    order: usize,
+    tokenizer: Tokenizer,
+    rng: &mut impl rand::Rng,
+) -> Option<String> {
+    let chain = Chain::new(quotes, order, tokenizer);
+    (0..MAX_TRIES).find_map(|_| {
+        let text = chain.walk(rng);
+        let text = text.trim();
+        (!text.is_empty() && text.len() <= MAX_LENGTH && !overlaps(text, quotes, tokenizer))
+            .then(|| text.to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn splits_particles_and_punctuation() {
+        assert_eq!(
+            tokenize("고양이는 집에서 잔다.", Tokenizer::Morpheme),
+            ["고양이", "는", " 집", "에서", " 잔다", "."]
+        );
+        assert_eq!(
+            tokenize("오늘도  cats! 집에서,", Tokenizer::Morpheme),
+            ["오늘도", "  cats", "!", " 집", "에서", ","]
+        );
+        assert_eq!(tokenize("냥 냥", Tokenizer::Character), ["냥", " 냥"]);
+        for text in ["고양이는 집에서 잔다.", "줄\n바꿈도 그대로…", "a,b"] {
+            for tokenizer in [Tokenizer::Character, Tokenizer::Morpheme] {
+                assert_eq!(tokenize(text, tokenizer).concat(), text);
+            }
+        }
+    }
+
+    #[test]
+    fn never_reproduces_quotes() {
+        let quotes = [
+            "고양이는 집에서 잠을 잔다.",
+            "강아지는 밖에서 공을 쫓는다.",
+            "나는 집에서 공을 던진다.",
+            "고양이는 밖에서 새를 본다.",
+            "강아지는 집에서 밥을 먹는다.",
+        ];
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..20 {
+            let text = generate(&quotes, 1, Tokenizer::Morpheme, &mut rng).unwrap();
+            assert!(!quotes.iter().any(|quote| quote.contains(&text)), "{text}");
+        }
+    }
+
+    #[test]
+    fn gives_up_without_novel_text() {
+        let mut rng = StdRng::seed_from_u64(0);
+        assert_eq!(
+            generate(&["고양이는 집에서 잔다."], 2, Tokenizer::Morpheme, &mut rng),
+            None
+        );
+        assert_eq!(generate(&[], 2, Tokenizer::Character, &mut rng), None);
+    }
+}
@@ -1,7 +1,21 @@
+use std::{collections::HashMap, sync::Mutex, time::Duration};
+
 use eyre::WrapErr;
-use http::HeaderMap;
+use http::{HeaderMap, StatusCode};
 use once_cell::sync::Lazy;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::time::Instant;
+use ulid::Ulid;
+
+const MAX_ATTEMPTS: u32 = 5;
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(5 * 60);
+/// How many times a Misskey note is sent, checking for it in between.
+const MISSKEY_MAX_ATTEMPTS: u32 = 3;
+/// How far back a note found while checking may have been created, for clock skew.
+const MISSKEY_CLOCK_SKEW: time::Duration = time::Duration::minutes(1);
 
 pub static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
     let mut headers = HeaderMap::new();
@@ -18,42 +32,221 @@ pub static HTTP_CLIENT: Lazy<reqwest::Client> = Lazy::new(|| {
         .expect("failed to build HTTP client")
 });
 
+/// Set per host when the server told us the rate limit is exhausted, so that following requests
+/// to it wait for the reset instead of burning attempts on 429s.
+static RATE_LIMITED_UNTIL: Lazy<Mutex<HashMap<String, Instant>>> = Lazy::new(Mutex::default);
+
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get("retry-after")?.to_str().ok()?;
+    if let Ok(seconds) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = OffsetDateTime::parse(value, &time::format_description::well_known::Rfc2822).ok()?;
+    Some(
+        (at - OffsetDateTime::now_utc())
+            .try_into()
+            .unwrap_or_default(),
+    )
+}
+
+fn parse_rate_limit_reset(headers: &HeaderMap) -> Option<Duration> {
+    let remaining = headers.get("x-ratelimit-remaining")?.to_str().ok()?;
+    if remaining.trim().parse::<u64>().ok()? > 0 {
+        return None;
+    }
+    let reset = headers.get("x-ratelimit-reset")?.to_str().ok()?;
+    let at = OffsetDateTime::parse(reset, &time::format_description::well_known::Rfc3339).ok()?;
+    Some(
+        (at - OffsetDateTime::now_utc())
+            .try_into()
+            .unwrap_or_default(),
+    )
+}
+
+fn backoff(attempt: u32) -> Duration {
+    let backoff = INITIAL_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt - 1))
+        .min(MAX_BACKOFF);
+    let jitter = rand::random_range(0..=backoff.as_millis() as u64 / 2);
+    backoff + Duration::from_millis(jitter)
+}
+
+/// Rate limits and unavailability mean the request was not handled, but other server errors may
+/// come after the post was created.
+fn is_retryable(status: StatusCode, idempotent: bool) -> bool {
+    match status {
+        StatusCode::TOO_MANY_REQUESTS | StatusCode::SERVICE_UNAVAILABLE => true,
+        StatusCode::INTERNAL_SERVER_ERROR
+        | StatusCode::BAD_GATEWAY
+        | StatusCode::GATEWAY_TIMEOUT => idempotent,
+        _ => false,
+    }
+}
+
+/// Whether the request may or may not have been handled.
+fn is_ambiguous(status: StatusCode) -> bool {
+    is_retryable(status, true) && !is_retryable(status, false)
+}
+
+/// Sends a request built by `build`, retrying transient failures with exponential backoff.
+///
+/// Timeouts and gateway errors are ambiguous, since the server may have created the post anyway,
+/// so they are only retried when `idempotent` is set.
+pub async fn send_with_retry(
+    url: &str,
+    idempotent: bool,
+    build: impl Fn() -> reqwest::RequestBuilder,
+) -> eyre::Result<(StatusCode, String)> {
+    let host = reqwest::Url::parse(url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_default();
+    let mut attempt = 0;
+    loop {
+        let rate_limited_until = RATE_LIMITED_UNTIL.lock().unwrap().get(&host).copied();
+        if let Some(rate_limited_until) = rate_limited_until {
+            tokio::time::sleep_until(rate_limited_until).await;
+        }
+
+        attempt += 1;
+        let resp = match build().send().await {
+            Ok(resp) => resp,
+            Err(error)
+                if attempt < MAX_ATTEMPTS
+                    && (error.is_connect() || (idempotent && error.is_timeout())) =>
+            {
+                let delay = backoff(attempt);
+                tracing::warn!(?error, attempt, ?delay, url, "request failed. retrying...");
+                tokio::time::sleep(delay).await;
+                continue;
+            }
+            Err(error) => {
+                return Err(error).wrap_err_with(|| format!("failed to request to `{url}`"));
+            }
+        };
+
+        let resp_status = resp.status();
+        let rate_limit_reset = parse_rate_limit_reset(resp.headers());
+        match rate_limit_reset.filter(|reset| *reset <= MAX_RATE_LIMIT_WAIT) {
+            Some(reset) => {
+                RATE_LIMITED_UNTIL
+                    .lock()
+                    .unwrap()
+                    .insert(host.clone(), Instant::now() + reset);
+            }
+            None => {
+                RATE_LIMITED_UNTIL.lock().unwrap().remove(&host);
+            }
+        }
+        let retry_after = parse_retry_after(resp.headers()).or(rate_limit_reset);
+        let resp_text = resp
+            .text()
+            .await
+            .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+
+        if !is_retryable(resp_status, idempotent) || attempt >= MAX_ATTEMPTS {
+            return Ok((resp_status, resp_text));
+        }
+
+        let delay = retry_after.unwrap_or_else(|| backoff(attempt));
+        if delay > MAX_RATE_LIMIT_WAIT {
+            return Err(eyre::eyre!(
+                "rate limited for {delay:?}, giving up: `{resp_text}`"
+            ));
+        }
+        tracing::warn!(%resp_status, attempt, ?delay, url, "retryable error response received. retrying...");
+        tokio::time::sleep(delay).await;
+    }
+}
+
+pub struct Posted {
+    pub id: String,
+    pub url: String,
+}
+
+#[derive(Serialize)]
+pub struct MastodonStatusReq<'a> {
+    status: &'a str,
+    visibility: &'a str,
+    in_reply_to_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    spoiler_text: Option<&'a str>,
+}
+
+pub fn mastodon_status_req<'a>(
+    status: &'a str,
+    reply_id: Option<String>,
+    visibility: &'a str,
+    cw: Option<&'a str>,
+) -> MastodonStatusReq<'a> {
+    MastodonStatusReq {
+        status,
+        visibility,
+        in_reply_to_id: reply_id,
+        spoiler_text: cw,
+    }
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MisskeyNoteReq<'a> {
+    text: &'a str,
+    visibility: &'a str,
+    reply_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cw: Option<&'a str>,
+}
+
+pub fn misskey_note_req<'a>(
+    text: &'a str,
+    reply_id: Option<String>,
+    visibility: &'a str,
+    cw: Option<&'a str>,
+) -> MisskeyNoteReq<'a> {
+    MisskeyNoteReq {
+        text,
+        visibility,
+        reply_id,
+        cw,
+    }
+}
+
 pub async fn post_mastodon(
     domain: &str,
     access_token: &str,
     quote: &str,
     reply_id: Option<String>,
-) -> eyre::Result<()> {
-    #[derive(Serialize)]
-    struct Req<'a> {
-        status: &'a str,
-        visibility: &'a str,
-        in_reply_to_id: Option<String>,
+    visibility: &str,
+    cw: Option<&str>,
+) -> eyre::Result<Posted> {
+    #[derive(Deserialize)]
+    struct Resp {
+        id: String,
+        uri: String,
+        url: Option<String>,
     }
 
-    let req = Req {
-        status: quote,
-        visibility: "unlisted",
-        in_reply_to_id: reply_id,
-    };
+    let req = mastodon_status_req(quote, reply_id, visibility, cw);
     let url = format!("https://{domain}/api/v1/statuses");
-    let resp = HTTP_CLIENT
-        .post(&url)
-        .bearer_auth(access_token)
-        .json(&req)
-        .send()
-        .await
-        .wrap_err_with(|| format!("failed to request to `{url}`"))?;
-    let resp_status = resp.status();
-    let resp_text = resp
-        .text()
-        .await
-        .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+    // Mastodon deduplicates statuses created with the same key, so retries never double-post.
+    let idempotency_key = Ulid::new().to_string();
+    let (resp_status, resp_text) = send_with_retry(&url, true, || {
+        HTTP_CLIENT
+            .post(&url)
+            .bearer_auth(access_token)
+            .header("idempotency-key", &idempotency_key)
+            .json(&req)
+    })
+    .await?;
     if !resp_status.is_success() {
-        Err(eyre::eyre!("error response received: `{resp_text}`"))
-    } else {
-        Ok(())
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
     }
+    let resp = serde_json::from_str::<Resp>(&resp_text)
+        .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+    Ok(Posted {
+        id: resp.id,
+        url: resp.url.unwrap_or(resp.uri),
+    })
 }
 
 pub async fn post_misskey(
@@ -61,37 +254,230 @@ pub async fn post_misskey(
     access_token: &str,
     text: &str,
     reply_id: Option<String>,
-) -> eyre::Result<()> {
+    visibility: &str,
+    cw: Option<&str>,
+) -> eyre::Result<Posted> {
     #[derive(Serialize)]
-    #[serde(rename_all = "camelCase")]
     struct Req<'a> {
         i: &'a str,
-        text: &'a str,
-        visibility: &'a str,
-        reply_id: Option<String>,
+        #[serde(flatten)]
+        note: MisskeyNoteReq<'a>,
     }
 
+    #[derive(Deserialize)]
+    struct Note {
+        id: String,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Resp {
+        created_note: Note,
+    }
+
+    let started_at = OffsetDateTime::now_utc();
     let req = Req {
         i: access_token,
-        text,
-        visibility: "home",
-        reply_id,
+        note: misskey_note_req(text, reply_id.clone(), visibility, cw),
     };
     let url = format!("https://{domain}/api/notes/create");
-    let resp = HTTP_CLIENT
-        .post(&url)
-        .json(&req)
-        .send()
-        .await
-        .wrap_err_with(|| format!("failed to request to `{url}`"))?;
-    let resp_status = resp.status();
-    let resp_text = resp
-        .text()
-        .await
-        .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+    let mut attempt = 0;
+    let (resp_status, resp_text) = loop {
+        attempt += 1;
+        let res = send_with_retry(&url, false, || HTTP_CLIENT.post(&url).json(&req)).await;
+        let ambiguous = match &res {
+            Ok((resp_status, _)) => is_ambiguous(*resp_status),
+            Err(_) => true,
+        };
+        if !ambiguous || attempt >= MISSKEY_MAX_ATTEMPTS {
+            break res?;
+        }
+        // Misskey has no idempotency key, so look for the note before sending it again.
+        match find_recent_note_misskey(domain, access_token, text, reply_id.as_deref(), started_at)
+            .await
+        {
+            Ok(Some(id)) => {
+                return Ok(Posted {
+                    url: format!("https://{domain}/notes/{id}"),
+                    id,
+                });
+            }
+            Ok(None) => {
+                let delay = backoff(attempt);
+                tracing::warn!(attempt, ?delay, "note not created. retrying...");
+                tokio::time::sleep(delay).await;
+            }
+            Err(error) => {
+                tracing::error!(?error, "failed to check whether the note was created");
+                break res?;
+            }
+        }
+    };
     if !resp_status.is_success() {
-        Err(eyre::eyre!("error response received: `{resp_text}`"))
-    } else {
-        Ok(())
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    let resp = serde_json::from_str::<Resp>(&resp_text)
+        .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+    Ok(Posted {
+        url: format!("https://{domain}/notes/{}", resp.created_note.id),
+        id: resp.created_note.id,
+    })
+}
+
+/// Finds the id of a note with `text` the bot created since `since`.
+async fn find_recent_note_misskey(
+    domain: &str,
+    access_token: &str,
+    text: &str,
+    reply_id: Option<&str>,
+    since: OffsetDateTime,
+) -> eyre::Result<Option<String>> {
+    #[derive(Deserialize)]
+    struct User {
+        id: String,
+    }
+
+    #[derive(Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Note {
+        id: String,
+        #[serde(with = "time::serde::rfc3339")]
+        created_at: OffsetDateTime,
+        text: Option<String>,
+        reply_id: Option<String>,
+    }
+
+    let url = format!("https://{domain}/api/i");
+    let (resp_status, resp_text) = send_with_retry(&url, true, || {
+        HTTP_CLIENT
+            .post(&url)
+            .json(&serde_json::json!({ "i": access_token }))
+    })
+    .await?;
+    if !resp_status.is_success() {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    let user = serde_json::from_str::<User>(&resp_text)
+        .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+
+    let url = format!("https://{domain}/api/users/notes");
+    let (resp_status, resp_text) = send_with_retry(&url, true, || {
+        HTTP_CLIENT.post(&url).json(&serde_json::json!({
+            "i": access_token,
+            "userId": user.id,
+            "limit": 10,
+        }))
+    })
+    .await?;
+    if !resp_status.is_success() {
+        return Err(eyre::eyre!("error response received: `{resp_text}`"));
+    }
+    let notes = serde_json::from_str::<Vec<Note>>(&resp_text)
+        .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+    Ok(notes
+        .into_iter()
+        .find(|note| {
+            note.created_at >= since - MISSKEY_CLOCK_SKEW
+                && note.text.as_deref() == Some(text)
+                && note.reply_id.as_deref() == reply_id
+        })
+        .map(|note| note.id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn headers(pairs: &[(&'static str, &str)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, value.parse().unwrap());
+        }
+        headers
+    }
+
+    fn rfc3339(at: OffsetDateTime) -> String {
+        at.format(&time::format_description::well_known::Rfc3339)
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_retry_after() {
+        assert_eq!(
+            parse_retry_after(&headers(&[("retry-after", "120")])),
+            Some(Duration::from_secs(120))
+        );
+        let at = OffsetDateTime::now_utc() + time::Duration::minutes(2);
+        let at = at
+            .format(&time::format_description::well_known::Rfc2822)
+            .unwrap();
+        let delay = parse_retry_after(&headers(&[("retry-after", &at)])).unwrap();
+        assert!(delay > Duration::from_secs(100) && delay <= Duration::from_secs(120));
+        assert_eq!(
+            parse_retry_after(&headers(&[(
+                "retry-after",
+                "Mon, 01 Jan 2024 00:00:00 +0000"
+            )])),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(
+            parse_retry_after(&headers(&[("retry-after", "soon")])),
+            None
+        );
+        assert_eq!(parse_retry_after(&HeaderMap::new()), None);
+    }
+
+    #[test]
+    fn parses_rate_limit_reset_only_when_exhausted() {
+        let reset = rfc3339(OffsetDateTime::now_utc() + time::Duration::minutes(1));
+        let delay = parse_rate_limit_reset(&headers(&[
+            ("x-ratelimit-remaining", "0"),
+            ("x-ratelimit-reset", &reset),
+        ]))
+        .unwrap();
+        assert!(delay > Duration::from_secs(50) && delay <= Duration::from_secs(60));
+        assert_eq!(
+            parse_rate_limit_reset(&headers(&[
+                ("x-ratelimit-remaining", "3"),
+                ("x-ratelimit-reset", &reset),
+            ])),
+            None
+        );
+        assert_eq!(
+            parse_rate_limit_reset(&headers(&[("x-ratelimit-remaining", "0")])),
+            None
+        );
+    }
+
+    #[test]
+    fn backs_off_exponentially_up_to_the_cap() {
+        for (attempt, base) in [(1, 1), (2, 2), (3, 4), (5, 16), (6, 30), (20, 30)] {
+            let base = Duration::from_secs(base);
+            let delay = backoff(attempt);
+            assert!(
+                delay >= base && delay <= base * 3 / 2,
+                "{attempt}: {delay:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn retries_ambiguous_errors_only_when_idempotent() {
+        for idempotent in [true, false] {
+            assert!(is_retryable(StatusCode::TOO_MANY_REQUESTS, idempotent));
+            assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE, idempotent));
+            assert!(!is_retryable(StatusCode::UNPROCESSABLE_ENTITY, idempotent));
+            assert!(!is_retryable(StatusCode::OK, idempotent));
+        }
+        assert!(!is_ambiguous(StatusCode::SERVICE_UNAVAILABLE));
+        for status in [
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::BAD_GATEWAY,
+            StatusCode::GATEWAY_TIMEOUT,
+        ] {
+            assert!(is_retryable(status, true));
+            assert!(!is_retryable(status, false));
+            assert!(is_ambiguous(status));
+        }
     }
 }
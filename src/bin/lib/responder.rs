@@ -0,0 +1,341 @@
+//! Built-in commands the streaming bot answers besides keyword replies. Each one is toggled by the
+//! `fediq.pbzweihander.dev/<id>-feature` annotation of the replies ConfigMap.
+
+use std::sync::LazyLock;
+
+use rand::{seq::IndexedRandom, Rng, RngCore};
+
+use crate::dice;
+
+pub trait Responder: Sync {
+    fn id(&self) -> &'static str;
+
+    fn respond(&self, text: &str, rng: &mut dyn RngCore) -> Option<String>;
+}
+
+/// Every responder, in the order they are tried.
+pub static RESPONDERS: [&dyn Responder; 6] =
+    [&Dice, &CoinFlip, &Choose, &EightBall, &Tarot, &RandomNumber];
+
+pub fn annotation_key(responder: &dyn Responder) -> String {
+    format!("fediq.pbzweihander.dev/{}-feature", responder.id())
+}
+
+/// Returns the id of the first responder that answered, with its answer.
+pub fn respond(
+    text: &str,
+    responders: &[&'static dyn Responder],
+    rng: &mut dyn RngCore,
+) -> Option<(&'static str, String)> {
+    responders.iter().find_map(|responder| {
+        responder
+            .respond(text, rng)
+            .map(|response| (responder.id(), response))
+    })
+}
+
+/// Returns the text after the first of `keywords` found as a whole word, ignoring ASCII case, so
+/// that `coin` does not answer `bitcoin`. Korean keywords may be followed by a particle, as in
+/// `동전을`.
+fn find_keyword<'a>(text: &'a str, keywords: &[&str]) -> Option<&'a str> {
+    fn is_word(c: char) -> bool {
+        c.is_alphanumeric() || c == '_'
+    }
+    // Whether `c` continues the word that `edge` of the keyword is part of.
+    fn joins(c: Option<char>, edge: char) -> bool {
+        is_word(edge) && c.is_some_and(|c| is_word(c) && c.is_ascii() == edge.is_ascii())
+    }
+
+    keywords.iter().find_map(|keyword| {
+        let first = keyword.chars().next()?;
+        let last = keyword.chars().next_back()?;
+        text.char_indices().find_map(|(index, _)| {
+            let end = index + keyword.len();
+            if !text
+                .get(index..end)
+                .is_some_and(|s| s.eq_ignore_ascii_case(keyword))
+            {
+                return None;
+            }
+            let before = text[..index].chars().next_back();
+            let after = text[end..].chars().next();
+            if joins(before, first) || (last.is_ascii() && joins(after, last)) {
+                return None;
+            }
+            Some(&text[end..])
+        })
+    })
+}
+
+struct Dice;
+
+impl Responder for Dice {
+    fn id(&self) -> &'static str {
+        "dice"
+    }
+
+    fn respond(&self, text: &str, mut rng: &mut dyn RngCore) -> Option<String> {
+        match dice::parse(text)? {
+            Ok(expr) => Some(expr.roll(&mut rng).to_string()),
+            Err(error) => {
+                tracing::info!(%error, "invalid dice expression");
+                Some(error.to_string())
+            }
+        }
+    }
+}
+
+struct CoinFlip;
+
+impl Responder for CoinFlip {
+    fn id(&self) -> &'static str {
+        "coin"
+    }
+
+    fn respond(&self, text: &str, rng: &mut dyn RngCore) -> Option<String> {
+        find_keyword(text, &["동전", "coin"])?;
+        Some(
+            if rng.random_bool(0.5) {
+                "앞면"
+            } else {
+                "뒷면"
+            }
+            .to_string(),
+        )
+    }
+}
+
+struct Choose;
+
+impl Responder for Choose {
+    fn id(&self) -> &'static str {
+        "choose"
+    }
+
+    fn respond(&self, text: &str, rng: &mut dyn RngCore) -> Option<String> {
+        static SEPARATOR: LazyLock<regex::Regex> = LazyLock::new(|| {
+            regex::Regex::new(r"(?i)\s+or\s+|\s+또는\s+|[,|/]").expect("failed to build regex")
+        });
+
+        let rest = find_keyword(text, &["choose", "골라줘", "골라"])?;
+        let options = SEPARATOR
+            .split(rest.trim_start_matches([':', ' ']))
+            .map(str::trim)
+            .filter(|option| !option.is_empty())
+            .collect::<Vec<_>>();
+        if options.len() < 2 {
+            return None;
+        }
+        options.choose(rng).map(|option| option.to_string())
+    }
+}
+
+const EIGHT_BALL_ANSWERS: [&str; 20] = [
+    "확실합니다.",
+    "분명히 그렇습니다.",
+    "의심의 여지가 없습니다.",
+    "틀림없이 그렇습니다.",
+    "믿어도 좋습니다.",
+    "제가 보기엔 그렇습니다.",
+    "아마도요.",
+    "전망이 좋습니다.",
+    "그렇습니다.",
+    "징조가 좋습니다.",
+    "흐릿하네요. 다시 물어보세요.",
+    "나중에 다시 물어보세요.",
+    "지금은 말하지 않는 게 좋겠습니다.",
+    "지금은 알 수 없습니다.",
+    "집중하고 다시 물어보세요.",
+    "기대하지 마세요.",
+    "제 대답은 아니오입니다.",
+    "제 정보에 따르면 아닙니다.",
+    "전망이 그리 좋지 않습니다.",
+    "매우 의심스럽습니다.",
+];
+
+struct EightBall;
+
+impl Responder for EightBall {
+    fn id(&self) -> &'static str {
+        "eight-ball"
+    }
+
+    fn respond(&self, text: &str, rng: &mut dyn RngCore) -> Option<String> {
+        find_keyword(text, &["8ball", "8-ball", "🎱"])?;
+        EIGHT_BALL_ANSWERS
+            .choose(rng)
+            .map(|answer| answer.to_string())
+    }
+}
+
+const MAJOR_ARCANA: [&str; 22] = [
+    "바보",
+    "마법사",
+    "여사제",
+    "여황제",
+    "황제",
+    "교황",
+    "연인",
+    "전차",
+    "힘",
+    "은둔자",
+    "운명의 수레바퀴",
+    "정의",
+    "매달린 사람",
+    "죽음",
+    "절제",
+    "악마",
+    "탑",
+    "별",
+    "달",
+    "태양",
+    "심판",
+    "세계",
+];
+const SUITS: [&str; 4] = ["완드", "컵", "소드", "펜타클"];
+const RANKS: [&str; 14] = [
+    "에이스",
+    "2",
+    "3",
+    "4",
+    "5",
+    "6",
+    "7",
+    "8",
+    "9",
+    "10",
+    "페이지",
+    "나이트",
+    "퀸",
+    "킹",
+];
+
+struct Tarot;
+
+impl Responder for Tarot {
+    fn id(&self) -> &'static str {
+        "tarot"
+    }
+
+    fn respond(&self, text: &str, rng: &mut dyn RngCore) -> Option<String> {
+        find_keyword(text, &["타로", "tarot"])?;
+        let index = rng.random_range(0..MAJOR_ARCANA.len() + SUITS.len() * RANKS.len());
+        let card = match MAJOR_ARCANA.get(index) {
+            Some(card) => card.to_string(),
+            None => {
+                let index = index - MAJOR_ARCANA.len();
+                format!(
+                    "{} {}",
+                    SUITS[index / RANKS.len()],
+                    RANKS[index % RANKS.len()]
+                )
+            }
+        };
+        let orientation = if rng.random_bool(0.5) {
+            "정방향"
+        } else {
+            "역방향"
+        };
+        Some(format!("{card} ({orientation})"))
+    }
+}
+
+struct RandomNumber;
+
+impl Responder for RandomNumber {
+    fn id(&self) -> &'static str {
+        "random-number"
+    }
+
+    fn respond(&self, text: &str, rng: &mut dyn RngCore) -> Option<String> {
+        static RANGE: LazyLock<regex::Regex> = LazyLock::new(|| {
+            regex::Regex::new(r"(?i)(?:random|랜덤|난수)\s*(-?\d+)(?:\s*(?:~|-|to)\s*(-?\d+))?")
+                .expect("failed to build regex")
+        });
+
+        let captures = RANGE.captures(text)?;
+        let first = captures.get(1)?.as_str().parse::<i64>().ok()?;
+        let (min, max) = match captures.get(2) {
+            Some(second) => {
+                let second = second.as_str().parse::<i64>().ok()?;
+                (first.min(second), first.max(second))
+            }
+            None if first >= 1 => (1, first),
+            None => return None,
+        };
+        Some(rng.random_range(min..=max).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use super::*;
+
+    fn respond_with(responder: &dyn Responder, text: &str) -> Option<String> {
+        responder.respond(text, &mut StdRng::seed_from_u64(0))
+    }
+
+    #[test]
+    fn chooses_one_of_the_options() {
+        for text in [
+            "choose 짜장 or 짬뽕 or 볶음밥",
+            "골라줘: 짜장, 짬뽕, 볶음밥",
+            "Choose 짜장 / 짬뽕 / 볶음밥",
+        ] {
+            let chosen = respond_with(&Choose, text).unwrap();
+            assert!(
+                ["짜장", "짬뽕", "볶음밥"].contains(&chosen.as_str()),
+                "{text}"
+            );
+        }
+        assert_eq!(respond_with(&Choose, "choose 짜장"), None);
+        assert_eq!(respond_with(&Choose, "the chooser: 짜장, 짬뽕"), None);
+        assert_eq!(respond_with(&Choose, "짜장 짬뽕"), None);
+    }
+
+    #[test]
+    fn picks_random_number_in_range() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let number = RandomNumber
+                .respond("랜덤 10~20", &mut rng)
+                .unwrap()
+                .parse::<i64>()
+                .unwrap();
+            assert!((10..=20).contains(&number));
+            let number = RandomNumber
+                .respond("random 6", &mut rng)
+                .unwrap()
+                .parse::<i64>()
+                .unwrap();
+            assert!((1..=6).contains(&number));
+        }
+        assert_eq!(respond_with(&RandomNumber, "random 0"), None);
+        assert_eq!(respond_with(&RandomNumber, "random"), None);
+    }
+
+    #[test]
+    fn answers_only_when_asked() {
+        assert!(respond_with(&CoinFlip, "동전 던져줘").is_some());
+        assert!(respond_with(&EightBall, "🎱 내일 비 와?").is_some());
+        assert!(respond_with(&Tarot, "오늘의 타로").is_some());
+        assert!(respond_with(&CoinFlip, "동전을 던져줘").is_some());
+        assert!(respond_with(&CoinFlip, "flip a coin!").is_some());
+        assert_eq!(respond_with(&CoinFlip, "안녕"), None);
+        assert_eq!(respond_with(&CoinFlip, "bitcoin 얼마야"), None);
+        assert_eq!(respond_with(&Tarot, "tarots"), None);
+        assert_eq!(respond_with(&Tarot, "안녕"), None);
+    }
+
+    #[test]
+    fn tries_responders_in_order() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let (id, _) = respond("1d6 동전", &RESPONDERS, &mut rng).unwrap();
+        assert_eq!(id, "dice");
+        let (id, _) = respond("1d6 동전", &[&CoinFlip], &mut rng).unwrap();
+        assert_eq!(id, "coin");
+        assert_eq!(respond("1d6 동전", &[], &mut rng), None);
+    }
+}
@@ -1,18 +1,41 @@
+#[path = "lib/feed.rs"]
+mod feed;
+#[path = "lib/markov.rs"]
+mod markov;
 #[path = "lib/post.rs"]
 mod post;
 
-use k8s_openapi::api::core::v1::ConfigMap;
+use std::collections::BTreeMap;
+
+use eyre::WrapErr;
+use k8s_openapi::api::{batch::v1::CronJob, core::v1::ConfigMap};
 use kube::{
-    api::{Patch, PatchParams},
+    api::{DeleteParams, Patch, PatchParams, PropagationPolicy},
     core::ObjectMeta,
     Api,
 };
 use rand::seq::IteratorRandom;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use time::{Duration, OffsetDateTime};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use ulid::Ulid;
 
+const POST_HISTORY_LIMIT: usize = 50;
+/// A scheduled post run this long after its time is a leftover of a failed run or delete, since
+/// its CronJob repeats yearly. A run before its time is the same date of an earlier year.
+const SCHEDULED_POST_MAX_DELAY: Duration = Duration::days(1);
+/// Key of the post history ConfigMap holding the post counters.
+const STATS_KEY: &str = "stats";
+const STATS_DAYS: usize = 30;
+const STATS_STATUS_LIMIT: usize = 1000;
+const FEED_URL_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/feed-url";
+const FEED_TEMPLATE_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/feed-template";
+/// Key of the quote dedup ConfigMap listing the ids of feed entries already posted.
+const FEED_SEEN_KEY: &str = "feed-seen";
+const GENERATION_MODE_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/generation-mode";
+const MARKOV_ORDER_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/markov-order";
+const MARKOV_TOKENIZER_ANNOTATION_KEY: &str = "fediq.pbzweihander.dev/markov-tokenizer";
+
 #[derive(Deserialize)]
 struct Config {
     domain: String,
@@ -21,6 +44,94 @@ struct Config {
     quotes_configmap_name: String,
     quote_dedup_configmap_name: String,
     dedup_duration_minutes: u32,
+    #[serde(default)]
+    quote_id: Option<Ulid>,
+    #[serde(default)]
+    quote_text: Option<String>,
+    #[serde(default)]
+    scheduled_cronjob_name: Option<String>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    scheduled_at: Option<OffsetDateTime>,
+    #[serde(default)]
+    post_history_configmap_name: Option<String>,
+    #[serde(default)]
+    dry_run: bool,
+    #[serde(default)]
+    dedup_fallback: DedupFallback,
+    /// Overrides the feed URL of the quotes ConfigMap, for trying out a local feed file.
+    #[serde(default)]
+    feed_url: Option<String>,
+}
+
+/// What to post when every quote is still within its dedup window.
+#[derive(Deserialize, Default, Clone, Copy, Debug)]
+#[serde(rename_all = "snake_case")]
+enum DedupFallback {
+    #[default]
+    Skip,
+    LeastRecentlyPosted,
+    ShrinkWindow,
+}
+
+#[derive(Deserialize, Serialize)]
+struct PostHistoryEntry {
+    #[serde(with = "time::serde::rfc3339")]
+    posted_at: OffsetDateTime,
+    quote_id: Option<Ulid>,
+    text: String,
+    status_id: Option<String>,
+    url: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct QuotePostStats {
+    count: u64,
+    #[serde(with = "time::serde::rfc3339::option")]
+    last_posted_at: Option<OffsetDateTime>,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct PostStats {
+    #[serde(default)]
+    quotes: BTreeMap<Ulid, QuotePostStats>,
+    /// Posts per UTC date, for the last [`STATS_DAYS`] days with posts.
+    #[serde(default)]
+    daily: BTreeMap<String, u64>,
+    /// Quote of each recent status, so that the streaming bot can count interactions per quote.
+    #[serde(default)]
+    statuses: Vec<(String, Ulid)>,
+}
+
+impl PostStats {
+    fn record(&mut self, now: OffsetDateTime, quote_id: Option<Ulid>, status_id: &str) {
+        *self.daily.entry(now.date().to_string()).or_default() += 1;
+        while self.daily.len() > STATS_DAYS {
+            self.daily.pop_first();
+        }
+        let Some(quote_id) = quote_id else {
+            return;
+        };
+        let quote = self.quotes.entry(quote_id).or_default();
+        quote.count += 1;
+        quote.last_posted_at = Some(now);
+        self.statuses.push((status_id.to_string(), quote_id));
+        if self.statuses.len() > STATS_STATUS_LIMIT {
+            self.statuses
+                .drain(..self.statuses.len() - STATS_STATUS_LIMIT);
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PostPreview {
+    #[serde(with = "time::serde::rfc3339")]
+    generated_at: OffsetDateTime,
+    quote_id: Option<Ulid>,
+    text: String,
+    payload: String,
+    #[serde(with = "time::serde::rfc3339::option")]
+    dedup_until: Option<OffsetDateTime>,
 }
 
 #[tokio::main]
@@ -44,14 +155,39 @@ async fn main() {
         .await
         .expect("failed to initialize Kubernetes client");
 
-    let configmap_api = Api::<ConfigMap>::default_namespaced(kube_client);
+    let configmap_api = Api::<ConfigMap>::default_namespaced(kube_client.clone());
+    let cronjob_api = Api::<CronJob>::default_namespaced(kube_client);
+
+    if let Some(scheduled_at) = config.scheduled_at {
+        if now - scheduled_at > SCHEDULED_POST_MAX_DELAY {
+            tracing::warn!(%scheduled_at, "scheduled post is long overdue, skipping post");
+            delete_scheduled_cronjob(&cronjob_api, &config).await;
+            return;
+        }
+        if now < scheduled_at {
+            tracing::info!(%scheduled_at, "scheduled post is not due yet, skipping post");
+            return;
+        }
+    }
 
-    let quotes_configmap_data = configmap_api
+    let quotes_configmap = configmap_api
         .get(&config.quotes_configmap_name)
         .await
-        .expect("failed to get quotes Kubernetes ConfigMap")
-        .data
-        .unwrap_or_default();
+        .expect("failed to get quotes Kubernetes ConfigMap");
+    let annotations = quotes_configmap.metadata.annotations.unwrap_or_default();
+    let markov_settings = markov_settings(&annotations);
+    // Only the override may read local files, not the URL set on the dashboard.
+    let (feed_url, allow_file) = match config.feed_url.clone() {
+        Some(feed_url) => (Some(feed_url), true),
+        None => (annotations.get(FEED_URL_ANNOTATION_KEY).cloned(), false),
+    };
+    let feed_url = feed_url.filter(|url| !url.is_empty());
+    let feed_template = annotations
+        .get(FEED_TEMPLATE_ANNOTATION_KEY)
+        .map(String::as_str)
+        .filter(|template| !template.trim().is_empty())
+        .unwrap_or(feed::DEFAULT_TEMPLATE);
+    let mut quotes_configmap_data = quotes_configmap.data.unwrap_or_default();
 
     let quote_dedup_configmap = configmap_api
         .get_opt(&config.quote_dedup_configmap_name)
@@ -61,63 +197,417 @@ async fn main() {
         .and_then(|cm| cm.data)
         .unwrap_or_default();
 
-    let quotes = quotes_configmap_data
-        .into_iter()
-        .filter_map(|(key, value)| {
-            let id = Ulid::from_string(&key).ok()?;
-            let sent_recently = quote_dedup_configmap_data
-                .get(&key)
-                .and_then(|value| {
-                    OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
-                        .ok()
-                })
-                .map(|timestamp| timestamp > now)
-                .unwrap_or(false);
-            if sent_recently {
-                None
-            } else {
-                Some((id, value))
-            }
-        });
+    let feed_entry = match (&feed_url, &config.quote_text, config.quote_id) {
+        (Some(feed_url), None, None) => {
+            next_feed_entry(
+                feed_url,
+                allow_file,
+                feed_template,
+                &quote_dedup_configmap_data,
+            )
+            .await
+        }
+        _ => None,
+    };
+    let mut feed_seen = None;
+
+    let quote = if let Some(quote_text) = config.quote_text.clone() {
+        Some((None, quote_text))
+    } else if let Some(quote_id) = config.quote_id {
+        let quote = quotes_configmap_data.remove(&quote_id.to_string());
+        if quote.is_none() {
+            tracing::warn!(%quote_id, "scheduled quote not found");
+        }
+        quote.map(|quote| (Some(quote_id), quote))
+    } else if let Some((text, seen)) = feed_entry {
+        feed_seen = Some(seen);
+        Some((None, text))
+    } else if let Some(text) = markov_settings.and_then(|(order, tokenizer)| {
+        let quotes = quotes_configmap_data
+            .iter()
+            .filter(|(key, _)| Ulid::from_string(key).is_ok())
+            .map(|(_, value)| value.as_str())
+            .collect::<Vec<_>>();
+        let text = markov::generate(&quotes, order, tokenizer, &mut rng);
+        if text.is_none() {
+            tracing::warn!(
+                quotes = quotes.len(),
+                "failed to generate a novel text, posting a quote instead"
+            );
+        }
+        text
+    }) {
+        Some((None, text))
+    } else {
+        let quotes = quotes_configmap_data
+            .into_iter()
+            .filter_map(|(key, value)| {
+                let id = Ulid::from_string(&key).ok()?;
+                let dedup_until = quote_dedup_configmap_data
+                    .get(&key)
+                    .and_then(|value| {
+                        OffsetDateTime::parse(value, &time::format_description::well_known::Rfc3339)
+                            .ok()
+                    })
+                    .filter(|timestamp| *timestamp > now);
+                Some((id, value, dedup_until))
+            })
+            .collect::<Vec<_>>();
+        let fresh_quote = quotes
+            .iter()
+            .filter(|(_, _, dedup_until)| dedup_until.is_none())
+            .choose(&mut rng);
+        let quote = if fresh_quote.is_some() || quotes.is_empty() {
+            fresh_quote
+        } else {
+            select_deduped_quote(&quotes, &config, now, &mut rng)
+        };
+        quote.map(|(id, quote, _)| (Some(*id), quote.clone()))
+    };
 
-    let quote = quotes.choose(&mut rng);
     let Some((quote_id, quote)) = quote else {
+        delete_scheduled_cronjob(&cronjob_api, &config).await;
         return;
     };
 
-    match config.software.as_str() {
-        "mastodon" => post::post_mastodon(&config.domain, &config.access_token, &quote, None)
-            .await
-            .expect("failed to post to Mastodon"),
-        "misskey" => post::post_misskey(&config.domain, &config.access_token, &quote, None)
-            .await
-            .expect("failed to post to Misskey"),
+    let dedup_until =
+        quote_id.map(|_| now + Duration::minutes(config.dedup_duration_minutes as i64));
+
+    if config.dry_run {
+        let payload = match config.software.as_str() {
+            "mastodon" => {
+                serde_json::to_string(&post::mastodon_status_req(&quote, None, "unlisted", None))
+            }
+            "misskey" => serde_json::to_string(&post::misskey_note_req(&quote, None, "home", None)),
+            software => {
+                panic!("unsupported software `{software}`");
+            }
+        }
+        .expect("failed to serialize payload");
+        tracing::info!(payload, ?quote_id, ?dedup_until, "dry run, skipping post");
+
+        if let Some(post_history_configmap_name) = &config.post_history_configmap_name {
+            let preview = PostPreview {
+                generated_at: now,
+                quote_id,
+                text: quote,
+                payload,
+                dedup_until,
+            };
+            if let Err(error) =
+                record_post_preview(&configmap_api, post_history_configmap_name, preview).await
+            {
+                tracing::error!(?error, "failed to record post preview");
+            }
+        }
+        return;
+    }
+
+    let res = match config.software.as_str() {
+        "mastodon" => post::post_mastodon(
+            &config.domain,
+            &config.access_token,
+            &quote,
+            None,
+            "unlisted",
+            None,
+        )
+        .await
+        .wrap_err("failed to post to Mastodon"),
+        "misskey" => post::post_misskey(
+            &config.domain,
+            &config.access_token,
+            &quote,
+            None,
+            "home",
+            None,
+        )
+        .await
+        .wrap_err("failed to post to Misskey"),
         software => {
             panic!("unsupported software `{software}`");
         }
+    };
+
+    if let Some(post_history_configmap_name) = &config.post_history_configmap_name {
+        let entry = PostHistoryEntry {
+            posted_at: now,
+            quote_id,
+            text: quote.clone(),
+            status_id: res.as_ref().ok().map(|posted| posted.id.clone()),
+            url: res.as_ref().ok().map(|posted| posted.url.clone()),
+            error: res.as_ref().err().map(|error| format!("{error:?}")),
+        };
+        // The post is already out, so failing here must not fail the Job and trigger a repost.
+        if let Err(error) =
+            record_post_history(&configmap_api, post_history_configmap_name, entry).await
+        {
+            tracing::error!(?error, "failed to record post history");
+        }
     }
 
-    let dedup_timestamp = now + Duration::minutes(config.dedup_duration_minutes as i64);
-    quote_dedup_configmap_data.insert(
-        quote_id.to_string(),
-        dedup_timestamp
-            .format(&time::format_description::well_known::Rfc3339)
-            .expect("failed to format OffsetDateTime"),
-    );
+    let posted = res.expect("failed to post");
+    tracing::info!(id = posted.id, url = posted.url, "posted");
+
+    if let Some(post_history_configmap_name) = &config.post_history_configmap_name {
+        if let Err(error) = record_post_stats(
+            &configmap_api,
+            post_history_configmap_name,
+            now,
+            quote_id,
+            &posted.id,
+        )
+        .await
+        {
+            tracing::error!(?error, "failed to record post stats");
+        }
+    }
+
+    if dedup_until.is_some() || feed_seen.is_some() {
+        if let (Some(quote_id), Some(dedup_timestamp)) = (quote_id, dedup_until) {
+            quote_dedup_configmap_data.insert(
+                quote_id.to_string(),
+                dedup_timestamp
+                    .format(&time::format_description::well_known::Rfc3339)
+                    .expect("failed to format OffsetDateTime"),
+            );
+        }
+        if let Some(feed_seen) = feed_seen {
+            quote_dedup_configmap_data.insert(
+                FEED_SEEN_KEY.to_string(),
+                serde_json::to_string(&feed_seen).expect("failed to serialize seen feed entries"),
+            );
+        }
+
+        configmap_api
+            .patch(
+                &config.quote_dedup_configmap_name,
+                &PatchParams::apply("fediq.pbzweihander.dev").force(),
+                &Patch::Apply(ConfigMap {
+                    metadata: ObjectMeta {
+                        name: Some(config.quote_dedup_configmap_name.clone()),
+                        ..Default::default()
+                    },
+                    data: Some(quote_dedup_configmap_data),
+                    ..Default::default()
+                }),
+            )
+            .await
+            .expect("failed to patch Kubernetes ConfigMap");
+    }
+
+    delete_scheduled_cronjob(&cronjob_api, &config).await;
+}
+
+/// Returns the oldest feed entry not posted yet, rendered by the template, with the ids of the
+/// entries to remember as posted once it is out.
+async fn next_feed_entry(
+    feed_url: &str,
+    allow_file: bool,
+    template: &str,
+    quote_dedup_configmap_data: &BTreeMap<String, String>,
+) -> Option<(String, Vec<String>)> {
+    let entries = match feed::fetch(feed_url, allow_file).await {
+        Ok(xml) => feed::parse(&xml),
+        Err(error) => {
+            tracing::error!(?error, "failed to fetch feed, posting a quote instead");
+            return None;
+        }
+    };
+    let seen = quote_dedup_configmap_data
+        .get(FEED_SEEN_KEY)
+        .and_then(|v| serde_json::from_str::<Vec<String>>(v).ok());
+    let entry = match &seen {
+        Some(seen) => entries.iter().find(|entry| !seen.contains(&entry.id)),
+        // Only the newest entry counts as new at first, or the whole backlog would be posted.
+        None => entries.last(),
+    };
+    let Some(entry) = entry else {
+        tracing::info!(entries = entries.len(), "no new feed entry");
+        return None;
+    };
+    // Entries that fell off the feed are forgotten, so the list is no longer than the feed.
+    let seen = entries
+        .iter()
+        .map(|entry| entry.id.clone())
+        .filter(|id| *id == entry.id || seen.as_ref().is_none_or(|seen| seen.contains(id)))
+        .collect();
+    tracing::info!(id = entry.id, "posting feed entry");
+    Some((entry.render(template), seen))
+}
+
+/// Returns the order and tokenizer of the Markov chain if the quotes are to be generated from
+/// rather than posted verbatim.
+fn markov_settings(annotations: &BTreeMap<String, String>) -> Option<(usize, markov::Tokenizer)> {
+    if annotations
+        .get(GENERATION_MODE_ANNOTATION_KEY)
+        .map(String::as_str)
+        != Some("markov")
+    {
+        return None;
+    }
+    let order = annotations
+        .get(MARKOV_ORDER_ANNOTATION_KEY)
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(2);
+    let tokenizer = annotations
+        .get(MARKOV_TOKENIZER_ANNOTATION_KEY)
+        .and_then(|v| markov::Tokenizer::parse(v))
+        .unwrap_or_default();
+    Some((order, tokenizer))
+}
+
+/// Picks a quote by `config.dedup_fallback` when every quote is within its dedup window.
+fn select_deduped_quote<'a>(
+    quotes: &'a [(Ulid, String, Option<OffsetDateTime>)],
+    config: &Config,
+    now: OffsetDateTime,
+    rng: &mut impl rand::Rng,
+) -> Option<&'a (Ulid, String, Option<OffsetDateTime>)> {
+    match config.dedup_fallback {
+        DedupFallback::Skip => {
+            tracing::warn!(
+                quotes = quotes.len(),
+                "every quote is within its dedup window, skipping post"
+            );
+            None
+        }
+        DedupFallback::LeastRecentlyPosted => {
+            tracing::warn!(
+                "every quote is within its dedup window, posting the least recently posted one"
+            );
+            quotes.iter().min_by_key(|(_, _, dedup_until)| *dedup_until)
+        }
+        DedupFallback::ShrinkWindow => {
+            // The dedup timestamp is when the window ends, so the quote was posted one window
+            // before it.
+            let dedup_duration = Duration::minutes(config.dedup_duration_minutes as i64);
+            let mut window = dedup_duration;
+            loop {
+                window /= 2;
+                let quote = quotes
+                    .iter()
+                    .filter(|(_, _, dedup_until)| {
+                        dedup_until
+                            .is_none_or(|dedup_until| dedup_until - dedup_duration + window <= now)
+                    })
+                    .choose(rng);
+                if quote.is_some() || window.is_zero() {
+                    tracing::warn!(
+                        window_minutes = window.whole_minutes(),
+                        "every quote is within its dedup window, shrank the window"
+                    );
+                    return quote;
+                }
+            }
+        }
+    }
+}
+
+async fn patch_post_history_configmap(
+    configmap_api: &Api<ConfigMap>,
+    configmap_name: &str,
+    f: impl FnOnce(&mut BTreeMap<String, String>) -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    let mut data = configmap_api
+        .get_opt(configmap_name)
+        .await
+        .wrap_err("failed to get post history Kubernetes ConfigMap")?
+        .and_then(|cm| cm.data)
+        .unwrap_or_default();
+    f(&mut data)?;
 
     configmap_api
         .patch(
-            &config.quote_dedup_configmap_name,
+            configmap_name,
             &PatchParams::apply("fediq.pbzweihander.dev").force(),
             &Patch::Apply(ConfigMap {
                 metadata: ObjectMeta {
-                    name: Some(config.quote_dedup_configmap_name.clone()),
+                    name: Some(configmap_name.to_string()),
                     ..Default::default()
                 },
-                data: Some(quote_dedup_configmap_data),
+                data: Some(data),
                 ..Default::default()
             }),
         )
         .await
-        .expect("failed to patch Kubernetes ConfigMap");
+        .wrap_err("failed to patch post history Kubernetes ConfigMap")?;
+
+    Ok(())
+}
+
+async fn record_post_history(
+    configmap_api: &Api<ConfigMap>,
+    configmap_name: &str,
+    entry: PostHistoryEntry,
+) -> eyre::Result<()> {
+    patch_post_history_configmap(configmap_api, configmap_name, |data| {
+        let mut post_history = data
+            .get("data")
+            .and_then(|v| serde_json::from_str::<Vec<PostHistoryEntry>>(v).ok())
+            .unwrap_or_default();
+        post_history.insert(0, entry);
+        post_history.truncate(POST_HISTORY_LIMIT);
+        data.insert(
+            "data".to_string(),
+            serde_json::to_string(&post_history).wrap_err("failed to serialize post history")?,
+        );
+        Ok(())
+    })
+    .await
+}
+
+async fn record_post_stats(
+    configmap_api: &Api<ConfigMap>,
+    configmap_name: &str,
+    now: OffsetDateTime,
+    quote_id: Option<Ulid>,
+    status_id: &str,
+) -> eyre::Result<()> {
+    patch_post_history_configmap(configmap_api, configmap_name, |data| {
+        let mut stats = data
+            .get(STATS_KEY)
+            .and_then(|v| serde_json::from_str::<PostStats>(v).ok())
+            .unwrap_or_default();
+        stats.record(now, quote_id, status_id);
+        data.insert(
+            STATS_KEY.to_string(),
+            serde_json::to_string(&stats).wrap_err("failed to serialize post stats")?,
+        );
+        Ok(())
+    })
+    .await
+}
+
+async fn record_post_preview(
+    configmap_api: &Api<ConfigMap>,
+    configmap_name: &str,
+    preview: PostPreview,
+) -> eyre::Result<()> {
+    patch_post_history_configmap(configmap_api, configmap_name, |data| {
+        data.insert(
+            "preview".to_string(),
+            serde_json::to_string(&preview).wrap_err("failed to serialize post preview")?,
+        );
+        Ok(())
+    })
+    .await
+}
+
+async fn delete_scheduled_cronjob(cronjob_api: &Api<CronJob>, config: &Config) {
+    let Some(scheduled_cronjob_name) = &config.scheduled_cronjob_name else {
+        return;
+    };
+
+    // Orphan the running Job, or the garbage collector would kill this very Pod.
+    cronjob_api
+        .delete(
+            scheduled_cronjob_name,
+            &DeleteParams {
+                propagation_policy: Some(PropagationPolicy::Orphan),
+                ..Default::default()
+            },
+        )
+        .await
+        .expect("failed to delete scheduled post Kubernetes CronJob");
 }
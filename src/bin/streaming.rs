@@ -1,25 +1,187 @@
+#[path = "lib/dice.rs"]
+mod dice;
+#[path = "lib/hash_ring.rs"]
+mod hash_ring;
+#[path = "lib/interact.rs"]
+mod interact;
+#[path = "../normalize.rs"]
+mod normalize;
 #[path = "lib/post.rs"]
 mod post;
+#[path = "lib/responder.rs"]
+mod responder;
 
-use std::{collections::BTreeMap, sync::LazyLock};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet, VecDeque},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
 
 use eyre::WrapErr;
 use futures_util::{SinkExt, StreamExt};
-use itertools::Itertools;
-use k8s_openapi::api::core::v1::ConfigMap;
-use kube::Api;
-use rand::seq::IteratorRandom;
+use k8s_openapi::api::core::v1::{ConfigMap, Secret};
+use kube::{
+    api::{Patch, PatchParams},
+    core::ObjectMeta,
+    runtime::{reflector, watcher, WatchStreamExt},
+    Api, ResourceExt,
+};
+use rand::{rngs::StdRng, seq::IteratorRandom, SeedableRng};
 use reqwest_websocket::RequestBuilderExt;
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+use tokio::sync::watch;
+use tracing::Instrument;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 use ulid::Ulid;
 
 #[derive(Deserialize)]
 struct Config {
+    replies_configmap_name: String,
+    #[serde(default)]
+    streaming_state_configmap_name: Option<String>,
+    #[serde(default)]
+    streaming_config_secret_name: Option<String>,
+    /// Where the poster records which quote each of its posts was, for the quote stats.
+    #[serde(default)]
+    post_history_configmap_name: Option<String>,
+}
+
+fn default_shard_count() -> usize {
+    1
+}
+
+/// With `multiplexed`, one process serves many bots instead of the single bot in [`Config`].
+#[derive(Deserialize)]
+struct MultiplexConfig {
+    #[serde(default)]
+    multiplexed: bool,
+    #[serde(default = "default_shard_count")]
+    shard_count: usize,
+    /// Falls back to the ordinal in the StatefulSet pod name.
+    #[serde(default)]
+    shard_index: Option<usize>,
+    #[serde(default)]
+    hostname: Option<String>,
+}
+
+/// Settings that can change while the bot is running. They are read from the streaming config
+/// Secret when there is one, using the same keys as the environment variables, and from the
+/// environment otherwise.
+#[derive(Clone, PartialEq, Deserialize)]
+struct ConnectionConfig {
+    domain: String,
+    access_token: String,
+    software: String,
+}
+
+const PING_INTERVAL: Duration = Duration::from_secs(30);
+const PONG_TIMEOUT: Duration = Duration::from_secs(90);
+const INITIAL_RECONNECT_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_BACKOFF: Duration = Duration::from_secs(5 * 60);
+/// A connection that stayed up this long counts as healthy, so the next failure starts the
+/// backoff over.
+const HEALTHY_CONNECTION_DURATION: Duration = Duration::from_secs(60);
+const CATCH_UP_MAX_PAGES: usize = 5;
+const LAST_SEEN_ID_KEY: &str = "last-seen-id";
+const STATUS_KEY: &str = "status";
+const STATS_KEY: &str = "stats";
+const STATS_DAYS: usize = 30;
+/// Stats are kept in memory and saved this often, and once more on shutdown.
+const STATS_FLUSH_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Used when the emoji pool for reacting back is empty.
+const DEFAULT_REACTION: &str = "❤️";
+const STREAMING_CONFIG_LABEL_KEY: &str = "fediq.pbzweihander.dev/streaming-config";
+
+/// The quote each post of the poster was made from, by status id.
+type QuoteStatuses = HashMap<String, Ulid>;
+
+struct Bot {
     domain: String,
     access_token: String,
     software: String,
     replies_configmap_name: String,
+    replies: reflector::Store<ConfigMap>,
+    state_configmap_name: Option<String>,
+    post_history_configmap_name: Option<String>,
+    post_history: Option<reflector::Store<ConfigMap>>,
+    kube_client: kube::Client,
+    /// The reply config with its keywords compiled, and the ConfigMap version it was built from.
+    reply_config: Mutex<Option<(String, Arc<ReplyConfig>)>>,
+    /// The quote of each recent post of the poster, and the ConfigMap version it was read from.
+    quote_statuses: Mutex<Option<(String, Arc<QuoteStatuses>)>>,
+}
+
+/// State that outlives a single websocket connection.
+struct StreamState {
+    limiter: ReplyLimiter,
+    conversations: Conversations,
+    last_seen_id: Option<String>,
+    /// Whether the last seen id changed since it was last saved.
+    last_seen_id_dirty: bool,
+    stats: StreamingStats,
+    /// Whether the stats changed since they were last saved.
+    stats_dirty: bool,
+    stats_saved_at: Instant,
+    connection_config: watch::Receiver<ConnectionConfig>,
+}
+
+/// Why a websocket connection ended without an error.
+enum StreamEnd {
+    /// Shut down, or closed by the server.
+    Closed,
+    ConfigChanged,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+struct Engagement {
+    replies: u64,
+    favourites: u64,
+    reblogs: u64,
+}
+
+#[derive(Default, Deserialize, Serialize)]
+#[serde(default)]
+struct StreamingStats {
+    /// Replies sent per UTC date, for the last [`STATS_DAYS`] days with replies.
+    daily_replies: BTreeMap<String, u64>,
+    /// How many mentions each keyword, script or built-in responder answered.
+    triggers: BTreeMap<String, u64>,
+    /// Interactions with the posts of each quote.
+    quotes: BTreeMap<Ulid, Engagement>,
+}
+
+impl StreamingStats {
+    fn record_response(&mut self, trigger: &str) {
+        *self.triggers.entry(trigger.to_string()).or_default() += 1;
+    }
+
+    fn record_reply(&mut self, now: OffsetDateTime) {
+        *self
+            .daily_replies
+            .entry(now.date().to_string())
+            .or_default() += 1;
+        while self.daily_replies.len() > STATS_DAYS {
+            self.daily_replies.pop_first();
+        }
+    }
+
+    fn record_engagement(&mut self, quote_id: Ulid, interaction: Option<Interaction>) {
+        let engagement = self.quotes.entry(quote_id).or_default();
+        match interaction {
+            None => engagement.replies += 1,
+            Some(Interaction::Favourite) => engagement.favourites += 1,
+            Some(Interaction::Reblog) => engagement.reblogs += 1,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct StreamingStatus<'a> {
+    state: &'a str,
+    #[serde(with = "time::serde::rfc3339")]
+    updated_at: OffsetDateTime,
+    error: Option<String>,
 }
 
 async fn shutdown_signal(stopper: stopper::Stopper) {
@@ -61,9 +223,8 @@ async fn main() {
         .with(tracing_subscriber::fmt::layer())
         .init();
 
-    let mut rng = rand::rng();
-    let config =
-        envy::from_env::<Config>().expect("failed to parse config from environment variables");
+    let multiplex_config = envy::from_env::<MultiplexConfig>()
+        .expect("failed to parse config from environment variables");
 
     let kube_client = kube::Client::try_default()
         .await
@@ -72,349 +233,2312 @@ async fn main() {
     let stopper = stopper::Stopper::new();
     tokio::spawn(shutdown_signal(stopper.clone()));
 
+    if multiplex_config.multiplexed {
+        run_multiplexed(kube_client, multiplex_config, stopper).await;
+        return;
+    }
+
+    let config =
+        envy::from_env::<Config>().expect("failed to parse config from environment variables");
+
+    // Without a Secret to watch, the sender is dropped and the config never changes.
+    let connection_config = match &config.streaming_config_secret_name {
+        Some(secret_name) => watch_connection_config(&kube_client, secret_name)
+            .await
+            .expect("failed to get streaming config Kubernetes Secret"),
+        None => {
+            watch::channel(
+                envy::from_env::<ConnectionConfig>()
+                    .expect("failed to parse config from environment variables"),
+            )
+            .1
+        }
+    };
+
+    run_bot(kube_client, config, connection_config, stopper).await;
+}
+
+/// Streams mentions for a single bot until `stopper` is stopped.
+async fn run_bot(
+    kube_client: kube::Client,
+    config: Config,
+    connection_config: watch::Receiver<ConnectionConfig>,
+    stopper: stopper::Stopper,
+) {
+    let mut rng = StdRng::from_os_rng();
+
+    let replies = watch_configmap(&kube_client, &config.replies_configmap_name, &stopper);
+    if let Err(error) = replies.wait_until_ready().await {
+        tracing::error!(?error, "failed to wait for replies Kubernetes ConfigMap");
+    }
+    let post_history = config
+        .post_history_configmap_name
+        .as_deref()
+        .map(|configmap_name| watch_configmap(&kube_client, configmap_name, &stopper));
+    if let Some(post_history) = &post_history {
+        if let Err(error) = post_history.wait_until_ready().await {
+            tracing::error!(
+                ?error,
+                "failed to wait for post history Kubernetes ConfigMap"
+            );
+        }
+    }
+
+    let ConnectionConfig {
+        domain,
+        access_token,
+        software,
+    } = connection_config.borrow().clone();
+    let mut bot = Bot {
+        domain,
+        access_token,
+        software,
+        replies_configmap_name: config.replies_configmap_name,
+        replies,
+        state_configmap_name: config.streaming_state_configmap_name,
+        post_history_configmap_name: config.post_history_configmap_name,
+        post_history,
+        kube_client,
+        reply_config: Mutex::default(),
+        quote_statuses: Mutex::default(),
+    };
+    let last_seen_id = load_last_seen_id(&bot).await.unwrap_or_else(|error| {
+        tracing::error!(?error, "failed to load last seen id");
+        None
+    });
+    let stats = load_stats(&bot).await.unwrap_or_else(|error| {
+        tracing::error!(?error, "failed to load stats");
+        StreamingStats::default()
+    });
+    let mut state = StreamState {
+        limiter: ReplyLimiter::default(),
+        conversations: Conversations::default(),
+        last_seen_id,
+        last_seen_id_dirty: false,
+        stats,
+        stats_dirty: false,
+        stats_saved_at: Instant::now(),
+        connection_config,
+    };
+
+    let mut attempt = 0;
     loop {
-        let res = match config.software.as_ref() {
-            "mastodon" => {
-                stream_mastodon(
-                    &config.domain,
-                    &config.access_token,
-                    &config.replies_configmap_name,
-                    &kube_client,
-                    &mut rng,
-                    &stopper,
-                )
-                .await
+        let connected_at = Instant::now();
+        save_status(&bot, "connecting", None).await;
+        let res = match bot.software.as_ref() {
+            "mastodon" => stream_mastodon(&bot, &mut state, &mut rng, &stopper).await,
+            "misskey" => stream_misskey(&bot, &mut state, &mut rng, &stopper).await,
+            software => {
+                tracing::error!(software, "unsupported software");
+                break;
             }
-            "misskey" => {
-                stream_misskey(
-                    &config.domain,
-                    &config.access_token,
-                    &config.replies_configmap_name,
-                    &kube_client,
-                    &mut rng,
-                    &stopper,
-                )
-                .await
+        };
+        match res {
+            Ok(StreamEnd::Closed) if stopper.is_stopped() => break,
+            Ok(StreamEnd::ConfigChanged) => {
+                tracing::info!("streaming config changed. reconnecting...");
+                attempt = 0;
+            }
+            res => {
+                if connected_at.elapsed() >= HEALTHY_CONNECTION_DURATION {
+                    attempt = 0;
+                }
+                attempt += 1;
+                let delay = reconnect_backoff(attempt);
+                let error = if let Err(error) = res {
+                    tracing::error!(?error, attempt, ?delay, "failed to stream. retrying...");
+                    Some(format!("{error:#}"))
+                } else {
+                    tracing::warn!(attempt, ?delay, "stream closed by server. reconnecting...");
+                    None
+                };
+                save_status(&bot, "reconnecting", error).await;
+                let wait = async {
+                    tokio::select! {
+                        _ = tokio::time::sleep(delay) => {},
+                        Ok(()) = state.connection_config.changed() => {
+                            tracing::info!("streaming config changed. reconnecting now...");
+                        },
+                    }
+                };
+                if stopper.stop_future(wait).await.is_none() {
+                    break;
+                }
+            }
+        }
+
+        let ConnectionConfig {
+            domain,
+            access_token,
+            software,
+        } = state.connection_config.borrow_and_update().clone();
+        bot.domain = domain;
+        bot.access_token = access_token;
+        bot.software = software;
+    }
+    flush_last_seen_id(&bot, &mut state).await;
+    flush_stats(&bot, &mut state, true).await;
+    save_status(&bot, "stopped", None).await;
+}
+
+struct BotTask {
+    replies_configmap_name: String,
+    state_configmap_name: Option<String>,
+    post_history_configmap_name: Option<String>,
+    connection_config: watch::Sender<ConnectionConfig>,
+    stopper: stopper::Stopper,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl BotTask {
+    async fn stop(self) {
+        self.stopper.stop();
+        if let Err(error) = self.handle.await {
+            tracing::error!(?error, "bot task panicked");
+        }
+    }
+}
+
+/// Serves every bot whose streaming config Secret is labeled, one task each. Bots are split
+/// across replicas by hashing the Secret name.
+async fn run_multiplexed(
+    kube_client: kube::Client,
+    multiplex_config: MultiplexConfig,
+    stopper: stopper::Stopper,
+) {
+    let shard_index = multiplex_config
+        .shard_index
+        .or_else(|| {
+            // StatefulSet pods are named `<name>-<ordinal>`.
+            let hostname = multiplex_config.hostname.as_deref()?;
+            hostname.rsplit_once('-')?.1.parse().ok()
+        })
+        .expect("failed to get shard index");
+    let ring = hash_ring::HashRing::new(multiplex_config.shard_count);
+    tracing::info!(
+        shard_index,
+        shard_count = multiplex_config.shard_count,
+        "serving multiplexed streaming"
+    );
+
+    let secret_api = Api::<Secret>::default_namespaced(kube_client.clone());
+    let stream = watcher(
+        secret_api,
+        watcher::Config::default().labels(STREAMING_CONFIG_LABEL_KEY),
+    )
+    .default_backoff();
+    let mut stream = stopper.stop_stream(stream).boxed();
+
+    let mut bots = HashMap::<String, BotTask>::new();
+    // Secrets listed so far while the watcher (re)initializes, to drop bots removed meanwhile.
+    let mut listed = None::<HashSet<String>>;
+    while let Some(event) = stream.next().await {
+        let secret = match event {
+            Ok(watcher::Event::Init) => {
+                listed = Some(HashSet::new());
+                continue;
+            }
+            Ok(watcher::Event::InitDone) => {
+                let listed = listed.take().unwrap_or_default();
+                let removed = bots
+                    .keys()
+                    .filter(|name| !listed.contains(*name))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                for name in removed {
+                    if let Some(task) = bots.remove(&name) {
+                        tracing::info!(name, "bot removed");
+                        task.stop().await;
+                    }
+                }
+                continue;
+            }
+            Ok(watcher::Event::Delete(secret)) => {
+                if let Some(task) = bots.remove(&secret.name_any()) {
+                    tracing::info!(name = secret.name_any(), "bot removed");
+                    task.stop().await;
+                }
+                continue;
+            }
+            Ok(watcher::Event::Apply(secret) | watcher::Event::InitApply(secret)) => secret,
+            Err(error) => {
+                tracing::warn!(
+                    ?error,
+                    "failed to watch streaming config Kubernetes Secrets"
+                );
+                continue;
             }
-            software => panic!("unsupported software `{software}`"),
         };
-        if let Err(error) = res {
-            tracing::error!(?error, "failed to stream. retrying...");
-        } else {
-            break;
+
+        let name = secret.name_any();
+        if let Some(listed) = &mut listed {
+            listed.insert(name.clone());
+        }
+        if ring.node(&name) != Some(shard_index) {
+            continue;
+        }
+        let (config, connection_config) = match parse_bot_secret(secret) {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                tracing::warn!(?error, name, "invalid streaming config");
+                continue;
+            }
+        };
+
+        if let Some(task) = bots.get(&name) {
+            if task.replies_configmap_name == config.replies_configmap_name
+                && task.state_configmap_name == config.streaming_state_configmap_name
+                && task.post_history_configmap_name == config.post_history_configmap_name
+            {
+                task.connection_config.send_if_modified(|current| {
+                    if *current == connection_config {
+                        return false;
+                    }
+                    *current = connection_config;
+                    true
+                });
+                continue;
+            }
         }
+        if let Some(task) = bots.remove(&name) {
+            task.stop().await;
+        }
+
+        tracing::info!(name, "bot added");
+        let bot_stopper = stopper::Stopper::new();
+        let (sender, receiver) = watch::channel(connection_config);
+        let task = BotTask {
+            replies_configmap_name: config.replies_configmap_name.clone(),
+            state_configmap_name: config.streaming_state_configmap_name.clone(),
+            post_history_configmap_name: config.post_history_configmap_name.clone(),
+            connection_config: sender,
+            stopper: bot_stopper.clone(),
+            handle: tokio::spawn(
+                run_bot(kube_client.clone(), config, receiver, bot_stopper)
+                    .instrument(tracing::info_span!("bot", name)),
+            ),
+        };
+        bots.insert(name, task);
     }
+
+    futures_util::future::join_all(bots.into_values().map(BotTask::stop)).await;
 }
 
-async fn get_reply_map_and_dice_feature(
-    kube_client: &kube::Client,
-    configmap_name: &str,
-) -> eyre::Result<(BTreeMap<String, BTreeMap<Ulid, String>>, bool)> {
-    let configmap_api = Api::<ConfigMap>::default_namespaced(kube_client.clone());
+fn parse_bot_secret(secret: Secret) -> eyre::Result<(Config, ConnectionConfig)> {
+    let data = secret_data(secret)?;
+    let config =
+        envy::from_iter(data.clone()).wrap_err("failed to parse config from Secret data")?;
+    let connection_config =
+        envy::from_iter(data).wrap_err("failed to parse config from Secret data")?;
+    Ok((config, connection_config))
+}
 
-    let replies_configmap = configmap_api
-        .get(configmap_name)
-        .await
-        .wrap_err("failed to get replies Kubernetes ConfigMap")?;
-    let dice_feature = replies_configmap
-        .metadata
-        .annotations
-        .unwrap_or_default()
-        .get("fediq.pbzweihander.dev/dice-feature")
-        .map(|v| v == "true")
-        .unwrap_or_default();
-    let reply_map = replies_configmap
+fn secret_data(secret: Secret) -> eyre::Result<Vec<(String, String)>> {
+    secret
         .data
         .unwrap_or_default()
-        .get("data")
-        .and_then(|v| serde_json::from_str::<BTreeMap<String, BTreeMap<Ulid, String>>>(v).ok())
-        .unwrap_or_default();
-    Ok((reply_map, dice_feature))
+        .into_iter()
+        .map(|(key, value)| {
+            String::from_utf8(value.0)
+                .map(|value| (key, value))
+                .wrap_err("failed to decode Secret data as UTF-8 string")
+        })
+        .collect()
 }
 
-async fn stream_mastodon(
-    domain: &str,
-    access_token: &str,
-    configmap_name: &str,
+/// Waits for the first valid config in the Secret, then keeps the returned receiver up to date
+/// with later edits.
+async fn watch_connection_config(
     kube_client: &kube::Client,
-    rng: &mut impl rand::Rng,
-    stopper: &stopper::Stopper,
-) -> eyre::Result<()> {
-    #[derive(Debug, Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    struct Event {
-        event: String,
-        payload: String,
-    }
-
-    #[derive(Debug, Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    struct Account {
-        acct: String,
+    secret_name: &str,
+) -> eyre::Result<watch::Receiver<ConnectionConfig>> {
+    fn parse(secret: Secret) -> eyre::Result<ConnectionConfig> {
+        envy::from_iter(secret_data(secret)?).wrap_err("failed to parse config from Secret data")
     }
 
-    #[derive(Debug, Deserialize)]
-    #[serde(rename_all = "camelCase")]
-    struct Status {
-        id: String,
-        content: String,
-    }
+    let secret_api = Api::<Secret>::default_namespaced(kube_client.clone());
+    let mut stream = watcher(
+        secret_api,
+        watcher::Config::default().fields(&format!("metadata.name={secret_name}")),
+    )
+    .default_backoff()
+    .applied_objects()
+    .boxed();
 
-    #[derive(Debug, Deserialize)]
-    #[serde(tag = "type", rename_all = "camelCase")]
-    enum EventInner {
-        Mention {
-            account: Account,
-            status: Status,
-        },
-        #[serde(other)]
-        Unknown,
-    }
+    let config = loop {
+        match stream.next().await {
+            Some(Ok(secret)) => match parse(secret) {
+                Ok(config) => break config,
+                Err(error) => tracing::warn!(?error, "invalid streaming config"),
+            },
+            Some(Err(error)) => {
+                tracing::warn!(?error, "failed to watch streaming config Kubernetes Secret")
+            }
+            None => return Err(eyre::eyre!("streaming config watcher ended")),
+        }
+    };
 
-    let resp = post::HTTP_CLIENT
-        .get(format!(
-            "wss://{domain}/api/v1/streaming?stream=user:notification"
-        ))
-        .bearer_auth(access_token)
-        .upgrade()
-        .send()
-        .await
-        .wrap_err("failed to request websocket")?;
-    let stream = resp
-        .into_websocket()
-        .await
-        .wrap_err("failed to connect websocket")?;
-    let mut stream = stopper.stop_stream(stream);
-    while let Some(message) = stream
-        .next()
-        .await
-        .transpose()
-        .wrap_err("failed to get message")?
-    {
-        if let reqwest_websocket::Message::Text(payload) = message {
-            if let Ok(Event { event, payload }) = serde_json::from_str::<Event>(&payload) {
-                if event == "notification" {
-                    if let Ok(EventInner::Mention { account, status }) =
-                        serde_json::from_str::<EventInner>(&payload)
-                    {
-                        tracing::info!(?account, ?status, "got mention");
-                        let (reply_map, dice_feature) =
-                            get_reply_map_and_dice_feature(kube_client, configmap_name)
-                                .await
-                                .wrap_err("failed to get reply map and dice feature")?;
-                        if let Some(reply) = get_reply(&status.content, &reply_map, rng) {
-                            tracing::info!(reply, "replying");
-                            if let Err(error) = post::post_mastodon(
-                                domain,
-                                access_token,
-                                &format!("@{} {}", account.acct, reply),
-                                Some(status.id),
-                            )
-                            .await
-                            {
-                                tracing::error!(?error, "failed to post reply to Mastodon");
-                            }
-                            continue;
-                        }
-                        if dice_feature {
-                            if let Some(dice_result) = get_dice(&status.content, rng) {
-                                tracing::info!(dice_result, "replying dice result");
-                                if let Err(error) = post::post_mastodon(
-                                    domain,
-                                    access_token,
-                                    &format!("@{} {}", account.acct, dice_result),
-                                    Some(status.id),
-                                )
-                                .await
-                                {
-                                    tracing::error!(
-                                        ?error,
-                                        "failed to post dice result to Mastodon"
-                                    );
-                                }
-                                continue;
-                            }
+    let (sender, receiver) = watch::channel(config);
+    tokio::spawn(async move {
+        while let Some(res) = stream.next().await {
+            match res.map_err(eyre::Report::new).and_then(parse) {
+                Ok(config) => {
+                    sender.send_if_modified(|current| {
+                        if *current == config {
+                            return false;
                         }
-                        tracing::info!("no match");
-                    }
+                        *current = config;
+                        true
+                    });
                 }
+                Err(error) => tracing::warn!(?error, "failed to reload streaming config"),
             }
         }
-    }
-    Ok(())
+    });
+    Ok(receiver)
 }
 
-async fn stream_misskey(
-    domain: &str,
-    access_token: &str,
-    configmap_name: &str,
-    kube_client: &kube::Client,
-    rng: &mut impl rand::Rng,
-    stopper: &stopper::Stopper,
-) -> eyre::Result<()> {
-    #[derive(Debug, Deserialize, Serialize)]
-    #[serde(rename_all = "camelCase")]
-    struct User {
-        username: String,
-        host: String,
-    }
-
-    #[derive(Debug, Deserialize, Serialize)]
-    #[serde(tag = "type", content = "body", rename_all = "camelCase")]
-    enum ChannelMessage {
-        Mention {
-            id: String,
-            user: User,
-            text: String,
-        },
-        #[serde(other)]
-        Unknown,
-    }
+fn reconnect_backoff(attempt: u32) -> Duration {
+    let backoff = INITIAL_RECONNECT_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RECONNECT_BACKOFF);
+    let jitter = rand::random_range(0..=backoff.as_millis() as u64 / 2);
+    backoff + Duration::from_millis(jitter)
+}
 
-    #[derive(Debug, Deserialize, Serialize)]
-    #[serde(tag = "type", content = "body", rename_all = "camelCase")]
-    enum Message {
-        Connect {
-            channel: String,
-            id: String,
-        },
-        Channel {
-            id: String,
-            #[serde(flatten)]
-            body: ChannelMessage,
-        },
-        #[serde(other)]
-        Unknown,
-    }
+/// Orders ids of the same kind. Mastodon ids are numeric strings of varying length, and Misskey
+/// ids are fixed-length strings sorted by creation time, so comparing length first works for
+/// both.
+fn compare_ids(a: &str, b: &str) -> std::cmp::Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.cmp(b))
+}
 
-    let resp = post::HTTP_CLIENT
-        .get(format!("wss://{domain}/streaming?i={access_token}"))
-        .upgrade()
-        .send()
+async fn load_last_seen_id(bot: &Bot) -> eyre::Result<Option<String>> {
+    let Some(state_configmap_name) = &bot.state_configmap_name else {
+        return Ok(None);
+    };
+    let configmap_api = Api::<ConfigMap>::default_namespaced(bot.kube_client.clone());
+    let state_configmap = configmap_api
+        .get_opt(state_configmap_name)
         .await
-        .wrap_err("failed to request websocket")?;
-    let mut stream = resp
-        .into_websocket()
+        .wrap_err("failed to get streaming state Kubernetes ConfigMap")?;
+    Ok(state_configmap
+        .and_then(|cm| cm.data)
+        .and_then(|mut data| data.remove(LAST_SEEN_ID_KEY)))
+}
+
+async fn save_state(bot: &Bot, key: &str, value: String) -> eyre::Result<()> {
+    let Some(state_configmap_name) = &bot.state_configmap_name else {
+        return Ok(());
+    };
+    let configmap_api = Api::<ConfigMap>::default_namespaced(bot.kube_client.clone());
+    let mut data = configmap_api
+        .get_opt(state_configmap_name)
         .await
-        .wrap_err("failed to connect websocket")?;
-    let channel_id = Ulid::new();
-    stream
-        .send(reqwest_websocket::Message::Text(
-            serde_json::to_string(&Message::Connect {
-                channel: "main".to_string(),
-                id: channel_id.to_string(),
-            })
-            .unwrap(),
-        ))
+        .wrap_err("failed to get streaming state Kubernetes ConfigMap")?
+        .and_then(|cm| cm.data)
+        .unwrap_or_default();
+    data.insert(key.to_string(), value);
+
+    configmap_api
+        .patch(
+            state_configmap_name,
+            &PatchParams::apply("fediq.pbzweihander.dev").force(),
+            &Patch::Apply(ConfigMap {
+                metadata: ObjectMeta {
+                    name: Some(state_configmap_name.clone()),
+                    ..Default::default()
+                },
+                data: Some(data),
+                ..Default::default()
+            }),
+        )
         .await
-        .wrap_err("failed to connect channel")?;
-    stream.flush().await.wrap_err("failed to flush stream")?;
-    let mut stream = stopper.stop_stream(stream);
-    while let Some(message) = stream
-        .next()
+        .wrap_err("failed to patch streaming state Kubernetes ConfigMap")?;
+
+    Ok(())
+}
+
+async fn load_stats(bot: &Bot) -> eyre::Result<StreamingStats> {
+    let Some(state_configmap_name) = &bot.state_configmap_name else {
+        return Ok(StreamingStats::default());
+    };
+    let configmap_api = Api::<ConfigMap>::default_namespaced(bot.kube_client.clone());
+    let state_configmap = configmap_api
+        .get_opt(state_configmap_name)
         .await
-        .transpose()
-        .wrap_err("failed to get message")?
-    {
-        if let reqwest_websocket::Message::Text(payload) = message {
-            if let Ok(Message::Channel {
-                id: rx_channel_id,
-                body:
-                    ChannelMessage::Mention {
-                        id: note_id,
-                        user,
-                        text,
-                    },
-            }) = serde_json::from_str::<Message>(&payload)
-            {
-                if rx_channel_id == channel_id.to_string() {
-                    tracing::info!(?user, text, "got mention");
-                    let (reply_map, dice_feature) =
-                        get_reply_map_and_dice_feature(kube_client, configmap_name)
-                            .await
-                            .wrap_err("failed to get reply map and dice feature")?;
-                    if let Some(reply) = get_reply(&text, &reply_map, rng) {
-                        tracing::info!(reply, "replying");
-                        if let Err(error) = post::post_misskey(
-                            domain,
-                            access_token,
-                            &format!("@{}@{} {}", user.username, user.host, reply),
-                            Some(note_id.clone()),
-                        )
-                        .await
-                        {
-                            tracing::error!(?error, "failed to post to Misskey");
-                            continue;
-                        }
-                    }
-                    if dice_feature {
-                        if let Some(dice_result) = get_dice(&text, rng) {
-                            tracing::info!(dice_result, "replying dice result");
-                            if let Err(error) = post::post_misskey(
-                                domain,
-                                access_token,
-                                &format!("@{}@{} {}", user.username, user.host, dice_result),
-                                Some(note_id),
-                            )
-                            .await
-                            {
-                                tracing::error!(?error, "failed to post dice result to Misskey");
-                            }
-                            continue;
-                        }
-                    }
-                    tracing::info!("no match");
-                }
-            }
-        }
+        .wrap_err("failed to get streaming state Kubernetes ConfigMap")?;
+    Ok(state_configmap
+        .and_then(|cm| cm.data)
+        .and_then(|data| {
+            data.get(STATS_KEY)
+                .and_then(|v| serde_json::from_str::<StreamingStats>(v).ok())
+        })
+        .unwrap_or_default())
+}
+
+/// Saves the stats if they changed, at most every [`STATS_FLUSH_INTERVAL`] unless `force` is set.
+async fn flush_stats(bot: &Bot, state: &mut StreamState, force: bool) {
+    if !state.stats_dirty || (!force && state.stats_saved_at.elapsed() < STATS_FLUSH_INTERVAL) {
+        return;
+    }
+    state.stats_saved_at = Instant::now();
+    let stats = serde_json::to_string(&state.stats).expect("failed to serialize stats");
+    match save_state(bot, STATS_KEY, stats).await {
+        Ok(()) => state.stats_dirty = false,
+        Err(error) => tracing::error!(?error, "failed to save stats"),
     }
-    Ok(())
 }
 
-fn get_reply(
-    text: &str,
-    reply_map: &BTreeMap<String, BTreeMap<Ulid, String>>,
-    rng: &mut impl rand::Rng,
-) -> Option<String> {
-    for (keyword, replies) in reply_map.iter().rev() {
-        if text.contains(keyword) {
-            return replies.values().choose(rng).cloned();
+/// Finds the quote the poster posted as `status_id`, if it was one of its recent posts. The post
+/// history is only parsed again when its ConfigMap has changed.
+fn quote_of_status(bot: &Bot, status_id: &str) -> Option<Ulid> {
+    #[derive(Deserialize)]
+    struct PostStats {
+        #[serde(default)]
+        statuses: Vec<(String, Ulid)>,
+    }
+
+    let post_history_configmap = bot
+        .post_history
+        .as_ref()?
+        .find(|cm| cm.metadata.name.as_deref() == bot.post_history_configmap_name.as_deref())?;
+    let resource_version = post_history_configmap
+        .metadata
+        .resource_version
+        .clone()
+        .unwrap_or_default();
+    let mut cache = bot
+        .quote_statuses
+        .lock()
+        .expect("quote statuses cache poisoned");
+    if let Some((version, quote_statuses)) = &*cache {
+        if *version == resource_version {
+            return quote_statuses.get(status_id).copied();
         }
     }
-    None
+    let quote_statuses: QuoteStatuses = post_history_configmap
+        .data
+        .as_ref()
+        .and_then(|data| data.get(STATS_KEY))
+        .and_then(|v| serde_json::from_str::<PostStats>(v).ok())
+        .map(|post_stats| post_stats.statuses.into_iter().collect())
+        .unwrap_or_default();
+    let quote_statuses = Arc::new(quote_statuses);
+    *cache = Some((resource_version, quote_statuses.clone()));
+    quote_statuses.get(status_id).copied()
 }
 
-fn get_dice(text: &str, rng: &mut impl rand::Rng) -> Option<String> {
-    static DICE_REGEX: LazyLock<regex::Regex> =
-        LazyLock::new(|| regex::Regex::new(r"(\d+)d(\d+)").expect("failed to build regex"));
-    if let Some(captures) = DICE_REGEX.captures(text) {
-        let n = captures.get(1)?.as_str().parse::<usize>().ok()?;
-        let m = captures.get(2)?.as_str().parse::<usize>().ok()?;
-        if n == 0 || m == 0 {
-            return None;
-        }
+/// Counts a reply, or the interaction, with the bot's post `status_id` towards its quote.
+fn record_engagement(
+    bot: &Bot,
+    state: &mut StreamState,
+    status_id: &str,
+    interaction: Option<Interaction>,
+) {
+    if let Some(quote_id) = quote_of_status(bot, status_id) {
+        state.stats.record_engagement(quote_id, interaction);
+        state.stats_dirty = true;
+    }
+}
 
-        if n == 1 {
-            return Some(rng.random_range(1..=m).to_string());
-        }
+/// Returns whether `id` has not been handled yet.
+fn is_unseen(state: &StreamState, id: &str) -> bool {
+    state
+        .last_seen_id
+        .as_deref()
+        .is_none_or(|last_seen_id| compare_ids(id, last_seen_id).is_gt())
+}
 
-        let mut list = Vec::new();
-        for _i in 0..n {
-            let result = rng.random_range(1..=m);
-            list.push(result);
-        }
-        let sum = list.iter().sum::<usize>();
+fn mark_seen(state: &mut StreamState, id: String) {
+    if !is_unseen(state, &id) {
+        return;
+    }
+    state.last_seen_id = Some(id);
+    state.last_seen_id_dirty = true;
+}
 
-        if n <= 10 && m <= 100 {
-            Some(format!(
-                "{} = {}",
-                list.iter().map(usize::to_string).join(" + "),
-                sum
-            ))
-        } else {
-            Some(format!("{sum}"))
+/// Saves the last seen id if it changed since it was last saved. Called on every ping and on
+/// shutdown, rather than for every notification.
+async fn flush_last_seen_id(bot: &Bot, state: &mut StreamState) {
+    let Some(id) = state
+        .last_seen_id
+        .clone()
+        .filter(|_| state.last_seen_id_dirty)
+    else {
+        return;
+    };
+    match save_state(bot, LAST_SEEN_ID_KEY, id).await {
+        Ok(()) => state.last_seen_id_dirty = false,
+        Err(error) => tracing::error!(?error, "failed to save last seen id"),
+    }
+}
+
+/// Reports the connection state so that the web UI can show it.
+async fn save_status(bot: &Bot, state: &str, error: Option<String>) {
+    let status = StreamingStatus {
+        state,
+        updated_at: OffsetDateTime::now_utc(),
+        error,
+    };
+    let status = serde_json::to_string(&status).expect("failed to serialize streaming status");
+    if let Err(error) = save_state(bot, STATUS_KEY, status).await {
+        tracing::error!(?error, "failed to save streaming status");
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum MatchType {
+    #[default]
+    Substring,
+    WholeWord,
+    CaseInsensitive,
+    Regex,
+}
+
+/// A keyword compiled for its match type once, when the reply config is loaded.
+enum KeywordMatcher {
+    Substring(String),
+    CaseInsensitive(String),
+    Regex(regex::Regex),
+}
+
+impl KeywordMatcher {
+    fn new(keyword: &str, match_type: MatchType) -> Result<Self, regex::Error> {
+        Ok(match match_type {
+            MatchType::Substring => Self::Substring(keyword.to_string()),
+            MatchType::WholeWord => Self::Regex(regex::Regex::new(&format!(
+                r"(?:^|\W){}(?:\W|$)",
+                regex::escape(keyword)
+            ))?),
+            MatchType::CaseInsensitive => Self::CaseInsensitive(keyword.to_lowercase()),
+            MatchType::Regex => Self::Regex(regex::Regex::new(keyword)?),
+        })
+    }
+
+    /// Compiles the keyword, skipping the rule if it is an invalid regex.
+    fn compile(keyword: &str, match_type: MatchType) -> Option<Self> {
+        Self::new(keyword, match_type)
+            .inspect_err(|error| {
+                tracing::warn!(?error, keyword, "invalid keyword regex, skipping the rule");
+            })
+            .ok()
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::Substring(keyword) => text.contains(keyword.as_str()),
+            Self::CaseInsensitive(keyword) => text.to_lowercase().contains(keyword.as_str()),
+            Self::Regex(regex) => regex.is_match(text),
         }
-    } else {
+    }
+}
+
+#[derive(Deserialize)]
+struct KeywordRule {
+    keyword: String,
+    #[serde(default)]
+    match_type: MatchType,
+    #[serde(default)]
+    reaction: Option<String>,
+    #[serde(default)]
+    react_only: bool,
+}
+
+struct ReplyRule {
+    keyword: String,
+    matcher: KeywordMatcher,
+    reaction: Option<String>,
+    react_only: bool,
+    replies: BTreeMap<Ulid, String>,
+}
+
+/// What to do with a mention. There is at most one reply, so that a mention never gets two.
+#[derive(Debug, PartialEq)]
+struct MentionResponse {
+    /// The keyword, script or built-in responder that answered, for the stats.
+    trigger: String,
+    reaction: Option<String>,
+    reply: Option<String>,
+    /// Set when the reply asks a question of a script, whose answer is awaited.
+    script: Option<ScriptProgress>,
+}
+
+#[derive(Debug, PartialEq)]
+struct ScriptProgress {
+    keyword: String,
+    step: usize,
+    expiry: Duration,
+}
+
+fn default_script_expiry_minutes() -> u64 {
+    10
+}
+
+#[derive(Deserialize)]
+struct ScriptDefinition {
+    keyword: String,
+    #[serde(default)]
+    match_type: MatchType,
+    #[serde(default = "default_script_expiry_minutes")]
+    expiry_minutes: u64,
+    steps: Vec<ScriptStep>,
+}
+
+/// A short dialogue started by a keyword. Each step's prompt is sent, and the next reply in the
+/// same thread is checked against its answers.
+struct Script {
+    keyword: String,
+    matcher: KeywordMatcher,
+    expiry_minutes: u64,
+    steps: Vec<ScriptStep>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct ScriptStep {
+    prompt: String,
+    /// Any reply is accepted if empty.
+    answers: Vec<String>,
+    correct: String,
+    wrong: String,
+}
+
+impl Script {
+    fn progress(&self, step: usize) -> Option<ScriptProgress> {
+        (step < self.steps.len()).then(|| ScriptProgress {
+            keyword: self.keyword.clone(),
+            step,
+            expiry: Duration::from_secs(self.expiry_minutes * 60),
+        })
+    }
+
+    fn start(&self) -> Option<MentionResponse> {
+        let step = self.steps.first()?;
+        Some(MentionResponse {
+            trigger: self.keyword.clone(),
+            reaction: None,
+            reply: Some(step.prompt.clone()),
+            script: self.progress(0),
+        })
+    }
+
+    fn answer(&self, step: usize, text: &str) -> Option<MentionResponse> {
+        let current = self.steps.get(step)?;
+        let words = answer_words(text);
+        // An answer has to be whole words of the reply, so that "12" is not taken for "2".
+        let correct = current.answers.is_empty()
+            || current.answers.iter().any(|answer| {
+                let answer = answer_words(answer);
+                !answer.is_empty() && words.windows(answer.len()).any(|window| window == answer)
+            });
+        let feedback = if correct {
+            &current.correct
+        } else {
+            &current.wrong
+        };
+        let next = self.steps.get(step + 1).map(|next| &next.prompt);
+        let reply = [Some(feedback), next]
+            .into_iter()
+            .flatten()
+            .filter(|s| !s.is_empty())
+            .map(String::as_str)
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        Some(MentionResponse {
+            trigger: self.keyword.clone(),
+            reaction: None,
+            reply: Some(reply).filter(|s| !s.is_empty()),
+            script: self.progress(step + 1),
+        })
+    }
+}
+
+/// Lowercased words, without the punctuation around them.
+fn answer_words(text: &str) -> Vec<String> {
+    text.split_whitespace()
+        .map(|word| word.trim_matches(|c: char| !c.is_alphanumeric()))
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// A script waiting for an answer from `acct` under the bot's post `last_post_id`.
+struct Conversation {
+    keyword: String,
+    step: usize,
+    acct: String,
+    last_post_id: String,
+    expires_at: Instant,
+}
+
+/// Scripted dialogues in progress, keyed by the id of the mention that started them. They only
+/// live in memory, since they expire in minutes anyway.
+#[derive(Default)]
+struct Conversations {
+    conversations: HashMap<String, Conversation>,
+}
+
+impl Conversations {
+    /// Removes and returns the conversation the mention answers, if any.
+    fn take(&mut self, mention: &Mention) -> Option<(String, Conversation)> {
+        let now = Instant::now();
+        self.conversations
+            .retain(|_, conversation| conversation.expires_at > now);
+        let in_reply_to_id = mention.in_reply_to_id.as_ref()?;
+        let root_id = self
+            .conversations
+            .iter()
+            .find(|(_, conversation)| {
+                &conversation.last_post_id == in_reply_to_id && conversation.acct == mention.acct
+            })?
+            .0
+            .clone();
+        self.conversations.remove_entry(&root_id)
+    }
+
+    fn restore(&mut self, root_id: String, conversation: Conversation) {
+        self.conversations.insert(root_id, conversation);
+    }
+
+    fn insert(
+        &mut self,
+        root_id: String,
+        acct: String,
+        last_post_id: String,
+        progress: ScriptProgress,
+    ) {
+        self.conversations.insert(
+            root_id,
+            Conversation {
+                keyword: progress.keyword,
+                step: progress.step,
+                acct,
+                last_post_id,
+                expires_at: Instant::now() + progress.expiry,
+            },
+        );
+    }
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum ResponseMode {
+    #[default]
+    Off,
+    Reply,
+    React,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct FollowReaction {
+    welcome: bool,
+    follow_back: bool,
+    messages: Vec<String>,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct NotificationReaction {
+    mode: ResponseMode,
+    messages: Vec<String>,
+}
+
+/// How to respond to notifications other than mentions.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+struct Reactions {
+    follow: FollowReaction,
+    reblog: NotificationReaction,
+    favourite: NotificationReaction,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Interaction {
+    Reblog,
+    Favourite,
+}
+
+impl Interaction {
+    fn reaction(self, reactions: &Reactions) -> &NotificationReaction {
+        match self {
+            Self::Reblog => &reactions.reblog,
+            Self::Favourite => &reactions.favourite,
+        }
+    }
+}
+
+/// Post visibility, ordered from the most open to the most restricted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Visibility {
+    Public,
+    Unlisted,
+    Private,
+    Direct,
+}
+
+impl Visibility {
+    fn parse(s: &str) -> Option<Self> {
+        match s {
+            "public" => Some(Self::Public),
+            "unlisted" => Some(Self::Unlisted),
+            "private" => Some(Self::Private),
+            "direct" => Some(Self::Direct),
+            _ => None,
+        }
+    }
+
+    /// Unknown visibilities are treated as direct, so that nothing leaks by mistake.
+    fn from_mastodon(s: &str) -> Self {
+        match s {
+            "local" => Self::Unlisted,
+            s => Self::parse(s).unwrap_or(Self::Direct),
+        }
+    }
+
+    fn from_misskey(s: &str) -> Self {
+        match s {
+            "public" => Self::Public,
+            "home" => Self::Unlisted,
+            "followers" => Self::Private,
+            _ => Self::Direct,
+        }
+    }
+
+    fn mastodon(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Unlisted => "unlisted",
+            Self::Private => "private",
+            Self::Direct => "direct",
+        }
+    }
+
+    fn misskey(self) -> &'static str {
+        match self {
+            Self::Public => "public",
+            Self::Unlisted => "home",
+            Self::Private => "followers",
+            Self::Direct => "specified",
+        }
+    }
+}
+
+#[derive(Default)]
+struct ReplyConfig {
+    reply_map: Vec<ReplyRule>,
+    scripts: Vec<Script>,
+    responders: Vec<&'static dyn responder::Responder>,
+    cooldown: Duration,
+    replies_per_minute: usize,
+    ignore_bots: bool,
+    reactions: Reactions,
+    visibility_cap: Option<Visibility>,
+}
+
+impl ReplyConfig {
+    /// Replies are never more open than what they reply to, nor than the bot's cap.
+    fn reply_visibility(&self, visibility: Visibility) -> Visibility {
+        self.visibility_cap
+            .map_or(visibility, |cap| visibility.max(cap))
+    }
+}
+
+/// Keeps one chatty account, or two bots talking to each other, from making the bot spam.
+#[derive(Default)]
+struct ReplyLimiter {
+    last_replied_at: HashMap<String, Instant>,
+    recent_replies: VecDeque<Instant>,
+}
+
+impl ReplyLimiter {
+    /// Returns why a reply to `account` must be skipped, if it must.
+    fn check(&mut self, account: &str, is_bot: bool, config: &ReplyConfig) -> Option<&'static str> {
+        let now = Instant::now();
+        if is_bot && config.ignore_bots {
+            return Some("ignoring bot account");
+        }
+        if let Some(last_replied_at) = self.last_replied_at.get(account) {
+            if now.duration_since(*last_replied_at) < config.cooldown {
+                return Some("account is in cooldown");
+            }
+        }
+        while self
+            .recent_replies
+            .front()
+            .is_some_and(|at| now.duration_since(*at) >= Duration::from_secs(60))
+        {
+            self.recent_replies.pop_front();
+        }
+        if config.replies_per_minute > 0 && self.recent_replies.len() >= config.replies_per_minute {
+            return Some("replies per minute limit reached");
+        }
+        None
+    }
+
+    fn record(&mut self, account: &str, config: &ReplyConfig) {
+        let now = Instant::now();
+        self.last_replied_at
+            .retain(|_, at| now.duration_since(*at) < config.cooldown);
+        if !config.cooldown.is_zero() {
+            self.last_replied_at.insert(account.to_string(), now);
+        }
+        self.recent_replies.push_back(now);
+    }
+}
+
+/// Keeps an in-memory copy of a ConfigMap, such as the replies one, so that edits apply right
+/// away and mentions are answered without a round-trip to the API server. The watcher retries
+/// with backoff on its own, so API errors never reach the websocket.
+fn watch_configmap(
+    kube_client: &kube::Client,
+    configmap_name: &str,
+    stopper: &stopper::Stopper,
+) -> reflector::Store<ConfigMap> {
+    let configmap_api = Api::<ConfigMap>::default_namespaced(kube_client.clone());
+    let (reader, writer) = reflector::store();
+    let stream = reflector(
+        writer,
+        watcher(
+            configmap_api,
+            watcher::Config::default().fields(&format!("metadata.name={configmap_name}")),
+        ),
+    )
+    .default_backoff()
+    .touched_objects();
+    let task = stopper.stop_stream(stream).for_each(|res| async move {
+        match res {
+            Ok(configmap) => tracing::info!(
+                name = configmap.metadata.name,
+                resource_version = configmap.metadata.resource_version,
+                "Kubernetes ConfigMap updated"
+            ),
+            Err(error) => tracing::warn!(?error, "failed to watch Kubernetes ConfigMap"),
+        }
+    });
+    tokio::spawn(task.in_current_span());
+    reader
+}
+
+/// Returns the reply config, rebuilt only when the replies ConfigMap has changed.
+fn get_reply_config(bot: &Bot) -> Arc<ReplyConfig> {
+    let replies_configmap = bot
+        .replies
+        .find(|cm| cm.metadata.name.as_deref() == Some(bot.replies_configmap_name.as_str()));
+    let Some(replies_configmap) = replies_configmap else {
+        tracing::warn!("replies Kubernetes ConfigMap not found");
+        return Arc::default();
+    };
+    let resource_version = replies_configmap
+        .metadata
+        .resource_version
+        .clone()
+        .unwrap_or_default();
+    let mut cache = bot
+        .reply_config
+        .lock()
+        .expect("reply config cache poisoned");
+    if let Some((version, reply_config)) = &*cache {
+        if *version == resource_version {
+            return reply_config.clone();
+        }
+    }
+    let reply_config = Arc::new(load_reply_config(&replies_configmap));
+    *cache = Some((resource_version, reply_config.clone()));
+    reply_config
+}
+
+fn load_reply_config(replies_configmap: &ConfigMap) -> ReplyConfig {
+    let annotations = replies_configmap
+        .metadata
+        .annotations
+        .clone()
+        .unwrap_or_default();
+    let responders = responder::RESPONDERS
+        .into_iter()
+        .filter(|responder| {
+            annotations
+                .get(&responder::annotation_key(*responder))
+                .is_some_and(|v| v == "true")
+        })
+        .collect();
+    let cooldown_seconds = annotations
+        .get("fediq.pbzweihander.dev/reply-cooldown-seconds")
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(0);
+    let replies_per_minute = annotations
+        .get("fediq.pbzweihander.dev/replies-per-minute")
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(0);
+    let ignore_bots = annotations
+        .get("fediq.pbzweihander.dev/ignore-bots")
+        .map(|v| v == "true")
+        .unwrap_or_default();
+    let visibility_cap = annotations
+        .get("fediq.pbzweihander.dev/reply-visibility-cap")
+        .and_then(|v| Visibility::parse(v));
+    let data = replies_configmap.data.clone().unwrap_or_default();
+    let mut reply_map = data
+        .get("data")
+        .and_then(|v| serde_json::from_str::<BTreeMap<String, BTreeMap<Ulid, String>>>(v).ok())
+        .unwrap_or_default();
+    let keyword_rules = data
+        .get("rules")
+        .and_then(|v| serde_json::from_str::<Vec<KeywordRule>>(v).ok())
+        .unwrap_or_default();
+    let reactions = data
+        .get("reactions")
+        .and_then(|v| serde_json::from_str::<Reactions>(v).ok())
+        .unwrap_or_default();
+    let scripts = data
+        .get("scripts")
+        .and_then(|v| serde_json::from_str::<Vec<ScriptDefinition>>(v).ok())
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|script| {
+            Some(Script {
+                matcher: KeywordMatcher::compile(&script.keyword, script.match_type)?,
+                keyword: script.keyword,
+                expiry_minutes: script.expiry_minutes,
+                steps: script.steps,
+            })
+        })
+        .collect();
+
+    let mut rules = keyword_rules
+        .into_iter()
+        .filter_map(|rule| {
+            let replies = reply_map.remove(&rule.keyword)?;
+            Some(ReplyRule {
+                matcher: KeywordMatcher::compile(&rule.keyword, rule.match_type)?,
+                keyword: rule.keyword,
+                reaction: rule.reaction,
+                react_only: rule.react_only,
+                replies,
+            })
+        })
+        .collect::<Vec<_>>();
+    rules.extend(
+        reply_map
+            .into_iter()
+            .rev()
+            .map(|(keyword, replies)| ReplyRule {
+                matcher: KeywordMatcher::Substring(keyword.clone()),
+                keyword,
+                reaction: None,
+                react_only: false,
+                replies,
+            }),
+    );
+    ReplyConfig {
+        reply_map: rules,
+        scripts,
+        responders,
+        cooldown: Duration::from_secs(cooldown_seconds),
+        replies_per_minute,
+        ignore_bots,
+        reactions,
+        visibility_cap,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MastodonAccount {
+    id: String,
+    acct: String,
+    #[serde(default)]
+    bot: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonStatus {
+    id: String,
+    #[serde(default)]
+    in_reply_to_id: Option<String>,
+    content: String,
+    visibility: String,
+    #[serde(default)]
+    spoiler_text: String,
+}
+
+impl MastodonStatus {
+    fn cw(&self) -> Option<&str> {
+        Some(self.spoiler_text.as_str()).filter(|s| !s.is_empty())
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum MastodonNotificationKind {
+    Mention {
+        account: MastodonAccount,
+        status: MastodonStatus,
+    },
+    Follow {
+        account: MastodonAccount,
+    },
+    Reblog {
+        account: MastodonAccount,
+        status: MastodonStatus,
+    },
+    Favourite {
+        account: MastodonAccount,
+        status: MastodonStatus,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+struct MastodonNotification {
+    id: String,
+    #[serde(flatten)]
+    kind: MastodonNotificationKind,
+}
+
+async fn stream_mastodon(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    stopper: &stopper::Stopper,
+) -> eyre::Result<StreamEnd> {
+    #[derive(Debug, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Event {
+        event: String,
+        payload: String,
+    }
+
+    let resp = post::HTTP_CLIENT
+        .get(format!(
+            "wss://{}/api/v1/streaming?stream=user:notification",
+            bot.domain
+        ))
+        .bearer_auth(&bot.access_token)
+        .upgrade()
+        .send()
+        .await
+        .wrap_err("failed to request websocket")?;
+    let websocket = resp
+        .into_websocket()
+        .await
+        .wrap_err("failed to connect websocket")?;
+    let (mut sink, stream) = websocket.split();
+    save_status(bot, "connected", None).await;
+
+    // Connect first, so that nothing slips between catching up and streaming.
+    if let Err(error) = catch_up_mastodon(bot, state, rng).await {
+        tracing::error!(?error, "failed to catch up missed mentions");
+    }
+
+    let mut stream = stopper.stop_stream(stream);
+    let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+    let mut last_received_at = Instant::now();
+    loop {
+        let message = tokio::select! {
+            message = stream.next() => message,
+            Ok(()) = state.connection_config.changed() => return Ok(StreamEnd::ConfigChanged),
+            _ = ping_interval.tick() => {
+                if last_received_at.elapsed() > PONG_TIMEOUT {
+                    return Err(eyre::eyre!("nothing received for {PONG_TIMEOUT:?}"));
+                }
+                sink.send(reqwest_websocket::Message::Ping(Default::default()))
+                    .await
+                    .wrap_err("failed to send ping")?;
+                flush_last_seen_id(bot, state).await;
+                flush_stats(bot, state, false).await;
+                continue;
+            }
+        };
+        let Some(message) = message.transpose().wrap_err("failed to get message")? else {
+            break;
+        };
+        last_received_at = Instant::now();
+
+        if let reqwest_websocket::Message::Text(payload) = message {
+            if let Ok(Event { event, payload }) = serde_json::from_str::<Event>(&payload) {
+                if event == "notification" {
+                    if let Ok(notification) = serde_json::from_str::<MastodonNotification>(&payload)
+                    {
+                        handle_mastodon_notification(bot, state, rng, notification).await?;
+                    }
+                }
+            }
+        }
+    }
+    Ok(StreamEnd::Closed)
+}
+
+async fn catch_up_mastodon(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+) -> eyre::Result<()> {
+    for _ in 0..CATCH_UP_MAX_PAGES {
+        let Some(last_seen_id) = state.last_seen_id.clone() else {
+            return Ok(());
+        };
+        let url = format!("https://{}/api/v1/notifications", bot.domain);
+        let resp = post::HTTP_CLIENT
+            .get(&url)
+            .query(&[
+                ("types[]", "mention"),
+                ("types[]", "follow"),
+                ("types[]", "reblog"),
+                ("types[]", "favourite"),
+                ("min_id", &last_seen_id),
+                ("limit", "40"),
+            ])
+            .bearer_auth(&bot.access_token)
+            .send()
+            .await
+            .wrap_err_with(|| format!("failed to request to `{url}`"))?;
+        let resp_status = resp.status();
+        let resp_text = resp
+            .text()
+            .await
+            .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+        if !resp_status.is_success() {
+            return Err(eyre::eyre!("error response received: `{resp_text}`"));
+        }
+        let mut notifications = serde_json::from_str::<Vec<MastodonNotification>>(&resp_text)
+            .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+        if notifications.is_empty() {
+            break;
+        }
+
+        tracing::info!(
+            count = notifications.len(),
+            "catching up missed notifications"
+        );
+        notifications.sort_by(|a, b| compare_ids(&a.id, &b.id));
+        for notification in notifications {
+            handle_mastodon_notification(bot, state, rng, notification).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn handle_mastodon_notification(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    notification: MastodonNotification,
+) -> eyre::Result<()> {
+    if !is_unseen(state, &notification.id) {
+        return Ok(());
+    }
+    match notification.kind {
+        MastodonNotificationKind::Mention { account, status } => {
+            tracing::info!(?account, ?status, "got mention");
+            handle_mention(bot, state, rng, Mention::from_mastodon(account, status)).await;
+        }
+        MastodonNotificationKind::Follow { account } => {
+            tracing::info!(?account, "got follow");
+            handle_mastodon_follow(bot, state, rng, account).await;
+        }
+        MastodonNotificationKind::Reblog { account, status } => {
+            tracing::info!(?account, status_id = status.id, "got reblog");
+            record_engagement(bot, state, &status.id, Some(Interaction::Reblog));
+            handle_mastodon_interaction(bot, state, rng, Interaction::Reblog, account, status)
+                .await;
+        }
+        MastodonNotificationKind::Favourite { account, status } => {
+            tracing::info!(?account, status_id = status.id, "got favourite");
+            record_engagement(bot, state, &status.id, Some(Interaction::Favourite));
+            handle_mastodon_interaction(bot, state, rng, Interaction::Favourite, account, status)
+                .await;
+        }
+        MastodonNotificationKind::Unknown => {}
+    }
+    mark_seen(state, notification.id);
+    Ok(())
+}
+
+/// Reacts with `emoji` where the server supports emoji reactions, and favourites otherwise.
+async fn react_mastodon(bot: &Bot, status_id: &str, emoji: &str) -> eyre::Result<()> {
+    if !interact::react_mastodon(&bot.domain, &bot.access_token, status_id, emoji).await? {
+        tracing::info!("emoji reactions not supported. favouriting instead");
+        interact::favourite_mastodon(&bot.domain, &bot.access_token, status_id).await?;
+    }
+    Ok(())
+}
+
+async fn handle_mastodon_follow(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    account: MastodonAccount,
+) {
+    let reply_config = get_reply_config(bot);
+    let reaction = &reply_config.reactions.follow;
+    if !reaction.welcome && !reaction.follow_back {
+        return;
+    }
+    if let Some(reason) = state
+        .limiter
+        .check(&account.acct, account.bot, &reply_config)
+    {
+        tracing::info!(reason, "skipping follow");
+        return;
+    }
+    state.limiter.record(&account.acct, &reply_config);
+
+    if reaction.follow_back {
+        match interact::follow_mastodon(&bot.domain, &bot.access_token, &account.id).await {
+            Ok(()) => tracing::info!("followed back"),
+            Err(error) => tracing::error!(?error, "failed to follow back on Mastodon"),
+        }
+    }
+    if reaction.welcome {
+        if let Some(message) = reaction.messages.iter().choose(rng) {
+            match post::post_mastodon(
+                &bot.domain,
+                &bot.access_token,
+                &format!("@{} {}", account.acct, message),
+                None,
+                "direct",
+                None,
+            )
+            .await
+            {
+                Ok(posted) => tracing::info!(id = posted.id, url = posted.url, "welcomed"),
+                Err(error) => tracing::error!(?error, "failed to post welcome to Mastodon"),
+            }
+        }
+    }
+}
+
+async fn handle_mastodon_interaction(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    interaction: Interaction,
+    account: MastodonAccount,
+    status: MastodonStatus,
+) {
+    let reply_config = get_reply_config(bot);
+    let reaction = interaction.reaction(&reply_config.reactions);
+    if matches!(reaction.mode, ResponseMode::Off) {
+        return;
+    }
+    if let Some(reason) = state
+        .limiter
+        .check(&account.acct, account.bot, &reply_config)
+    {
+        tracing::info!(reason, ?interaction, "skipping");
+        return;
+    }
+
+    match reaction.mode {
+        ResponseMode::Off => {}
+        ResponseMode::Reply => {
+            let Some(message) = reaction.messages.iter().choose(rng) else {
+                return;
+            };
+            state.limiter.record(&account.acct, &reply_config);
+            match post::post_mastodon(
+                &bot.domain,
+                &bot.access_token,
+                &format!("@{} {}", account.acct, message),
+                Some(status.id),
+                reply_config
+                    .reply_visibility(Visibility::Unlisted)
+                    .mastodon(),
+                None,
+            )
+            .await
+            {
+                Ok(posted) => tracing::info!(id = posted.id, url = posted.url, "thanked"),
+                Err(error) => tracing::error!(?error, "failed to post thanks to Mastodon"),
+            }
+        }
+        // Mastodon has no emoji reactions, so favourite their latest status instead.
+        ResponseMode::React => {
+            state.limiter.record(&account.acct, &reply_config);
+            let res = async {
+                let Some(status_id) =
+                    interact::latest_status_mastodon(&bot.domain, &bot.access_token, &account.id)
+                        .await?
+                else {
+                    return Ok(());
+                };
+                interact::favourite_mastodon(&bot.domain, &bot.access_token, &status_id).await
+            };
+            match res.await {
+                Ok(()) => tracing::info!("favourited back"),
+                Err(error) => tracing::error!(?error, "failed to favourite on Mastodon"),
+            }
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MisskeyUser {
+    id: String,
+    username: String,
+    host: Option<String>,
+    #[serde(default)]
+    is_bot: bool,
+}
+
+impl MisskeyUser {
+    fn acct(&self) -> String {
+        match &self.host {
+            Some(host) => format!("{}@{}", self.username, host),
+            None => self.username.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MisskeyNote {
+    id: String,
+    user: MisskeyUser,
+    text: Option<String>,
+    #[serde(default)]
+    reply_id: Option<String>,
+    #[serde(default)]
+    renote_id: Option<String>,
+    #[serde(default)]
+    visibility: String,
+    #[serde(default)]
+    cw: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+enum MisskeyNotificationKind {
+    Mention {
+        note: MisskeyNote,
+    },
+    Reply {
+        note: MisskeyNote,
+    },
+    Follow {
+        user: MisskeyUser,
+    },
+    Renote {
+        user: MisskeyUser,
+        note: MisskeyNote,
+    },
+    Reaction {
+        user: MisskeyUser,
+        note: MisskeyNote,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Deserialize)]
+struct MisskeyNotification {
+    id: String,
+    #[serde(flatten)]
+    kind: MisskeyNotificationKind,
+}
+
+async fn stream_misskey(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    stopper: &stopper::Stopper,
+) -> eyre::Result<StreamEnd> {
+    #[derive(Debug, Deserialize, Serialize)]
+    #[serde(tag = "type", content = "body", rename_all = "camelCase")]
+    enum ChannelMessage {
+        Notification(serde_json::Value),
+        #[serde(other)]
+        Unknown,
+    }
+
+    #[derive(Debug, Deserialize, Serialize)]
+    #[serde(tag = "type", content = "body", rename_all = "camelCase")]
+    enum Message {
+        Connect {
+            channel: String,
+            id: String,
+        },
+        Channel {
+            id: String,
+            #[serde(flatten)]
+            body: ChannelMessage,
+        },
+        #[serde(other)]
+        Unknown,
+    }
+
+    let resp = post::HTTP_CLIENT
+        .get(format!(
+            "wss://{}/streaming?i={}",
+            bot.domain, bot.access_token
+        ))
+        .upgrade()
+        .send()
+        .await
+        .wrap_err("failed to request websocket")?;
+    let websocket = resp
+        .into_websocket()
+        .await
+        .wrap_err("failed to connect websocket")?;
+    let (mut sink, stream) = websocket.split();
+    save_status(bot, "connected", None).await;
+    let channel_id = Ulid::new();
+    sink.send(reqwest_websocket::Message::Text(
+        serde_json::to_string(&Message::Connect {
+            channel: "main".to_string(),
+            id: channel_id.to_string(),
+        })
+        .unwrap(),
+    ))
+    .await
+    .wrap_err("failed to connect channel")?;
+    sink.flush().await.wrap_err("failed to flush stream")?;
+
+    // Connect first, so that nothing slips between catching up and streaming.
+    if let Err(error) = catch_up_misskey(bot, state, rng).await {
+        tracing::error!(?error, "failed to catch up missed mentions");
+    }
+
+    let mut stream = stopper.stop_stream(stream);
+    let mut ping_interval = tokio::time::interval(PING_INTERVAL);
+    let mut last_received_at = Instant::now();
+    loop {
+        let message = tokio::select! {
+            message = stream.next() => message,
+            Ok(()) = state.connection_config.changed() => return Ok(StreamEnd::ConfigChanged),
+            _ = ping_interval.tick() => {
+                if last_received_at.elapsed() > PONG_TIMEOUT {
+                    return Err(eyre::eyre!("nothing received for {PONG_TIMEOUT:?}"));
+                }
+                sink.send(reqwest_websocket::Message::Ping(Default::default()))
+                    .await
+                    .wrap_err("failed to send ping")?;
+                flush_last_seen_id(bot, state).await;
+                flush_stats(bot, state, false).await;
+                continue;
+            }
+        };
+        let Some(message) = message.transpose().wrap_err("failed to get message")? else {
+            break;
+        };
+        last_received_at = Instant::now();
+
+        if let reqwest_websocket::Message::Text(payload) = message {
+            if let Ok(Message::Channel {
+                id: rx_channel_id,
+                body: ChannelMessage::Notification(notification),
+            }) = serde_json::from_str::<Message>(&payload)
+            {
+                if rx_channel_id == channel_id.to_string() {
+                    match serde_json::from_value::<MisskeyNotification>(notification) {
+                        Ok(notification) => {
+                            handle_misskey_notification(bot, state, rng, notification).await?
+                        }
+                        Err(error) => tracing::warn!(?error, "failed to parse notification"),
+                    }
+                }
+            }
+        }
+    }
+    Ok(StreamEnd::Closed)
+}
+
+async fn catch_up_misskey(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+) -> eyre::Result<()> {
+    #[derive(Serialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Req<'a> {
+        i: &'a str,
+        since_id: &'a str,
+        limit: u32,
+        include_types: [&'a str; 5],
+    }
+
+    for _ in 0..CATCH_UP_MAX_PAGES {
+        let Some(last_seen_id) = state.last_seen_id.clone() else {
+            return Ok(());
+        };
+        // The last seen id may be a note id from older versions, but Misskey ids sort by creation
+        // time across kinds, and a notification is always created after its note.
+        let url = format!("https://{}/api/i/notifications", bot.domain);
+        let resp = post::HTTP_CLIENT
+            .post(&url)
+            .json(&Req {
+                i: &bot.access_token,
+                since_id: &last_seen_id,
+                limit: 50,
+                include_types: ["mention", "reply", "follow", "renote", "reaction"],
+            })
+            .send()
+            .await
+            .wrap_err_with(|| format!("failed to request to `{url}`"))?;
+        let resp_status = resp.status();
+        let resp_text = resp
+            .text()
+            .await
+            .wrap_err_with(|| format!("failed to read response from `{url}`"))?;
+        if !resp_status.is_success() {
+            return Err(eyre::eyre!("error response received: `{resp_text}`"));
+        }
+        let notifications = serde_json::from_str::<Vec<serde_json::Value>>(&resp_text)
+            .wrap_err_with(|| format!("failed to parse response `{resp_text}`"))?;
+        // A notification whose note was deleted fails to parse, and is skipped on its own.
+        let mut notifications = notifications
+            .into_iter()
+            .filter_map(|notification| {
+                serde_json::from_value::<MisskeyNotification>(notification)
+                    .inspect_err(|error| tracing::warn!(?error, "failed to parse notification"))
+                    .ok()
+            })
+            .filter(|notification| is_unseen(state, &notification.id))
+            .collect::<Vec<_>>();
+        if notifications.is_empty() {
+            break;
+        }
+
+        tracing::info!(
+            count = notifications.len(),
+            "catching up missed notifications"
+        );
+        notifications.sort_by(|a, b| compare_ids(&a.id, &b.id));
+        for notification in notifications {
+            handle_misskey_notification(bot, state, rng, notification).await?;
+        }
+    }
+    Ok(())
+}
+
+async fn handle_misskey_notification(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    notification: MisskeyNotification,
+) -> eyre::Result<()> {
+    if !is_unseen(state, &notification.id) {
+        return Ok(());
+    }
+    match notification.kind {
+        MisskeyNotificationKind::Mention { note } | MisskeyNotificationKind::Reply { note } => {
+            tracing::info!(user = ?note.user, text = note.text, "got mention");
+            handle_mention(bot, state, rng, Mention::from_misskey(note)).await;
+        }
+        MisskeyNotificationKind::Follow { user } => {
+            tracing::info!(?user, "got follow");
+            handle_misskey_follow(bot, state, rng, user).await;
+        }
+        MisskeyNotificationKind::Renote { user, note } => {
+            tracing::info!(?user, note_id = note.id, "got renote");
+            // The notification carries the renote itself, but thanks belong under the original.
+            let note_id = note.renote_id.unwrap_or(note.id);
+            record_engagement(bot, state, &note_id, Some(Interaction::Reblog));
+            handle_misskey_interaction(bot, state, rng, Interaction::Reblog, user, note_id).await;
+        }
+        MisskeyNotificationKind::Reaction { user, note } => {
+            tracing::info!(?user, note_id = note.id, "got reaction");
+            record_engagement(bot, state, &note.id, Some(Interaction::Favourite));
+            handle_misskey_interaction(bot, state, rng, Interaction::Favourite, user, note.id)
+                .await;
+        }
+        MisskeyNotificationKind::Unknown => {}
+    }
+    mark_seen(state, notification.id);
+    Ok(())
+}
+
+async fn handle_misskey_follow(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    user: MisskeyUser,
+) {
+    let reply_config = get_reply_config(bot);
+    let reaction = &reply_config.reactions.follow;
+    if !reaction.welcome && !reaction.follow_back {
+        return;
+    }
+    let account = user.acct();
+    if let Some(reason) = state.limiter.check(&account, user.is_bot, &reply_config) {
+        tracing::info!(reason, "skipping follow");
+        return;
+    }
+    state.limiter.record(&account, &reply_config);
+
+    if reaction.follow_back {
+        match interact::follow_misskey(&bot.domain, &bot.access_token, &user.id).await {
+            Ok(()) => tracing::info!("followed back"),
+            Err(error) => tracing::error!(?error, "failed to follow back on Misskey"),
+        }
+    }
+    if reaction.welcome {
+        if let Some(message) = reaction.messages.iter().choose(rng) {
+            // Misskey adds mentioned users to the recipients of a specified note.
+            match post::post_misskey(
+                &bot.domain,
+                &bot.access_token,
+                &format!("@{account} {message}"),
+                None,
+                "specified",
+                None,
+            )
+            .await
+            {
+                Ok(posted) => tracing::info!(id = posted.id, url = posted.url, "welcomed"),
+                Err(error) => tracing::error!(?error, "failed to post welcome to Misskey"),
+            }
+        }
+    }
+}
+
+async fn handle_misskey_interaction(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    interaction: Interaction,
+    user: MisskeyUser,
+    note_id: String,
+) {
+    let reply_config = get_reply_config(bot);
+    let reaction = interaction.reaction(&reply_config.reactions);
+    if matches!(reaction.mode, ResponseMode::Off) {
+        return;
+    }
+    let account = user.acct();
+    if let Some(reason) = state.limiter.check(&account, user.is_bot, &reply_config) {
+        tracing::info!(reason, ?interaction, "skipping");
+        return;
+    }
+
+    match reaction.mode {
+        ResponseMode::Off => {}
+        ResponseMode::Reply => {
+            let Some(message) = reaction.messages.iter().choose(rng) else {
+                return;
+            };
+            state.limiter.record(&account, &reply_config);
+            match post::post_misskey(
+                &bot.domain,
+                &bot.access_token,
+                &format!("@{account} {message}"),
+                Some(note_id),
+                reply_config
+                    .reply_visibility(Visibility::Unlisted)
+                    .misskey(),
+                None,
+            )
+            .await
+            {
+                Ok(posted) => tracing::info!(id = posted.id, url = posted.url, "thanked"),
+                Err(error) => tracing::error!(?error, "failed to post thanks to Misskey"),
+            }
+        }
+        ResponseMode::React => {
+            let reaction = reaction
+                .messages
+                .iter()
+                .choose(rng)
+                .map(String::as_str)
+                .unwrap_or(DEFAULT_REACTION);
+            state.limiter.record(&account, &reply_config);
+            let res = async {
+                let Some(note_id) =
+                    interact::latest_note_misskey(&bot.domain, &bot.access_token, &user.id).await?
+                else {
+                    return Ok(());
+                };
+                interact::react_misskey(&bot.domain, &bot.access_token, &note_id, reaction).await
+            };
+            match res.await {
+                Ok(()) => tracing::info!(reaction, "reacted back"),
+                Err(error) => tracing::error!(?error, "failed to react on Misskey"),
+            }
+        }
+    }
+}
+
+/// A mention from either backend, reduced to what responding to it needs.
+#[derive(Debug, PartialEq)]
+struct Mention {
+    id: String,
+    in_reply_to_id: Option<String>,
+    acct: String,
+    is_bot: bool,
+    text: String,
+    visibility: Visibility,
+    cw: Option<String>,
+}
+
+impl Mention {
+    fn from_mastodon(account: MastodonAccount, status: MastodonStatus) -> Self {
+        Self {
+            text: normalize::html_to_text(&status.content),
+            visibility: Visibility::from_mastodon(&status.visibility),
+            cw: status.cw().map(str::to_string),
+            id: status.id,
+            in_reply_to_id: status.in_reply_to_id,
+            acct: account.acct,
+            is_bot: account.bot,
+        }
+    }
+
+    fn from_misskey(note: MisskeyNote) -> Self {
+        Self {
+            text: normalize::strip_mentions(note.text.as_deref().unwrap_or_default()),
+            visibility: Visibility::from_misskey(&note.visibility),
+            cw: note.cw,
+            acct: note.user.acct(),
+            is_bot: note.user.is_bot,
+            id: note.id,
+            in_reply_to_id: note.reply_id,
+        }
+    }
+}
+
+/// An answer to a script comes first, then scripts, keyword rules and the built-in responders in
+/// order.
+fn respond_to_mention(
+    text: &str,
+    conversation: Option<&Conversation>,
+    reply_config: &ReplyConfig,
+    rng: &mut impl rand::Rng,
+) -> Option<MentionResponse> {
+    if let Some(conversation) = conversation {
+        let response = reply_config
+            .scripts
+            .iter()
+            .find(|script| script.keyword == conversation.keyword)
+            .and_then(|script| script.answer(conversation.step, text));
+        if response.is_some() {
+            return response;
+        }
+    }
+    if let Some(script) = reply_config
+        .scripts
+        .iter()
+        .find(|script| script.matcher.matches(text))
+    {
+        tracing::info!(keyword = script.keyword, "starting script");
+        return script.start();
+    }
+    if let Some(response) = get_reply(text, &reply_config.reply_map, rng) {
+        return Some(response);
+    }
+    let (responder, reply) = responder::respond(text, &reply_config.responders, rng)?;
+    tracing::info!(responder, "built-in responder matched");
+    Some(MentionResponse {
+        trigger: responder.to_string(),
+        reaction: None,
+        reply: Some(reply),
+        script: None,
+    })
+}
+
+async fn handle_mention(
+    bot: &Bot,
+    state: &mut StreamState,
+    rng: &mut impl rand::Rng,
+    mention: Mention,
+) {
+    if let Some(in_reply_to_id) = &mention.in_reply_to_id {
+        record_engagement(bot, state, in_reply_to_id, None);
+    }
+    let reply_config = get_reply_config(bot);
+    let (root_id, conversation) = match state.conversations.take(&mention) {
+        Some((root_id, conversation)) => (root_id, Some(conversation)),
+        None => (mention.id.clone(), None),
+    };
+    // The bot asked for the answer, so the cooldown does not apply to it.
+    if conversation.is_none() {
+        if let Some(reason) = state
+            .limiter
+            .check(&mention.acct, mention.is_bot, &reply_config)
+        {
+            tracing::info!(reason, "skipping mention");
+            return;
+        }
+    }
+    let Some(response) =
+        respond_to_mention(&mention.text, conversation.as_ref(), &reply_config, rng)
+    else {
+        tracing::info!("no match");
+        return;
+    };
+    state.limiter.record(&mention.acct, &reply_config);
+    state.stats.record_response(&response.trigger);
+    state.stats_dirty = true;
+
+    if let Some(reaction) = response.reaction {
+        tracing::info!(reaction, "reacting");
+        let res = match bot.software.as_str() {
+            "misskey" => {
+                interact::react_misskey(&bot.domain, &bot.access_token, &mention.id, &reaction)
+                    .await
+            }
+            _ => react_mastodon(bot, &mention.id, &reaction).await,
+        };
+        match res {
+            Ok(()) => tracing::info!("reacted"),
+            Err(error) => tracing::error!(?error, "failed to react"),
+        }
+    }
+
+    if let Some(reply) = response.reply {
+        tracing::info!(reply, "replying");
+        let text = format!("@{} {}", mention.acct, reply);
+        let visibility = reply_config.reply_visibility(mention.visibility);
+        let res = match bot.software.as_str() {
+            "misskey" => {
+                post::post_misskey(
+                    &bot.domain,
+                    &bot.access_token,
+                    &text,
+                    Some(mention.id.clone()),
+                    visibility.misskey(),
+                    mention.cw.as_deref(),
+                )
+                .await
+            }
+            _ => {
+                post::post_mastodon(
+                    &bot.domain,
+                    &bot.access_token,
+                    &text,
+                    Some(mention.id.clone()),
+                    visibility.mastodon(),
+                    mention.cw.as_deref(),
+                )
+                .await
+            }
+        };
+        match res {
+            Ok(posted) => {
+                tracing::info!(id = posted.id, url = posted.url, "replied");
+                state.stats.record_reply(OffsetDateTime::now_utc());
+                if let Some(progress) = response.script {
+                    state
+                        .conversations
+                        .insert(root_id, mention.acct, posted.id, progress);
+                }
+            }
+            Err(error) => {
+                tracing::error!(?error, "failed to post reply");
+                // Let them answer again, since they never saw whether they were right.
+                if let Some(conversation) = conversation {
+                    state.conversations.restore(root_id, conversation);
+                }
+            }
+        }
+    }
+}
+
+fn get_reply(
+    text: &str,
+    reply_map: &[ReplyRule],
+    rng: &mut impl rand::Rng,
+) -> Option<MentionResponse> {
+    let rule = reply_map.iter().find(|rule| rule.matcher.matches(text))?;
+    let reply = if rule.react_only {
         None
+    } else {
+        rule.replies.values().choose(rng).cloned()
+    };
+    if rule.reaction.is_none() && reply.is_none() {
+        return None;
+    }
+    Some(MentionResponse {
+        trigger: rule.keyword.clone(),
+        reaction: rule.reaction.clone(),
+        reply,
+        script: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mastodon_mention() -> Mention {
+        let notification = serde_json::from_str::<MastodonNotification>(include_str!(
+            "fixtures/mastodon-mention.json"
+        ))
+        .unwrap();
+        let MastodonNotificationKind::Mention { account, status } = notification.kind else {
+            panic!("not a mention");
+        };
+        Mention::from_mastodon(account, status)
+    }
+
+    fn misskey_mention() -> Mention {
+        let notification = serde_json::from_str::<MisskeyNotification>(include_str!(
+            "fixtures/misskey-mention.json"
+        ))
+        .unwrap();
+        let MisskeyNotificationKind::Mention { note } = notification.kind else {
+            panic!("not a mention");
+        };
+        Mention::from_misskey(note)
+    }
+
+    fn reply_config(reaction: Option<&str>, react_only: bool) -> ReplyConfig {
+        ReplyConfig {
+            reply_map: vec![ReplyRule {
+                keyword: "고양이".to_string(),
+                matcher: KeywordMatcher::Substring("고양이".to_string()),
+                reaction: reaction.map(str::to_string),
+                react_only,
+                replies: BTreeMap::from([(Ulid::nil(), "야옹".to_string())]),
+            }],
+            responders: responder::RESPONDERS
+                .into_iter()
+                .filter(|responder| responder.id() == "dice")
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn normalizes_mastodon_mention() {
+        assert_eq!(
+            mastodon_mention(),
+            Mention {
+                id: "114448213380539216".to_string(),
+                in_reply_to_id: None,
+                acct: "alice@mastodon.example".to_string(),
+                is_bot: false,
+                text: "고양이 1d6 굴려줘".to_string(),
+                visibility: Visibility::Private,
+                cw: Some("주사위 굴림".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn normalizes_misskey_mention() {
+        assert_eq!(
+            misskey_mention(),
+            Mention {
+                id: "a6h2kq1z9b".to_string(),
+                in_reply_to_id: None,
+                acct: "bob@misskey.example".to_string(),
+                is_bot: true,
+                text: "고양이 1d6 굴려줘".to_string(),
+                visibility: Visibility::Direct,
+                cw: None,
+            }
+        );
+    }
+
+    #[test]
+    fn keyword_reply_takes_priority_over_responders() {
+        let mut rng = StdRng::seed_from_u64(0);
+        for mention in [mastodon_mention(), misskey_mention()] {
+            assert_eq!(
+                respond_to_mention(&mention.text, None, &reply_config(None, false), &mut rng),
+                Some(MentionResponse {
+                    trigger: "고양이".to_string(),
+                    reaction: None,
+                    reply: Some("야옹".to_string()),
+                    script: None,
+                })
+            );
+            assert_eq!(
+                respond_to_mention(
+                    &mention.text,
+                    None,
+                    &reply_config(Some("🐱"), true),
+                    &mut rng
+                ),
+                Some(MentionResponse {
+                    trigger: "고양이".to_string(),
+                    reaction: Some("🐱".to_string()),
+                    reply: None,
+                    script: None,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn falls_back_to_responders() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let reply_config = ReplyConfig {
+            reply_map: Vec::new(),
+            ..reply_config(None, false)
+        };
+        for mention in [mastodon_mention(), misskey_mention()] {
+            let response =
+                respond_to_mention(&mention.text, None, &reply_config, &mut rng).unwrap();
+            assert_eq!(response.trigger, "dice");
+            assert_eq!(response.reaction, None);
+            let roll = response.reply.unwrap().parse::<u32>().unwrap();
+            assert!((1..=6).contains(&roll));
+        }
+
+        let reply_config = ReplyConfig::default();
+        assert_eq!(
+            respond_to_mention(&mastodon_mention().text, None, &reply_config, &mut rng),
+            None
+        );
+    }
+
+    #[test]
+    fn matches_keywords_by_type() {
+        let matcher = |keyword, match_type| KeywordMatcher::new(keyword, match_type).unwrap();
+        assert!(matcher("cat", MatchType::Substring).matches("concatenate"));
+        assert!(!matcher("cat", MatchType::WholeWord).matches("concatenate"));
+        assert!(matcher("cat", MatchType::WholeWord).matches("a cat!"));
+        assert!(matcher("Cat", MatchType::CaseInsensitive).matches("CAT"));
+        assert!(matcher(r"^\d+$", MatchType::Regex).matches("42"));
+        assert!(KeywordMatcher::compile("(", MatchType::Regex).is_none());
+        assert!(KeywordMatcher::compile("(", MatchType::WholeWord).is_some());
+    }
+
+    fn quiz() -> Script {
+        Script {
+            keyword: "퀴즈".to_string(),
+            matcher: KeywordMatcher::Substring("퀴즈".to_string()),
+            expiry_minutes: 10,
+            steps: vec![
+                ScriptStep {
+                    prompt: "고양이는 영어로?".to_string(),
+                    answers: vec!["cat".to_string(), "kitty".to_string()],
+                    correct: "정답!".to_string(),
+                    wrong: "땡!".to_string(),
+                },
+                ScriptStep {
+                    prompt: "1 + 1은?".to_string(),
+                    answers: vec!["2".to_string()],
+                    correct: "끝까지 정답!".to_string(),
+                    wrong: "아쉽네요.".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn runs_script_through_conversation() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let reply_config = ReplyConfig {
+            scripts: vec![quiz()],
+            ..reply_config(None, false)
+        };
+        let mut conversations = Conversations::default();
+        let mut mention = mastodon_mention();
+
+        mention.text = "고양이 퀴즈 내줘".to_string();
+        let response = respond_to_mention(&mention.text, None, &reply_config, &mut rng).unwrap();
+        assert_eq!(response.reply.as_deref(), Some("고양이는 영어로?"));
+        let progress = response.script.unwrap();
+        assert_eq!(progress.step, 0);
+        assert_eq!(progress.expiry, Duration::from_secs(600));
+        conversations.insert(
+            mention.id.clone(),
+            mention.acct.clone(),
+            "1".to_string(),
+            progress,
+        );
+
+        mention.id = "2".to_string();
+        mention.in_reply_to_id = Some("1".to_string());
+        mention.text = "Cat!".to_string();
+        let (root_id, conversation) = conversations.take(&mention).unwrap();
+        assert_eq!(root_id, "114448213380539216");
+        let response =
+            respond_to_mention(&mention.text, Some(&conversation), &reply_config, &mut rng)
+                .unwrap();
+        assert_eq!(response.reply.as_deref(), Some("정답!\n\n1 + 1은?"));
+        conversations.insert(
+            root_id,
+            mention.acct.clone(),
+            "3".to_string(),
+            response.script.unwrap(),
+        );
+
+        mention.in_reply_to_id = Some("3".to_string());
+        mention.text = "고양이".to_string();
+        let (_, conversation) = conversations.take(&mention).unwrap();
+        let response =
+            respond_to_mention(&mention.text, Some(&conversation), &reply_config, &mut rng)
+                .unwrap();
+        assert_eq!(response.reply.as_deref(), Some("아쉽네요."));
+        assert_eq!(response.script, None);
+    }
+
+    #[test]
+    fn takes_only_matching_live_conversation() {
+        let mut conversations = Conversations::default();
+        let progress = || quiz().progress(0).unwrap();
+        conversations.insert(
+            "root".to_string(),
+            "alice".to_string(),
+            "1".to_string(),
+            progress(),
+        );
+        conversations.insert(
+            "expired".to_string(),
+            "alice".to_string(),
+            "2".to_string(),
+            ScriptProgress {
+                expiry: Duration::ZERO,
+                ..progress()
+            },
+        );
+
+        let mut mention = mastodon_mention();
+        mention.acct = "alice".to_string();
+        mention.in_reply_to_id = Some("2".to_string());
+        assert!(conversations.take(&mention).is_none());
+        assert!(!conversations.conversations.contains_key("expired"));
+
+        mention.in_reply_to_id = Some("1".to_string());
+        mention.acct = "mallory".to_string();
+        assert!(conversations.take(&mention).is_none());
+        mention.acct = "alice".to_string();
+        let (root_id, conversation) = conversations.take(&mention).unwrap();
+        assert!(conversations.take(&mention).is_none());
+        conversations.restore(root_id, conversation);
+        assert!(conversations.take(&mention).is_some());
+    }
+
+    #[test]
+    fn matches_script_answers_by_whole_words() {
+        let quiz = quiz();
+        let correct = |step, text| {
+            let reply = quiz.answer(step, text).unwrap().reply.unwrap();
+            reply.starts_with("정답") || reply.starts_with("끝까지 정답")
+        };
+        assert!(correct(1, "2"));
+        assert!(correct(1, "@bot 답은 2!"));
+        assert!(!correct(1, "12"));
+        assert!(!correct(1, "2.5"));
+        assert!(correct(0, "CAT?"));
+        assert!(!correct(0, "category"));
+    }
+
+    #[test]
+    fn caps_reply_visibility() {
+        let reply_config = ReplyConfig {
+            visibility_cap: Some(Visibility::Unlisted),
+            ..Default::default()
+        };
+        assert_eq!(
+            reply_config.reply_visibility(Visibility::Public),
+            Visibility::Unlisted
+        );
+        assert_eq!(
+            reply_config.reply_visibility(Visibility::Direct),
+            Visibility::Direct
+        );
+        assert_eq!(
+            ReplyConfig::default().reply_visibility(Visibility::Public),
+            Visibility::Public
+        );
     }
 }
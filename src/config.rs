@@ -37,6 +37,9 @@ pub struct Config {
 
     pub streaming_container_image: String,
     pub streaming_serviceaccount_name: String,
+    /// Leave streaming to a shared multiplexed process instead of a Deployment per bot.
+    #[serde(default)]
+    pub streaming_multiplexed: bool,
 }
 
 impl Config {
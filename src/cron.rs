@@ -0,0 +1,234 @@
+//! A minimal parser for the 5-field cron expressions accepted by Kubernetes CronJobs, just enough
+//! to tell how often a schedule fires.
+
+use time::{Date, Duration, OffsetDateTime, PrimitiveDateTime, Time};
+
+const MONTH_NAMES: [&str; 12] = [
+    "jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+/// The Gregorian calendar, weekdays included, repeats every 400 years.
+const CALENDAR_PERIOD_DAYS: i64 = 146_097;
+
+pub struct Schedule {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    days_restricted: bool,
+    weekdays_restricted: bool,
+}
+
+fn parse_value(value: &str, names: &[&str], offset: u32) -> Option<u32> {
+    if let Ok(value) = value.parse::<u32>() {
+        return Some(value);
+    }
+    let value = value.to_ascii_lowercase();
+    names
+        .iter()
+        .position(|name| *name == value)
+        .map(|index| index as u32 + offset)
+}
+
+/// Parses a single field into a bitset, with bit `n` set when the field matches value `n`.
+fn parse_field(field: &str, min: u32, max: u32, names: &[&str]) -> Option<u64> {
+    let mut bits = 0u64;
+    for item in field.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((range, step)) => (range, step.parse::<u32>().ok().filter(|step| *step > 0)?),
+            None => (item, 1),
+        };
+        let (start, end) = if range == "*" || range == "?" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (
+                parse_value(start, names, min)?,
+                parse_value(end, names, min)?,
+            )
+        } else {
+            let start = parse_value(range, names, min)?;
+            // `5/15` means every 15 starting from 5.
+            (start, if item.contains('/') { max } else { start })
+        };
+        if start < min || end > max || start > end {
+            return None;
+        }
+        for value in (start..=end).step_by(step as usize) {
+            bits |= 1 << value;
+        }
+    }
+    Some(bits)
+}
+
+impl Schedule {
+    pub fn parse(expr: &str) -> Option<Self> {
+        let expr = match expr.trim() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            expr => expr,
+        };
+        let [minutes, hours, days, months, weekdays] =
+            <[&str; 5]>::try_from(expr.split_whitespace().collect::<Vec<_>>()).ok()?;
+
+        let mut weekday_bits = parse_field(weekdays, 0, 7, &WEEKDAY_NAMES)?;
+        // Both 0 and 7 are Sunday.
+        if weekday_bits & (1 << 7) != 0 {
+            weekday_bits |= 1;
+        }
+
+        Some(Self {
+            minutes: parse_field(minutes, 0, 59, &[])?,
+            hours: parse_field(hours, 0, 23, &[])?,
+            days: parse_field(days, 1, 31, &[])?,
+            months: parse_field(months, 1, 12, &MONTH_NAMES)?,
+            weekdays: weekday_bits,
+            days_restricted: !days.starts_with(['*', '?']),
+            weekdays_restricted: !weekdays.starts_with(['*', '?']),
+        })
+    }
+
+    fn matches_date(&self, date: Date) -> bool {
+        if self.months & (1 << u8::from(date.month())) == 0 {
+            return false;
+        }
+        let day = self.days & (1 << date.day()) != 0;
+        let weekday = self.weekdays & (1 << date.weekday().number_days_from_sunday()) != 0;
+        // Like cron, a restricted day of month and day of week match when either one does.
+        match (self.days_restricted, self.weekdays_restricted) {
+            (true, true) => day || weekday,
+            _ => day && weekday,
+        }
+    }
+
+    /// Counts how many times the schedule fires in `[from, to)`, in UTC.
+    pub fn count_between(&self, from: OffsetDateTime, to: OffsetDateTime) -> u64 {
+        let from = from.to_offset(time::UtcOffset::UTC);
+        let to = to.to_offset(time::UtcOffset::UTC);
+        let fires_per_day = (self.hours.count_ones() * self.minutes.count_ones()) as u64;
+        let mut count = 0;
+        let mut date = from.date();
+        while date <= to.date() {
+            if self.matches_date(date) {
+                let start = date.midnight().assume_utc();
+                if from <= start && date < to.date() {
+                    count += fires_per_day;
+                } else {
+                    count += self.count_in_day(date, from, to);
+                }
+            }
+            let Some(next) = date.next_day() else {
+                break;
+            };
+            date = next;
+        }
+        count
+    }
+
+    fn count_in_day(&self, date: Date, from: OffsetDateTime, to: OffsetDateTime) -> u64 {
+        let mut count = 0;
+        for hour in (0..24).filter(|hour| self.hours & (1 << hour) != 0) {
+            for minute in (0..60).filter(|minute| self.minutes & (1 << minute) != 0) {
+                let Ok(time) = Time::from_hms(hour, minute, 0) else {
+                    continue;
+                };
+                let at = date.with_time(time).assume_utc();
+                if from <= at && at < to {
+                    count += 1;
+                }
+            }
+        }
+        count
+    }
+
+    /// Counts how many times the schedule fires within `minutes` from `from`. Whole 400-year
+    /// calendar periods are counted once and multiplied, so any window is cheap.
+    pub fn count_within(&self, from: OffsetDateTime, minutes: u32) -> u64 {
+        let add = |duration: Duration| {
+            from.checked_add(duration)
+                .unwrap_or(PrimitiveDateTime::MAX.assume_utc())
+        };
+        let window = Duration::minutes(minutes as i64);
+        let periods = window.whole_days() / CALENDAR_PERIOD_DAYS;
+        let period = Duration::days(CALENDAR_PERIOD_DAYS);
+        let mut count = self.count_between(from, add(window - period * periods as i32));
+        if periods > 0 {
+            count += periods as u64 * self.count_between(from, add(period));
+        }
+        count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use super::*;
+
+    #[test]
+    fn parses_cron_expressions() {
+        let schedule = Schedule::parse("5,10-12 */6 1 jan-Mar mon").unwrap();
+        assert_eq!(schedule.minutes, 1 << 5 | 1 << 10 | 1 << 11 | 1 << 12);
+        assert_eq!(schedule.hours, 1 | 1 << 6 | 1 << 12 | 1 << 18);
+        assert_eq!(schedule.days, 1 << 1);
+        assert_eq!(schedule.months, 1 << 1 | 1 << 2 | 1 << 3);
+        assert_eq!(schedule.weekdays, 1 << 1);
+        assert!(schedule.days_restricted && schedule.weekdays_restricted);
+
+        assert_eq!(Schedule::parse("0 0 * * 7").unwrap().weekdays & 1, 1);
+        assert_eq!(
+            Schedule::parse("5/20 * * * *").unwrap().minutes,
+            1 << 5 | 1 << 25 | 1 << 45
+        );
+        assert!(Schedule::parse("@hourly").is_some());
+        for invalid in [
+            "",
+            "* * * *",
+            "60 * * * *",
+            "* 24 * * *",
+            "* * 0 * *",
+            "*/0 * * * *",
+            "5-1 * * * *",
+            "* * * foo *",
+        ] {
+            assert!(Schedule::parse(invalid).is_none(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn counts_fires() {
+        let from = datetime!(2025-01-01 00:30 UTC);
+        let hourly = Schedule::parse("@hourly").unwrap();
+        assert_eq!(hourly.count_within(from, 60), 1);
+        assert_eq!(hourly.count_within(from, 24 * 60), 24);
+        assert_eq!(
+            Schedule::parse("*/15 * * * *")
+                .unwrap()
+                .count_within(from, 3 * 24 * 60),
+            3 * 24 * 4
+        );
+        // 2025-01 has 4 Mondays besides the 1st, a Wednesday.
+        assert_eq!(
+            Schedule::parse("0 0 1 * mon")
+                .unwrap()
+                .count_within(datetime!(2025-01-01 00:00 UTC), 31 * 24 * 60),
+            5
+        );
+    }
+
+    #[test]
+    fn counts_long_windows_without_overflow() {
+        let yearly = Schedule::parse("@yearly").unwrap();
+        let from = datetime!(2025-06-01 00:00 UTC);
+        let count = yearly.count_within(from, u32::MAX);
+        assert!((8165..=8167).contains(&count), "{count}");
+        let hourly = Schedule::parse("@hourly").unwrap();
+        assert_eq!(
+            hourly.count_within(from, u32::MAX),
+            (u32::MAX as u64).div_ceil(60)
+        );
+    }
+}
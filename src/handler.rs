@@ -11,19 +11,26 @@ use axum::{
 };
 use axum_extra::{headers, TypedHeader};
 use serde::Deserialize;
+use time::{macros::format_description, Duration, OffsetDateTime, PrimitiveDateTime};
 use ulid::Ulid;
 
 use crate::{
-    api::{fediverse::get_auth_redirect_url, kube::*},
+    api::{
+        fediverse::{get_auth_redirect_url, import_posts, ImportFilter},
+        kube::*,
+    },
     internationalization::t,
 };
 
 use self::{
     auth::FediverseUser,
-    extract::Language,
-    templates::{IndexLoginTemplate, IndexLogoutTemplate, TemplateError},
+    extract::{Language, QuoteQuery},
+    templates::{IndexLoginTemplate, IndexLogoutTemplate, StatsTemplate, TemplateError},
 };
 
+/// Scheduled posts repeat yearly, so they can be at most this far ahead.
+const SCHEDULED_POST_MAX_AHEAD: Duration = Duration::days(365);
+
 pub fn create_router() -> Router {
     let auth = auth::create_router();
 
@@ -31,6 +38,7 @@ pub fn create_router() -> Router {
         .route("/index.css", routing::get(get_index_css))
         .route("/healthz", routing::get(get_healthz))
         .route("/", routing::get(get_index).post(post_index))
+        .route("/stats", routing::get(get_stats))
         .nest("/auth", auth)
 }
 
@@ -54,7 +62,11 @@ fn fmt_user(user: &Result<FediverseUser, ()>) -> String {
 }
 
 #[tracing::instrument(skip_all, fields(user = fmt_user(&user)))]
-async fn get_index(Language(language): Language, user: Result<FediverseUser, ()>) -> Html<String> {
+async fn get_index(
+    Language(language): Language,
+    quote_query: QuoteQuery,
+    user: Result<FediverseUser, ()>,
+) -> Html<String> {
     if let Ok(user) = user {
         let quotes = load_quotes(&user.domain, &user.handle)
             .await
@@ -62,18 +74,18 @@ async fn get_index(Language(language): Language, user: Result<FediverseUser, ()>
                 tracing::error!(?error, "failed to load quotes");
                 BTreeMap::new()
             });
-        let (cron_input, dedup_duration_minutes, suspend_schedule) =
+        let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
             load_cronjob(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load schedule");
-                    (String::new(), 0, false)
+                    (String::new(), 0, String::new(), false)
                 });
         let reply_map = load_replies(&user.domain, &user.handle)
             .await
             .unwrap_or_else(|error| {
                 tracing::error!(?error, "failed to load replies");
-                BTreeMap::new()
+                Vec::new()
             });
         let enable_reply = get_reply_enabled(&user.domain, &user.handle)
             .await
@@ -81,11 +93,53 @@ async fn get_index(Language(language): Language, user: Result<FediverseUser, ()>
                 tracing::error!(?error, "failed to get reply enabled");
                 false
             });
-        let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+        let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
             .await
             .unwrap_or_else(|error| {
-                tracing::error!(?error, "failed to get dice feature enabled");
-                false
+                tracing::error!(?error, "failed to get enabled responders");
+                Vec::new()
+            });
+        let reply_settings = get_reply_settings(&user.domain, &user.handle)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::error!(?error, "failed to get reply settings");
+                ReplySettings::default()
+            });
+        let generation_settings = get_generation_settings(&user.domain, &user.handle)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::error!(?error, "failed to get generation settings");
+                GenerationSettings::default()
+            });
+        let streaming_status = get_streaming_status(&user.domain, &user.handle)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::error!(?error, "failed to get streaming status");
+                None
+            });
+        let reactions = load_reactions(&user.domain, &user.handle)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::error!(?error, "failed to load reactions");
+                Reactions::default()
+            });
+        let scripts = load_scripts(&user.domain, &user.handle)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::error!(?error, "failed to load scripts");
+                Vec::new()
+            });
+        let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::error!(?error, "failed to load scheduled posts");
+                Vec::new()
+            });
+        let post_history = load_post_history(&user.domain, &user.handle)
+            .await
+            .unwrap_or_else(|error| {
+                tracing::error!(?error, "failed to load post history");
+                PostHistory::default()
             });
 
         Html(
@@ -94,22 +148,43 @@ async fn get_index(Language(language): Language, user: Result<FediverseUser, ()>
                 user,
                 quote_mode_selected: true,
                 quotes,
+                quote_query,
                 is_quote_bulk_selected: false,
                 quote_input: String::new(),
                 quote_bulk_input: String::new(),
                 quote_error: None,
+                quote_report: AddQuotesReport::default(),
                 cron_input,
                 cron_error: None,
                 dedup_duration_minutes,
+                dedup_fallback,
                 suspend_schedule,
+                generation_settings,
+                generation_error: None,
+                import_candidates: Vec::new(),
+                import_error: None,
                 enable_reply,
                 is_reply_bulk_selected: false,
                 reply_map,
                 reply_keyword_input: String::new(),
+                reply_match_type_input: String::new(),
                 reply_input: String::new(),
                 reply_bulk_input: String::new(),
                 reply_error: None,
-                enable_dice_feature,
+                enabled_responders,
+                reply_settings,
+                streaming_status,
+                reactions,
+                scripts,
+                script_keyword_input: String::new(),
+                script_steps_input: String::new(),
+                script_error: None,
+                post_history,
+                scheduled_posts,
+                scheduled_at_input: String::new(),
+                scheduled_text_input: String::new(),
+                post_now_error: None,
+                scheduled_post_error: None,
             }
             .render()
             .unwrap(),
@@ -127,42 +202,80 @@ async fn get_index(Language(language): Language, user: Result<FediverseUser, ()>
     }
 }
 
+#[tracing::instrument(skip_all, fields(user = fmt_user(&user)))]
+async fn get_stats(
+    Language(language): Language,
+    user: Result<FediverseUser, ()>,
+) -> Result<Html<String>, Redirect> {
+    let Ok(user) = user else {
+        return Err(Redirect::to("/"));
+    };
+    let stats = load_stats(&user.domain, &user.handle)
+        .await
+        .unwrap_or_else(|error| {
+            tracing::error!(?error, "failed to load stats");
+            Stats::default()
+        });
+
+    Ok(Html(
+        StatsTemplate {
+            language,
+            user,
+            stats,
+        }
+        .render()
+        .unwrap(),
+    ))
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "snake_case", tag = "add_quote_mode")]
 enum AddQuote {
     OneByOne {
         #[serde(default)]
         quote: String,
+        #[serde(default)]
+        near_duplicate: NearDuplicateChoice,
     },
     Bulk {
         #[serde(default)]
         quote_bulk: String,
+        #[serde(default)]
+        near_duplicate: NearDuplicateChoice,
     },
 }
 
 impl AddQuote {
     fn is_empty(&self) -> bool {
         match self {
-            Self::OneByOne { quote } => quote.is_empty(),
-            Self::Bulk { quote_bulk } => quote_bulk.is_empty(),
+            Self::OneByOne { quote, .. } => quote.is_empty(),
+            Self::Bulk { quote_bulk, .. } => quote_bulk.is_empty(),
         }
     }
 
     fn is_bulk(&self) -> bool {
-        matches!(self, Self::Bulk { quote_bulk: _ })
+        matches!(self, Self::Bulk { .. })
     }
 
     fn as_one_by_one(&self) -> String {
         match self {
-            Self::OneByOne { quote } => quote.clone(),
-            Self::Bulk { quote_bulk: _ } => String::new(),
+            Self::OneByOne { quote, .. } => quote.clone(),
+            Self::Bulk { .. } => String::new(),
         }
     }
 
     fn as_bulk(&self) -> String {
         match self {
-            Self::OneByOne { quote: _ } => String::new(),
-            Self::Bulk { quote_bulk } => quote_bulk.clone(),
+            Self::OneByOne { .. } => String::new(),
+            Self::Bulk { quote_bulk, .. } => quote_bulk.clone(),
+        }
+    }
+
+    fn near_duplicate_choice(&self) -> NearDuplicateChoice {
+        match self {
+            Self::OneByOne { near_duplicate, .. } | Self::Bulk { near_duplicate, .. } => {
+                *near_duplicate
+            }
         }
     }
 }
@@ -174,12 +287,16 @@ enum AddReply {
         #[serde(default)]
         keyword: String,
         #[serde(default)]
+        match_type: String,
+        #[serde(default)]
         reply: String,
     },
     Bulk {
         #[serde(default)]
         keyword: String,
         #[serde(default)]
+        match_type: String,
+        #[serde(default)]
         reply_bulk: String,
     },
 }
@@ -187,58 +304,77 @@ enum AddReply {
 impl AddReply {
     fn is_empty(&self) -> bool {
         match self {
-            Self::OneByOne { keyword, reply } => keyword.is_empty() || reply.is_empty(),
+            Self::OneByOne { keyword, reply, .. } => keyword.is_empty() || reply.is_empty(),
             Self::Bulk {
                 keyword,
                 reply_bulk,
+                ..
             } => keyword.is_empty() || reply_bulk.is_empty(),
         }
     }
 
     fn is_bulk(&self) -> bool {
-        matches!(
-            self,
-            Self::Bulk {
-                keyword: _,
-                reply_bulk: _
-            }
-        )
+        matches!(self, Self::Bulk { .. })
     }
 
     fn keyword(&self) -> String {
         match self {
-            Self::OneByOne { keyword, reply: _ } => keyword.clone(),
-            Self::Bulk {
-                keyword,
-                reply_bulk: _,
-            } => keyword.clone(),
+            Self::OneByOne { keyword, .. } => keyword.clone(),
+            Self::Bulk { keyword, .. } => keyword.clone(),
+        }
+    }
+
+    fn match_type(&self) -> String {
+        match self {
+            Self::OneByOne { match_type, .. } => match_type.clone(),
+            Self::Bulk { match_type, .. } => match_type.clone(),
         }
     }
 
     fn as_one_by_one(&self) -> String {
         match self {
-            Self::OneByOne { keyword: _, reply } => reply.clone(),
-            Self::Bulk {
-                keyword: _,
-                reply_bulk: _,
-            } => String::new(),
+            Self::OneByOne { reply, .. } => reply.clone(),
+            Self::Bulk { .. } => String::new(),
         }
     }
 
     fn as_bulk(&self) -> String {
         match self {
-            Self::OneByOne {
-                keyword: _,
-                reply: _,
-            } => String::new(),
-            Self::Bulk {
-                keyword: _,
-                reply_bulk,
-            } => reply_bulk.clone(),
+            Self::OneByOne { .. } => String::new(),
+            Self::Bulk { reply_bulk, .. } => reply_bulk.clone(),
         }
     }
 }
 
+fn parse_scheduled_post(
+    scheduled_at: &str,
+    scheduled_quote_id: &str,
+    scheduled_text: &str,
+) -> Result<(OffsetDateTime, Option<Ulid>, Option<String>), &'static str> {
+    let scheduled_at = PrimitiveDateTime::parse(
+        scheduled_at,
+        format_description!("[year]-[month]-[day]T[hour]:[minute]"),
+    )
+    .map_err(|_| "invalid-datetime")?
+    .assume_utc();
+    let now = OffsetDateTime::now_utc();
+    if scheduled_at <= now {
+        return Err("scheduled-at-must-be-future");
+    }
+    if scheduled_at > now + SCHEDULED_POST_MAX_AHEAD {
+        return Err("scheduled-at-too-far");
+    }
+
+    if !scheduled_quote_id.is_empty() {
+        let quote_id = Ulid::from_string(scheduled_quote_id).map_err(|_| "quote-not-found")?;
+        Ok((scheduled_at, Some(quote_id), None))
+    } else if !scheduled_text.is_empty() {
+        Ok((scheduled_at, None, Some(scheduled_text.to_string())))
+    } else {
+        Err("value-cannot-empty")
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "snake_case", tag = "type")]
 enum PostIndexReq {
@@ -247,15 +383,46 @@ enum PostIndexReq {
         domain: String,
     },
     AddQuote(AddQuote),
+    ImportQuotes {
+        acct: String,
+        #[serde(default)]
+        hashtag: String,
+        #[serde(default)]
+        since: String,
+        #[serde(default)]
+        min_length: String,
+    },
+    AddImportedQuotes {
+        /// Checked posts, as `import_<index>` keys with the post as the value.
+        #[serde(flatten)]
+        selected: BTreeMap<String, String>,
+    },
     ConfigureSchedule {
         cron: String,
         #[serde(default)]
         suspend: String,
         #[serde(default)]
         dedup_duration_minutes: String,
+        #[serde(default)]
+        dedup_fallback: String,
+    },
+    DeleteQuotes {
+        /// Quotes to delete, as `quote_<id>` keys.
+        #[serde(flatten)]
+        selected: BTreeMap<String, String>,
+    },
+    PostNow {},
+    PreviewPost {},
+    SchedulePost {
+        #[serde(default)]
+        scheduled_at: String,
+        #[serde(default)]
+        scheduled_quote_id: String,
+        #[serde(default)]
+        scheduled_text: String,
     },
-    DeleteQuote {
-        quote_id: Ulid,
+    DeleteScheduledPost {
+        scheduled_post_id: Ulid,
     },
     AddReply(AddReply),
     DeleteReply {
@@ -265,19 +432,148 @@ enum PostIndexReq {
     DeleteReplyAll {
         keyword: String,
     },
+    UpdateReplyRule {
+        keyword: String,
+        match_type: String,
+        #[serde(default)]
+        reaction: String,
+        #[serde(default)]
+        react_only: String,
+    },
+    MoveReplyRule {
+        keyword: String,
+        direction: String,
+    },
+    ConfigureGeneration {
+        #[serde(default)]
+        markov: String,
+        markov_order: String,
+        markov_tokenizer: String,
+        #[serde(default)]
+        feed_url: String,
+        #[serde(default)]
+        feed_template: String,
+    },
     ConfigureReply {
         #[serde(default)]
         enable: String,
         #[serde(default)]
         dice_feature: String,
+        #[serde(default)]
+        coin_feature: String,
+        #[serde(default)]
+        choose_feature: String,
+        #[serde(default)]
+        eight_ball_feature: String,
+        #[serde(default)]
+        tarot_feature: String,
+        #[serde(default)]
+        random_number_feature: String,
+        #[serde(default)]
+        reply_cooldown_seconds: String,
+        #[serde(default)]
+        replies_per_minute: String,
+        #[serde(default)]
+        ignore_bots: String,
+        #[serde(default)]
+        reply_visibility_cap: String,
+    },
+    AddScript {
+        keyword: String,
+        match_type: String,
+        expiry_minutes: String,
+        steps: String,
     },
-    RestartReply {},
+    DeleteScript {
+        keyword: String,
+    },
+    ConfigureReactions {
+        #[serde(default)]
+        follow_welcome: String,
+        #[serde(default)]
+        follow_back: String,
+        #[serde(default)]
+        follow_messages: String,
+        #[serde(default)]
+        reblog_mode: String,
+        #[serde(default)]
+        reblog_messages: String,
+        #[serde(default)]
+        favourite_mode: String,
+        #[serde(default)]
+        favourite_messages: String,
+    },
+}
+
+/// Splits a textarea with one item per line.
+fn lines(text: &str) -> Vec<String> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Parses script steps, which are blocks separated by blank lines. In each block, lines start with
+/// `Q:` for the prompt, `A:` for comma-separated answers, `O:` for the reply to a right answer and
+/// `X:` for the reply to a wrong one. Other lines continue the line before.
+fn parse_script_steps(text: &str) -> Result<Vec<ScriptStep>, String> {
+    let mut steps = Vec::new();
+    let mut step: Option<ScriptStep> = None;
+    let mut field = None;
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            steps.extend(step.take());
+            field = None;
+            continue;
+        }
+        let current = step.get_or_insert_with(ScriptStep::default);
+        let (prefix, value) = match line.split_once(':') {
+            Some((prefix, value)) if ["Q", "A", "O", "X"].contains(&prefix.trim()) => {
+                (Some(prefix.trim()), value.trim())
+            }
+            _ => (None, line),
+        };
+        let Some(prefix) = prefix.or(field) else {
+            return Err(format!("{}: {line}", index + 1));
+        };
+        field = Some(prefix);
+        let target = match prefix {
+            "A" => {
+                current.answers.extend(
+                    value
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|answer| !answer.is_empty())
+                        .map(str::to_string),
+                );
+                continue;
+            }
+            "Q" => &mut current.prompt,
+            "O" => &mut current.correct,
+            _ => &mut current.wrong,
+        };
+        if !target.is_empty() {
+            target.push('\n');
+        }
+        target.push_str(value);
+    }
+    steps.extend(step);
+    if steps.is_empty() {
+        return Err(text.trim().to_string());
+    }
+    if let Some(index) = steps.iter().position(|step| step.prompt.is_empty()) {
+        return Err(format!("#{}", index + 1));
+    }
+    Ok(steps)
 }
 
 #[tracing::instrument(skip_all, fields(user = fmt_user(&user)))]
 async fn post_index(
     user: Result<FediverseUser, ()>,
     Language(language): Language,
+    quote_query: QuoteQuery,
     Form(req): Form<PostIndexReq>,
 ) -> Result<Html<String>, Redirect> {
     match (user, req) {
@@ -343,18 +639,18 @@ async fn post_index(
             .unwrap(),
         )),
         (Ok(user), PostIndexReq::AddQuote(req)) => {
-            let (cron_input, dedup_duration_minutes, suspend_schedule) =
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
                 load_cronjob(&user.domain, &user.handle)
                     .await
                     .unwrap_or_else(|error| {
                         tracing::error!(?error, "failed to load schedule");
-                        (String::new(), 0, false)
+                        (String::new(), 0, String::new(), false)
                     });
             let reply_map = load_replies(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load replies");
-                    BTreeMap::new()
+                    Vec::new()
                 });
             let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
@@ -362,11 +658,53 @@ async fn post_index(
                     tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
-            let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
-                    tracing::error!(?error, "failed to get dice feature enabled");
-                    false
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
                 });
 
             if req.is_empty() {
@@ -382,6 +720,7 @@ async fn post_index(
                         user,
                         quote_mode_selected: true,
                         quotes,
+                        quote_query,
                         is_quote_bulk_selected: req.is_bulk(),
                         quote_input: req.as_one_by_one(),
                         quote_bulk_input: req.as_bulk(),
@@ -389,56 +728,112 @@ async fn post_index(
                             summary: t(&language, "value-cannot-empty"),
                             detail: None,
                         }),
+                        quote_report: AddQuotesReport::default(),
                         cron_input,
                         cron_error: None,
                         dedup_duration_minutes,
+                        dedup_fallback,
                         suspend_schedule,
+                        generation_settings,
+                        generation_error: None,
+                        import_candidates: Vec::new(),
+                        import_error: None,
                         enable_reply,
                         is_reply_bulk_selected: false,
                         reply_map,
                         reply_keyword_input: String::new(),
+                        reply_match_type_input: String::new(),
                         reply_input: String::new(),
                         reply_bulk_input: String::new(),
                         reply_error: None,
                         language,
-                        enable_dice_feature,
+                        enabled_responders,
+                        reply_settings,
+                        streaming_status,
+                        reactions,
+                        scripts,
+                        script_keyword_input: String::new(),
+                        script_steps_input: String::new(),
+                        script_error: None,
+                        post_history,
+                        scheduled_posts,
+                        scheduled_at_input: String::new(),
+                        scheduled_text_input: String::new(),
+                        post_now_error: None,
+                        scheduled_post_error: None,
                     }
                     .render()
                     .unwrap(),
                 ));
             }
             let quotes = match &req {
-                AddQuote::OneByOne { quote } => vec![quote.trim().to_string()],
-                AddQuote::Bulk { quote_bulk } => quote_bulk
+                AddQuote::OneByOne { quote, .. } => vec![quote.trim().to_string()],
+                AddQuote::Bulk { quote_bulk, .. } => quote_bulk
                     .lines()
                     .filter(|s| !s.is_empty())
                     .map(|s| s.trim().to_string())
                     .collect(),
             };
 
-            match add_quotes(&user.domain, &user.handle, quotes).await {
-                Ok(quotes) => Ok(Html(
+            match add_quotes(
+                &user.domain,
+                &user.handle,
+                quotes,
+                req.near_duplicate_choice(),
+            )
+            .await
+            {
+                Ok((quotes, quote_report)) => Ok(Html(
                     IndexLoginTemplate {
                         user,
                         quote_mode_selected: true,
                         quotes,
+                        quote_query,
                         is_quote_bulk_selected: req.is_bulk(),
-                        quote_input: String::new(),
-                        quote_bulk_input: String::new(),
+                        quote_input: if quote_report.pending {
+                            req.as_one_by_one()
+                        } else {
+                            String::new()
+                        },
+                        quote_bulk_input: if quote_report.pending {
+                            req.as_bulk()
+                        } else {
+                            String::new()
+                        },
                         quote_error: None,
+                        quote_report,
                         cron_input,
                         cron_error: None,
                         dedup_duration_minutes,
+                        dedup_fallback,
                         suspend_schedule,
+                        generation_settings,
+                        generation_error: None,
+                        import_candidates: Vec::new(),
+                        import_error: None,
                         enable_reply,
                         is_reply_bulk_selected: false,
                         reply_map,
                         reply_keyword_input: String::new(),
+                        reply_match_type_input: String::new(),
                         reply_input: String::new(),
                         reply_bulk_input: String::new(),
                         reply_error: None,
                         language,
-                        enable_dice_feature,
+                        enabled_responders,
+                        reply_settings,
+                        streaming_status,
+                        reactions,
+                        scripts,
+                        script_keyword_input: String::new(),
+                        script_steps_input: String::new(),
+                        script_error: None,
+                        post_history,
+                        scheduled_posts,
+                        scheduled_at_input: String::new(),
+                        scheduled_text_input: String::new(),
+                        post_now_error: None,
+                        scheduled_post_error: None,
                     }
                     .render()
                     .unwrap(),
@@ -456,6 +851,7 @@ async fn post_index(
                             user,
                             quote_mode_selected: true,
                             quotes,
+                            quote_query,
                             is_quote_bulk_selected: req.is_bulk(),
                             quote_input: req.as_one_by_one(),
                             quote_bulk_input: req.as_bulk(),
@@ -463,19 +859,39 @@ async fn post_index(
                                 summary: t(&language, "add-quote-error"),
                                 detail: Some(format!("{error:?}")),
                             }),
+                            quote_report: AddQuotesReport::default(),
                             cron_input,
                             cron_error: None,
                             dedup_duration_minutes,
+                            dedup_fallback,
                             suspend_schedule,
+                            generation_settings,
+                            generation_error: None,
+                            import_candidates: Vec::new(),
+                            import_error: None,
                             enable_reply,
                             is_reply_bulk_selected: false,
                             reply_map,
                             reply_keyword_input: String::new(),
+                            reply_match_type_input: String::new(),
                             reply_input: String::new(),
                             reply_bulk_input: String::new(),
                             reply_error: None,
                             language,
-                            enable_dice_feature,
+                            enabled_responders,
+                            reply_settings,
+                            streaming_status,
+                            reactions,
+                            scripts,
+                            script_keyword_input: String::new(),
+                            script_steps_input: String::new(),
+                            script_error: None,
+                            post_history,
+                            scheduled_posts,
+                            scheduled_at_input: String::new(),
+                            scheduled_text_input: String::new(),
+                            post_now_error: None,
+                            scheduled_post_error: None,
                         }
                         .render()
                         .unwrap(),
@@ -489,10 +905,15 @@ async fn post_index(
                 cron,
                 suspend,
                 dedup_duration_minutes,
+                dedup_fallback,
             },
         ) => {
             let suspend = suspend == "on";
             let dedup_duration_minutes = dedup_duration_minutes.parse::<u32>().unwrap_or(0);
+            let dedup_fallback = match dedup_fallback.as_str() {
+                "least_recently_posted" | "shrink_window" => dedup_fallback,
+                _ => "skip".to_string(),
+            };
             let quotes = load_quotes(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
@@ -503,7 +924,7 @@ async fn post_index(
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load replies");
-                    BTreeMap::new()
+                    Vec::new()
                 });
             let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
@@ -511,11 +932,53 @@ async fn post_index(
                     tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
-            let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
-                    tracing::error!(?error, "failed to get dice feature enabled");
-                    false
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
                 });
 
             if cron.is_empty() {
@@ -524,26 +987,47 @@ async fn post_index(
                         user,
                         quote_mode_selected: true,
                         quotes,
+                        quote_query,
                         is_quote_bulk_selected: false,
                         quote_input: String::new(),
                         quote_bulk_input: String::new(),
                         quote_error: None,
+                        quote_report: AddQuotesReport::default(),
                         cron_input: String::new(),
                         cron_error: Some(TemplateError {
                             summary: t(&language, "value-cannot-empty"),
                             detail: None,
                         }),
                         dedup_duration_minutes,
+                        dedup_fallback,
                         suspend_schedule: suspend,
+                        generation_settings,
+                        generation_error: None,
+                        import_candidates: Vec::new(),
+                        import_error: None,
                         enable_reply,
                         is_reply_bulk_selected: false,
                         reply_map,
                         reply_keyword_input: String::new(),
+                        reply_match_type_input: String::new(),
                         reply_input: String::new(),
                         reply_bulk_input: String::new(),
                         reply_error: None,
                         language,
-                        enable_dice_feature,
+                        enabled_responders,
+                        reply_settings,
+                        streaming_status,
+                        reactions,
+                        scripts,
+                        script_keyword_input: String::new(),
+                        script_steps_input: String::new(),
+                        script_error: None,
+                        post_history,
+                        scheduled_posts,
+                        scheduled_at_input: String::new(),
+                        scheduled_text_input: String::new(),
+                        post_now_error: None,
+                        scheduled_post_error: None,
                     }
                     .render()
                     .unwrap(),
@@ -557,6 +1041,7 @@ async fn post_index(
                 &user.software,
                 &cron,
                 dedup_duration_minutes,
+                &dedup_fallback,
                 suspend,
             )
             .await
@@ -566,23 +1051,44 @@ async fn post_index(
                         user,
                         quote_mode_selected: true,
                         quotes,
+                        quote_query,
                         is_quote_bulk_selected: false,
                         quote_input: String::new(),
                         quote_bulk_input: String::new(),
                         quote_error: None,
+                        quote_report: AddQuotesReport::default(),
                         cron_input: cron,
                         cron_error: None,
+                        generation_settings,
+                        generation_error: None,
+                        import_candidates: Vec::new(),
+                        import_error: None,
                         enable_reply,
                         dedup_duration_minutes,
+                        dedup_fallback,
                         suspend_schedule: suspend,
                         is_reply_bulk_selected: false,
                         reply_map,
                         reply_keyword_input: String::new(),
+                        reply_match_type_input: String::new(),
                         reply_input: String::new(),
                         reply_bulk_input: String::new(),
                         reply_error: None,
                         language,
-                        enable_dice_feature,
+                        enabled_responders,
+                        reply_settings,
+                        streaming_status,
+                        reactions,
+                        scripts,
+                        script_keyword_input: String::new(),
+                        script_steps_input: String::new(),
+                        script_error: None,
+                        post_history,
+                        scheduled_posts,
+                        scheduled_at_input: String::new(),
+                        scheduled_text_input: String::new(),
+                        post_now_error: None,
+                        scheduled_post_error: None,
                     }
                     .render()
                     .unwrap(),
@@ -594,26 +1100,47 @@ async fn post_index(
                             user,
                             quote_mode_selected: true,
                             quotes,
+                            quote_query,
                             is_quote_bulk_selected: false,
                             quote_input: String::new(),
                             quote_bulk_input: String::new(),
                             quote_error: None,
+                            quote_report: AddQuotesReport::default(),
                             cron_input: cron,
                             cron_error: Some(TemplateError {
                                 summary: t(&language, "configure-schedule-error"),
                                 detail: Some(format!("{error:?}")),
                             }),
                             dedup_duration_minutes,
+                            dedup_fallback,
                             suspend_schedule: suspend,
+                            generation_settings,
+                            generation_error: None,
+                            import_candidates: Vec::new(),
+                            import_error: None,
                             enable_reply,
                             is_reply_bulk_selected: false,
                             reply_map,
                             reply_keyword_input: String::new(),
+                            reply_match_type_input: String::new(),
                             reply_input: String::new(),
                             reply_bulk_input: String::new(),
                             reply_error: None,
                             language,
-                            enable_dice_feature,
+                            enabled_responders,
+                            reply_settings,
+                            streaming_status,
+                            reactions,
+                            scripts,
+                            script_keyword_input: String::new(),
+                            script_steps_input: String::new(),
+                            script_error: None,
+                            post_history,
+                            scheduled_posts,
+                            scheduled_at_input: String::new(),
+                            scheduled_text_input: String::new(),
+                            post_now_error: None,
+                            scheduled_post_error: None,
                         }
                         .render()
                         .unwrap(),
@@ -621,8 +1148,12 @@ async fn post_index(
                 }
             }
         }
-        (Ok(user), PostIndexReq::DeleteQuote { quote_id }) => {
-            let quotes = match delete_quote(&user.domain, &user.handle, quote_id).await {
+        (Ok(user), PostIndexReq::DeleteQuotes { selected }) => {
+            let ids = selected
+                .keys()
+                .filter_map(|key| Ulid::from_string(key.strip_prefix("quote_")?).ok())
+                .collect::<Vec<_>>();
+            let quotes = match delete_quotes(&user.domain, &user.handle, &ids).await {
                 Ok(quotes) => quotes,
                 Err(error) => {
                     tracing::error!(?error, "failed to delete quotes");
@@ -634,18 +1165,18 @@ async fn post_index(
                         })
                 }
             };
-            let (cron_input, dedup_duration_minutes, suspend_schedule) =
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
                 load_cronjob(&user.domain, &user.handle)
                     .await
                     .unwrap_or_else(|error| {
                         tracing::error!(?error, "failed to load schedule");
-                        (String::new(), 0, false)
+                        (String::new(), 0, String::new(), false)
                     });
             let reply_map = load_replies(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load replies");
-                    BTreeMap::new()
+                    Vec::new()
                 });
             let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
@@ -653,11 +1184,53 @@ async fn post_index(
                     tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
-            let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
-                    tracing::error!(?error, "failed to get dice feature enabled");
-                    false
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
                 });
 
             Ok(Html(
@@ -665,305 +1238,2129 @@ async fn post_index(
                     user,
                     quote_mode_selected: true,
                     quotes,
+                    quote_query,
                     is_quote_bulk_selected: false,
                     quote_input: String::new(),
                     quote_bulk_input: String::new(),
                     quote_error: None,
+                    quote_report: AddQuotesReport::default(),
                     cron_input,
                     cron_error: None,
                     dedup_duration_minutes,
+                    dedup_fallback,
                     suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
                     enable_reply,
                     is_reply_bulk_selected: false,
                     reply_map,
                     reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
                     reply_input: String::new(),
                     reply_bulk_input: String::new(),
                     reply_error: None,
                     language,
-                    enable_dice_feature,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
                 }
                 .render()
                 .unwrap(),
             ))
         }
-        (Ok(user), PostIndexReq::AddReply(req)) => {
+        (Ok(user), PostIndexReq::PostNow {}) => {
+            let post_now_error = if let Err(error) = post_now(&user.domain, &user.handle).await {
+                tracing::warn!(?error, "failed to post now");
+                Some(TemplateError {
+                    summary: t(&language, "post-now-error"),
+                    detail: Some(format!("{error:?}")),
+                })
+            } else {
+                None
+            };
+
             let quotes = load_quotes(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load quotes");
                     BTreeMap::new()
                 });
-            let (cron_input, dedup_duration_minutes, suspend_schedule) =
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
                 load_cronjob(&user.domain, &user.handle)
                     .await
                     .unwrap_or_else(|error| {
                         tracing::error!(?error, "failed to load schedule");
-                        (String::new(), 0, false)
+                        (String::new(), 0, String::new(), false)
                     });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
             let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
-            let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
-                    tracing::error!(?error, "failed to get dice feature enabled");
-                    false
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
                 });
 
-            if req.is_empty() {
-                let reply_map = load_replies(&user.domain, &user.handle)
-                    .await
-                    .unwrap_or_else(|error| {
-                        tracing::error!(?error, "failed to load replies");
-                        BTreeMap::new()
-                    });
-
-                return Ok(Html(
-                    IndexLoginTemplate {
-                        user,
-                        quote_mode_selected: false,
-                        quotes,
-                        is_quote_bulk_selected: false,
-                        quote_input: String::new(),
-                        quote_bulk_input: String::new(),
-                        quote_error: None,
-                        cron_input,
-                        cron_error: None,
-                        dedup_duration_minutes,
-                        suspend_schedule,
-                        enable_reply,
-                        is_reply_bulk_selected: req.is_bulk(),
-                        reply_map,
-                        reply_keyword_input: req.keyword(),
-                        reply_input: req.as_one_by_one(),
-                        reply_bulk_input: req.as_bulk(),
-                        reply_error: Some(TemplateError {
-                            summary: t(&language, "value-cannot-empty"),
-                            detail: None,
-                        }),
-                        language,
-                        enable_dice_feature,
+            Ok(Html(
+                IndexLoginTemplate {
+                    language,
+                    user,
+                    quote_mode_selected: true,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error,
+                    scheduled_post_error: None,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (Ok(user), PostIndexReq::PreviewPost {}) => {
+            let post_now_error = if let Err(error) = preview_post(&user.domain, &user.handle).await
+            {
+                tracing::warn!(?error, "failed to preview post");
+                Some(TemplateError {
+                    summary: t(&language, "preview-post-error"),
+                    detail: Some(format!("{error:?}")),
+                })
+            } else {
+                None
+            };
+
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    language,
+                    user,
+                    quote_mode_selected: true,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error,
+                    scheduled_post_error: None,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (
+            Ok(user),
+            PostIndexReq::SchedulePost {
+                scheduled_at,
+                scheduled_quote_id,
+                scheduled_text,
+            },
+        ) => {
+            let scheduled_text = scheduled_text.trim().to_string();
+            let scheduled_post_error =
+                match parse_scheduled_post(&scheduled_at, &scheduled_quote_id, &scheduled_text) {
+                    Ok((scheduled_at, quote_id, text)) => {
+                        if let Err(error) =
+                            schedule_post(&user.domain, &user.handle, scheduled_at, quote_id, text)
+                                .await
+                        {
+                            tracing::warn!(?error, "failed to schedule post");
+                            Some(TemplateError {
+                                summary: t(&language, "schedule-post-error"),
+                                detail: Some(format!("{error:?}")),
+                            })
+                        } else {
+                            None
+                        }
                     }
-                    .render()
-                    .unwrap(),
-                ));
+                    Err(text_id) => Some(TemplateError {
+                        summary: t(&language, text_id),
+                        detail: None,
+                    }),
+                };
+            let (scheduled_at_input, scheduled_text_input) = if scheduled_post_error.is_some() {
+                (scheduled_at, scheduled_text)
+            } else {
+                (String::new(), String::new())
+            };
+
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    language,
+                    user,
+                    quote_mode_selected: true,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input,
+                    scheduled_text_input,
+                    post_now_error: None,
+                    scheduled_post_error,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (Ok(user), PostIndexReq::DeleteScheduledPost { scheduled_post_id }) => {
+            if let Err(error) =
+                delete_scheduled_post(&user.domain, &user.handle, scheduled_post_id).await
+            {
+                tracing::error!(?error, "failed to delete scheduled post");
             }
-            let (keyword, replies) = match &req {
-                AddReply::OneByOne { keyword, reply } => {
-                    (keyword.trim().to_string(), vec![reply.trim().to_string()])
+
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    language,
+                    user,
+                    quote_mode_selected: true,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (Ok(user), PostIndexReq::AddReply(req)) => {
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            if req.is_empty() {
+                let reply_map = load_replies(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load replies");
+                        Vec::new()
+                    });
+
+                return Ok(Html(
+                    IndexLoginTemplate {
+                        user,
+                        quote_mode_selected: false,
+                        quotes,
+                        quote_query,
+                        is_quote_bulk_selected: false,
+                        quote_input: String::new(),
+                        quote_bulk_input: String::new(),
+                        quote_error: None,
+                        quote_report: AddQuotesReport::default(),
+                        cron_input,
+                        cron_error: None,
+                        dedup_duration_minutes,
+                        dedup_fallback,
+                        suspend_schedule,
+                        generation_settings,
+                        generation_error: None,
+                        import_candidates: Vec::new(),
+                        import_error: None,
+                        enable_reply,
+                        is_reply_bulk_selected: req.is_bulk(),
+                        reply_map,
+                        reply_keyword_input: req.keyword(),
+                        reply_match_type_input: req.match_type(),
+                        reply_input: req.as_one_by_one(),
+                        reply_bulk_input: req.as_bulk(),
+                        reply_error: Some(TemplateError {
+                            summary: t(&language, "value-cannot-empty"),
+                            detail: None,
+                        }),
+                        language,
+                        enabled_responders,
+                        reply_settings,
+                        streaming_status,
+                        reactions,
+                        scripts,
+                        script_keyword_input: String::new(),
+                        script_steps_input: String::new(),
+                        script_error: None,
+                        post_history,
+                        scheduled_posts,
+                        scheduled_at_input: String::new(),
+                        scheduled_text_input: String::new(),
+                        post_now_error: None,
+                        scheduled_post_error: None,
+                    }
+                    .render()
+                    .unwrap(),
+                ));
+            }
+            let (keyword, replies) = match &req {
+                AddReply::OneByOne { keyword, reply, .. } => {
+                    (keyword.trim().to_string(), vec![reply.trim().to_string()])
+                }
+                AddReply::Bulk {
+                    keyword,
+                    reply_bulk,
+                    ..
+                } => (
+                    keyword.trim().to_string(),
+                    reply_bulk
+                        .lines()
+                        .filter(|s| !s.is_empty())
+                        .map(|s| s.trim().to_string())
+                        .collect(),
+                ),
+            };
+
+            let match_type = MatchType::parse(&req.match_type()).unwrap_or_default();
+            if let Err(error) = validate_keyword(&keyword, match_type) {
+                tracing::warn!(?error, "invalid keyword");
+                let reply_map = load_replies(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load replies");
+                        Vec::new()
+                    });
+                return Ok(Html(
+                    IndexLoginTemplate {
+                        user,
+                        quote_mode_selected: false,
+                        quotes,
+                        quote_query,
+                        is_quote_bulk_selected: false,
+                        quote_input: String::new(),
+                        quote_bulk_input: String::new(),
+                        quote_error: None,
+                        quote_report: AddQuotesReport::default(),
+                        cron_input,
+                        cron_error: None,
+                        dedup_duration_minutes,
+                        dedup_fallback,
+                        suspend_schedule,
+                        generation_settings,
+                        generation_error: None,
+                        import_candidates: Vec::new(),
+                        import_error: None,
+                        enable_reply,
+                        is_reply_bulk_selected: req.is_bulk(),
+                        reply_map,
+                        reply_keyword_input: req.keyword(),
+                        reply_match_type_input: req.match_type(),
+                        reply_input: req.as_one_by_one(),
+                        reply_bulk_input: req.as_bulk(),
+                        reply_error: Some(TemplateError {
+                            summary: t(&language, "invalid-regex"),
+                            detail: Some(error.to_string()),
+                        }),
+                        language,
+                        enabled_responders,
+                        reply_settings,
+                        streaming_status,
+                        reactions,
+                        scripts,
+                        script_keyword_input: String::new(),
+                        script_steps_input: String::new(),
+                        script_error: None,
+                        post_history,
+                        scheduled_posts,
+                        scheduled_at_input: String::new(),
+                        scheduled_text_input: String::new(),
+                        post_now_error: None,
+                        scheduled_post_error: None,
+                    }
+                    .render()
+                    .unwrap(),
+                ));
+            }
+
+            match add_replies(&user.domain, &user.handle, keyword, match_type, replies).await {
+                Ok(reply_map) => Ok(Html(
+                    IndexLoginTemplate {
+                        user,
+                        quote_mode_selected: false,
+                        quotes,
+                        quote_query,
+                        is_quote_bulk_selected: false,
+                        quote_input: String::new(),
+                        quote_bulk_input: String::new(),
+                        quote_error: None,
+                        quote_report: AddQuotesReport::default(),
+                        cron_input,
+                        cron_error: None,
+                        dedup_duration_minutes,
+                        dedup_fallback,
+                        suspend_schedule,
+                        generation_settings,
+                        generation_error: None,
+                        import_candidates: Vec::new(),
+                        import_error: None,
+                        enable_reply,
+                        is_reply_bulk_selected: req.is_bulk(),
+                        reply_map,
+                        reply_keyword_input: req.keyword(),
+                        reply_match_type_input: req.match_type(),
+                        reply_input: String::new(),
+                        reply_bulk_input: String::new(),
+                        reply_error: None,
+                        language,
+                        enabled_responders,
+                        reply_settings,
+                        streaming_status,
+                        reactions,
+                        scripts,
+                        script_keyword_input: String::new(),
+                        script_steps_input: String::new(),
+                        script_error: None,
+                        post_history,
+                        scheduled_posts,
+                        scheduled_at_input: String::new(),
+                        scheduled_text_input: String::new(),
+                        post_now_error: None,
+                        scheduled_post_error: None,
+                    }
+                    .render()
+                    .unwrap(),
+                )),
+                Err(error) => {
+                    tracing::warn!(?error, "failed to add replies");
+                    let reply_map = load_replies(&user.domain, &user.handle)
+                        .await
+                        .unwrap_or_else(|error| {
+                            tracing::error!(?error, "failed to load replies");
+                            Vec::new()
+                        });
+                    Ok(Html(
+                        IndexLoginTemplate {
+                            user,
+                            quote_mode_selected: false,
+                            quotes,
+                            quote_query,
+                            is_quote_bulk_selected: false,
+                            quote_input: String::new(),
+                            quote_bulk_input: String::new(),
+                            quote_error: None,
+                            quote_report: AddQuotesReport::default(),
+                            cron_input,
+                            cron_error: None,
+                            dedup_duration_minutes,
+                            dedup_fallback,
+                            suspend_schedule,
+                            generation_settings,
+                            generation_error: None,
+                            import_candidates: Vec::new(),
+                            import_error: None,
+                            enable_reply,
+                            is_reply_bulk_selected: req.is_bulk(),
+                            reply_map,
+                            reply_keyword_input: req.keyword(),
+                            reply_match_type_input: req.match_type(),
+                            reply_input: req.as_one_by_one(),
+                            reply_bulk_input: req.as_bulk(),
+                            reply_error: Some(TemplateError {
+                                summary: t(&language, "add-reply-error"),
+                                detail: Some(format!("{error:?}")),
+                            }),
+                            language,
+                            enabled_responders,
+                            reply_settings,
+                            streaming_status,
+                            reactions,
+                            scripts,
+                            script_keyword_input: String::new(),
+                            script_steps_input: String::new(),
+                            script_error: None,
+                            post_history,
+                            scheduled_posts,
+                            scheduled_at_input: String::new(),
+                            scheduled_text_input: String::new(),
+                            post_now_error: None,
+                            scheduled_post_error: None,
+                        }
+                        .render()
+                        .unwrap(),
+                    ))
+                }
+            }
+        }
+        (Ok(user), PostIndexReq::DeleteReply { keyword, reply_id }) => {
+            let reply_map = match delete_reply(&user.domain, &user.handle, keyword, reply_id).await
+            {
+                Ok(replies) => replies,
+                Err(error) => {
+                    tracing::error!(?error, "failed to delete reply");
+                    load_replies(&user.domain, &user.handle)
+                        .await
+                        .unwrap_or_else(|error| {
+                            tracing::error!(?error, "failed to load replies");
+                            Vec::new()
+                        })
+                }
+            };
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    user,
+                    quote_mode_selected: false,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    language,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (Ok(user), PostIndexReq::DeleteReplyAll { keyword }) => {
+            let reply_map = match delete_reply_all(&user.domain, &user.handle, keyword).await {
+                Ok(replies) => replies,
+                Err(error) => {
+                    tracing::error!(?error, "failed to delete reply");
+                    load_replies(&user.domain, &user.handle)
+                        .await
+                        .unwrap_or_else(|error| {
+                            tracing::error!(?error, "failed to load replies");
+                            Vec::new()
+                        })
+                }
+            };
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    user,
+                    quote_mode_selected: false,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    language,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (
+            Ok(user),
+            PostIndexReq::UpdateReplyRule {
+                keyword,
+                match_type,
+                reaction,
+                react_only,
+            },
+        ) => {
+            let match_type = MatchType::parse(&match_type).unwrap_or_default();
+            let reaction = Some(reaction.trim().to_string()).filter(|s| !s.is_empty());
+            let react_only = reaction.is_some() && react_only == "on";
+            let res = match validate_keyword(&keyword, match_type) {
+                Ok(()) => update_reply_rule(
+                    &user.domain,
+                    &user.handle,
+                    keyword,
+                    match_type,
+                    reaction,
+                    react_only,
+                )
+                .await
+                .map_err(|error| {
+                    tracing::error!(?error, "failed to update reply rule");
+                    TemplateError {
+                        summary: t(&language, "update-reply-rule-error"),
+                        detail: Some(format!("{error:?}")),
+                    }
+                }),
+                Err(error) => {
+                    tracing::warn!(?error, "invalid keyword");
+                    Err(TemplateError {
+                        summary: t(&language, "invalid-regex"),
+                        detail: Some(error.to_string()),
+                    })
+                }
+            };
+            let (reply_map, reply_error) = match res {
+                Ok(reply_map) => (reply_map, None),
+                Err(reply_error) => (
+                    load_replies(&user.domain, &user.handle)
+                        .await
+                        .unwrap_or_else(|error| {
+                            tracing::error!(?error, "failed to load replies");
+                            Vec::new()
+                        }),
+                    Some(reply_error),
+                ),
+            };
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    user,
+                    quote_mode_selected: false,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error,
+                    language,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (Ok(user), PostIndexReq::MoveReplyRule { keyword, direction }) => {
+            let reply_map =
+                match move_reply_rule(&user.domain, &user.handle, keyword, direction == "up").await
+                {
+                    Ok(replies) => replies,
+                    Err(error) => {
+                        tracing::error!(?error, "failed to move reply rule");
+                        load_replies(&user.domain, &user.handle)
+                            .await
+                            .unwrap_or_else(|error| {
+                                tracing::error!(?error, "failed to load replies");
+                                Vec::new()
+                            })
+                    }
+                };
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    user,
+                    quote_mode_selected: false,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    language,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
                 }
-                AddReply::Bulk {
-                    keyword,
-                    reply_bulk,
-                } => (
-                    keyword.trim().to_string(),
-                    reply_bulk
-                        .lines()
-                        .filter(|s| !s.is_empty())
-                        .map(|s| s.trim().to_string())
-                        .collect(),
-                ),
+                .render()
+                .unwrap(),
+            ))
+        }
+        (
+            Ok(user),
+            PostIndexReq::ConfigureReply {
+                enable,
+                dice_feature,
+                coin_feature,
+                choose_feature,
+                eight_ball_feature,
+                tarot_feature,
+                random_number_feature,
+                reply_cooldown_seconds,
+                replies_per_minute,
+                ignore_bots,
+                reply_visibility_cap,
+            },
+        ) => {
+            if enable == "on" {
+                if let Err(error) = enable_reply(
+                    &user.domain,
+                    &user.handle,
+                    &user.access_token,
+                    &user.software,
+                )
+                .await
+                {
+                    tracing::error!(?error, "failed to enable reply");
+                }
+            } else if let Err(error) = disable_reply(&user.domain, &user.handle).await {
+                tracing::error!(?error, "failed to disable reply");
+            }
+            let responders = [
+                (Responder::Dice, dice_feature),
+                (Responder::Coin, coin_feature),
+                (Responder::Choose, choose_feature),
+                (Responder::EightBall, eight_ball_feature),
+                (Responder::Tarot, tarot_feature),
+                (Responder::RandomNumber, random_number_feature),
+            ]
+            .into_iter()
+            .filter(|(_, enabled)| enabled == "on")
+            .map(|(responder, _)| responder)
+            .collect::<Vec<_>>();
+            if let Err(error) =
+                save_enabled_responders(&user.domain, &user.handle, &responders).await
+            {
+                tracing::error!(?error, "failed to save enabled responders");
+            }
+            let reply_settings = ReplySettings {
+                cooldown_seconds: reply_cooldown_seconds.parse().unwrap_or(0),
+                replies_per_minute: replies_per_minute.parse().unwrap_or(0),
+                ignore_bots: ignore_bots == "on",
+                visibility_cap: Visibility::parse(&reply_visibility_cap),
             };
+            if let Err(error) =
+                save_reply_settings(&user.domain, &user.handle, &reply_settings).await
+            {
+                tracing::error!(?error, "failed to save reply settings");
+            }
 
-            match add_replies(&user.domain, &user.handle, keyword, replies).await {
-                Ok(reply_map) => Ok(Html(
-                    IndexLoginTemplate {
-                        user,
-                        quote_mode_selected: false,
-                        quotes,
-                        is_quote_bulk_selected: false,
-                        quote_input: String::new(),
-                        quote_bulk_input: String::new(),
-                        quote_error: None,
-                        cron_input,
-                        cron_error: None,
-                        dedup_duration_minutes,
-                        suspend_schedule,
-                        enable_reply,
-                        is_reply_bulk_selected: req.is_bulk(),
-                        reply_map,
-                        reply_keyword_input: req.keyword(),
-                        reply_input: String::new(),
-                        reply_bulk_input: String::new(),
-                        reply_error: None,
-                        language,
-                        enable_dice_feature,
-                    }
-                    .render()
-                    .unwrap(),
-                )),
-                Err(error) => {
-                    tracing::warn!(?error, "failed to add replies");
-                    let reply_map = load_replies(&user.domain, &user.handle)
-                        .await
-                        .unwrap_or_else(|error| {
-                            tracing::error!(?error, "failed to load replies");
-                            BTreeMap::new()
-                        });
-                    Ok(Html(
-                        IndexLoginTemplate {
-                            user,
-                            quote_mode_selected: false,
-                            quotes,
-                            is_quote_bulk_selected: false,
-                            quote_input: String::new(),
-                            quote_bulk_input: String::new(),
-                            quote_error: None,
-                            cron_input,
-                            cron_error: None,
-                            dedup_duration_minutes,
-                            suspend_schedule,
-                            enable_reply,
-                            is_reply_bulk_selected: req.is_bulk(),
-                            reply_map,
-                            reply_keyword_input: req.keyword(),
-                            reply_input: req.as_one_by_one(),
-                            reply_bulk_input: req.as_bulk(),
-                            reply_error: Some(TemplateError {
-                                summary: t(&language, "add-reply-error"),
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    language,
+                    user,
+                    quote_mode_selected: false,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (
+            Ok(user),
+            PostIndexReq::ImportQuotes {
+                acct,
+                hashtag,
+                since,
+                min_length,
+            },
+        ) => {
+            let hashtag = hashtag.trim().trim_start_matches('#');
+            let filter = ImportFilter {
+                hashtag: (!hashtag.is_empty()).then(|| hashtag.to_string()),
+                since: time::Date::parse(since.trim(), format_description!("[year]-[month]-[day]"))
+                    .ok(),
+                min_length: min_length.trim().parse().unwrap_or(0),
+            };
+            let (import_candidates, import_error) = if acct.trim().is_empty() {
+                (
+                    Vec::new(),
+                    Some(TemplateError {
+                        summary: t(&language, "value-cannot-empty"),
+                        detail: None,
+                    }),
+                )
+            } else {
+                match import_posts(&user.domain, &user.software, &acct, &filter).await {
+                    Ok(posts) if posts.is_empty() => (
+                        posts,
+                        Some(TemplateError {
+                            summary: t(&language, "import-quotes-empty"),
+                            detail: None,
+                        }),
+                    ),
+                    Ok(posts) => (posts, None),
+                    Err(error) => {
+                        tracing::error!(?error, "failed to import posts");
+                        (
+                            Vec::new(),
+                            Some(TemplateError {
+                                summary: t(&language, "import-quotes-error"),
                                 detail: Some(format!("{error:?}")),
                             }),
-                            language,
-                            enable_dice_feature,
-                        }
-                        .render()
-                        .unwrap(),
-                    ))
+                        )
+                    }
+                }
+            };
+
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply enabled");
+                    false
+                });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    language,
+                    user,
+                    quote_mode_selected: true,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates,
+                    import_error,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
                 }
-            }
+                .render()
+                .unwrap(),
+            ))
         }
-        (Ok(user), PostIndexReq::DeleteReply { keyword, reply_id }) => {
-            let reply_map = match delete_reply(&user.domain, &user.handle, keyword, reply_id).await
+        (Ok(user), PostIndexReq::AddImportedQuotes { selected }) => {
+            let mut selected = selected
+                .into_iter()
+                .filter_map(|(key, quote)| {
+                    let index = key.strip_prefix("import_")?.parse::<usize>().ok()?;
+                    let quote = quote.trim();
+                    (!quote.is_empty()).then(|| (index, quote.to_string()))
+                })
+                .collect::<Vec<_>>();
+            selected.sort_by_key(|(index, _)| *index);
+            let (quote_report, import_error) = match add_quotes(
+                &user.domain,
+                &user.handle,
+                selected.into_iter().map(|(_, quote)| quote).collect(),
+                NearDuplicateChoice::Keep,
+            )
+            .await
             {
-                Ok(replies) => replies,
+                Ok((_, quote_report)) => (quote_report, None),
                 Err(error) => {
-                    tracing::error!(?error, "failed to delete reply");
-                    load_replies(&user.domain, &user.handle)
-                        .await
-                        .unwrap_or_else(|error| {
-                            tracing::error!(?error, "failed to load replies");
-                            BTreeMap::new()
-                        })
+                    tracing::error!(?error, "failed to add imported quotes");
+                    (
+                        AddQuotesReport::default(),
+                        Some(TemplateError {
+                            summary: t(&language, "add-quote-error"),
+                            detail: Some(format!("{error:?}")),
+                        }),
+                    )
                 }
             };
+
             let quotes = load_quotes(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load quotes");
                     BTreeMap::new()
                 });
-            let (cron_input, dedup_duration_minutes, suspend_schedule) =
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
                 load_cronjob(&user.domain, &user.handle)
                     .await
                     .unwrap_or_else(|error| {
                         tracing::error!(?error, "failed to load schedule");
-                        (String::new(), 0, false)
+                        (String::new(), 0, String::new(), false)
                     });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
             let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
-            let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
-                    tracing::error!(?error, "failed to get dice feature enabled");
-                    false
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
                 });
 
             Ok(Html(
                 IndexLoginTemplate {
+                    language,
                     user,
-                    quote_mode_selected: false,
+                    quote_mode_selected: true,
                     quotes,
+                    quote_query,
                     is_quote_bulk_selected: false,
                     quote_input: String::new(),
                     quote_bulk_input: String::new(),
                     quote_error: None,
+                    quote_report,
                     cron_input,
                     cron_error: None,
                     dedup_duration_minutes,
+                    dedup_fallback,
                     suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error,
                     enable_reply,
                     is_reply_bulk_selected: false,
                     reply_map,
                     reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
                     reply_input: String::new(),
                     reply_bulk_input: String::new(),
                     reply_error: None,
-                    language,
-                    enable_dice_feature,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
                 }
                 .render()
                 .unwrap(),
             ))
         }
-        (Ok(user), PostIndexReq::DeleteReplyAll { keyword }) => {
-            let reply_map = match delete_reply_all(&user.domain, &user.handle, keyword).await {
-                Ok(replies) => replies,
-                Err(error) => {
-                    tracing::error!(?error, "failed to delete reply");
-                    load_replies(&user.domain, &user.handle)
-                        .await
-                        .unwrap_or_else(|error| {
-                            tracing::error!(?error, "failed to load replies");
-                            BTreeMap::new()
-                        })
+        (
+            Ok(user),
+            PostIndexReq::ConfigureGeneration {
+                markov,
+                markov_order,
+                markov_tokenizer,
+                feed_url,
+                feed_template,
+            },
+        ) => {
+            let feed_url = feed_url.trim().to_string();
+            let generation_error = if !feed_url.is_empty()
+                && !url::Url::parse(&feed_url)
+                    .is_ok_and(|url| url.scheme() == "http" || url.scheme() == "https")
+            {
+                Some(TemplateError {
+                    summary: t(&language, "invalid-feed-url"),
+                    detail: None,
+                })
+            } else {
+                let settings = GenerationSettings {
+                    markov: markov == "on",
+                    markov_order: markov_order.trim().parse::<u32>().unwrap_or(2).clamp(1, 5),
+                    markov_tokenizer: MarkovTokenizer::parse(&markov_tokenizer).unwrap_or_default(),
+                    feed_url,
+                    feed_template: feed_template.trim().to_string(),
+                };
+                if let Err(error) =
+                    save_generation_settings(&user.domain, &user.handle, &settings).await
+                {
+                    tracing::error!(?error, "failed to save generation settings");
                 }
+                None
             };
+
             let quotes = load_quotes(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load quotes");
                     BTreeMap::new()
                 });
-            let (cron_input, dedup_duration_minutes, suspend_schedule) =
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
                 load_cronjob(&user.domain, &user.handle)
                     .await
                     .unwrap_or_else(|error| {
                         tracing::error!(?error, "failed to load schedule");
-                        (String::new(), 0, false)
+                        (String::new(), 0, String::new(), false)
                     });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
             let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
-            let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
-                    tracing::error!(?error, "failed to get dice feature enabled");
-                    false
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
                 });
 
             Ok(Html(
                 IndexLoginTemplate {
+                    language,
                     user,
-                    quote_mode_selected: false,
+                    quote_mode_selected: true,
                     quotes,
+                    quote_query,
                     is_quote_bulk_selected: false,
                     quote_input: String::new(),
                     quote_bulk_input: String::new(),
                     quote_error: None,
+                    quote_report: AddQuotesReport::default(),
                     cron_input,
                     cron_error: None,
                     dedup_duration_minutes,
+                    dedup_fallback,
                     suspend_schedule,
+                    generation_settings,
+                    generation_error,
+                    import_candidates: Vec::new(),
+                    import_error: None,
                     enable_reply,
                     is_reply_bulk_selected: false,
                     reply_map,
                     reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
                     reply_input: String::new(),
                     reply_bulk_input: String::new(),
                     reply_error: None,
-                    language,
-                    enable_dice_feature,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
                 }
                 .render()
                 .unwrap(),
@@ -971,31 +3368,51 @@ async fn post_index(
         }
         (
             Ok(user),
-            PostIndexReq::ConfigureReply {
-                enable,
-                dice_feature,
+            PostIndexReq::ConfigureReactions {
+                follow_welcome,
+                follow_back,
+                follow_messages,
+                reblog_mode,
+                reblog_messages,
+                favourite_mode,
+                favourite_messages,
             },
         ) => {
-            if enable == "on" {
-                if let Err(error) = enable_reply(
-                    &user.domain,
-                    &user.handle,
-                    &user.access_token,
-                    &user.software,
-                )
-                .await
-                {
-                    tracing::error!(?error, "failed to enable reply");
-                }
-            } else if let Err(error) = disable_reply(&user.domain, &user.handle).await {
-                tracing::error!(?error, "failed to disable reply");
+            let reactions = Reactions {
+                follow: FollowReaction {
+                    welcome: follow_welcome == "on",
+                    follow_back: follow_back == "on",
+                    messages: lines(&follow_messages),
+                },
+                reblog: NotificationReaction {
+                    mode: ResponseMode::parse(&reblog_mode).unwrap_or_default(),
+                    messages: lines(&reblog_messages),
+                },
+                favourite: NotificationReaction {
+                    mode: ResponseMode::parse(&favourite_mode).unwrap_or_default(),
+                    messages: lines(&favourite_messages),
+                },
+            };
+            if let Err(error) = save_reactions(&user.domain, &user.handle, &reactions).await {
+                tracing::error!(?error, "failed to save reactions");
             }
-            if dice_feature == "on" {
-                if let Err(error) = enable_dice_feature(&user.domain, &user.handle).await {
-                    tracing::error!(?error, "failed to enable dice feature");
+            // Following back and reacting need scopes that older tokens lack, so hand the
+            // streaming bot the token of this session.
+            match get_reply_enabled(&user.domain, &user.handle).await {
+                Ok(true) => {
+                    if let Err(error) = enable_reply(
+                        &user.domain,
+                        &user.handle,
+                        &user.access_token,
+                        &user.software,
+                    )
+                    .await
+                    {
+                        tracing::error!(?error, "failed to update streaming config");
+                    }
                 }
-            } else if let Err(error) = disable_dice_feature(&user.domain, &user.handle).await {
-                tracing::error!(?error, "failed to disable dice feature");
+                Ok(false) => {}
+                Err(error) => tracing::error!(?error, "failed to get reply enabled"),
             }
 
             let quotes = load_quotes(&user.domain, &user.handle)
@@ -1004,18 +3421,18 @@ async fn post_index(
                     tracing::error!(?error, "failed to load quotes");
                     BTreeMap::new()
                 });
-            let (cron_input, dedup_duration_minutes, suspend_schedule) =
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
                 load_cronjob(&user.domain, &user.handle)
                     .await
                     .unwrap_or_else(|error| {
                         tracing::error!(?error, "failed to load schedule");
-                        (String::new(), 0, false)
+                        (String::new(), 0, String::new(), false)
                     });
             let reply_map = load_replies(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load replies");
-                    BTreeMap::new()
+                    Vec::new()
                 });
             let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
@@ -1023,12 +3440,230 @@ async fn post_index(
                     tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
-            let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
+
+            Ok(Html(
+                IndexLoginTemplate {
+                    language,
+                    user,
+                    quote_mode_selected: false,
+                    quotes,
+                    quote_query,
+                    is_quote_bulk_selected: false,
+                    quote_input: String::new(),
+                    quote_bulk_input: String::new(),
+                    quote_error: None,
+                    quote_report: AddQuotesReport::default(),
+                    cron_input,
+                    cron_error: None,
+                    dedup_duration_minutes,
+                    dedup_fallback,
+                    suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
+                    enable_reply,
+                    is_reply_bulk_selected: false,
+                    reply_map,
+                    reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
+                    reply_input: String::new(),
+                    reply_bulk_input: String::new(),
+                    reply_error: None,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
+                }
+                .render()
+                .unwrap(),
+            ))
+        }
+        (
+            Ok(user),
+            PostIndexReq::AddScript {
+                keyword,
+                match_type,
+                expiry_minutes,
+                steps,
+            },
+        ) => {
+            let keyword = keyword.trim().to_string();
+            let (script_keyword_input, script_steps_input, script_error) = if keyword.is_empty() {
+                (
+                    keyword,
+                    steps,
+                    Some(TemplateError {
+                        summary: t(&language, "value-cannot-empty"),
+                        detail: None,
+                    }),
+                )
+            } else {
+                match parse_script_steps(&steps) {
+                    Ok(parsed_steps) => {
+                        let script = Script {
+                            keyword,
+                            match_type: MatchType::parse(&match_type).unwrap_or_default(),
+                            expiry_minutes: expiry_minutes.trim().parse().unwrap_or(10).max(1),
+                            steps: parsed_steps,
+                        };
+                        match add_script(&user.domain, &user.handle, script).await {
+                            Ok(()) => (String::new(), String::new(), None),
+                            Err(error) => {
+                                tracing::error!(?error, "failed to add script");
+                                (
+                                    String::new(),
+                                    steps,
+                                    Some(TemplateError {
+                                        summary: t(&language, "add-script-error"),
+                                        detail: Some(format!("{error:?}")),
+                                    }),
+                                )
+                            }
+                        }
+                    }
+                    Err(detail) => (
+                        keyword,
+                        steps,
+                        Some(TemplateError {
+                            summary: t(&language, "invalid-script"),
+                            detail: Some(detail),
+                        }),
+                    ),
+                }
+            };
+
+            let quotes = load_quotes(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load quotes");
+                    BTreeMap::new()
+                });
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
+                load_cronjob(&user.domain, &user.handle)
+                    .await
+                    .unwrap_or_else(|error| {
+                        tracing::error!(?error, "failed to load schedule");
+                        (String::new(), 0, String::new(), false)
+                    });
+            let reply_map = load_replies(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load replies");
+                    Vec::new()
+                });
+            let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
-                    tracing::error!(?error, "failed to get dice feature enabled");
+                    tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
+                });
 
             Ok(Html(
                 IndexLoginTemplate {
@@ -1036,30 +3671,51 @@ async fn post_index(
                     user,
                     quote_mode_selected: false,
                     quotes,
+                    quote_query,
                     is_quote_bulk_selected: false,
                     quote_input: String::new(),
                     quote_bulk_input: String::new(),
                     quote_error: None,
+                    quote_report: AddQuotesReport::default(),
                     cron_input,
                     cron_error: None,
                     dedup_duration_minutes,
+                    dedup_fallback,
                     suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
                     enable_reply,
                     is_reply_bulk_selected: false,
                     reply_map,
                     reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
                     reply_input: String::new(),
                     reply_bulk_input: String::new(),
                     reply_error: None,
-                    enable_dice_feature,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input,
+                    script_steps_input,
+                    script_error,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
                 }
                 .render()
                 .unwrap(),
             ))
         }
-        (Ok(user), PostIndexReq::RestartReply {}) => {
-            if let Err(error) = restart_reply(&user.domain, &user.handle).await {
-                tracing::error!(?error, "failed to restart reply");
+        (Ok(user), PostIndexReq::DeleteScript { keyword }) => {
+            if let Err(error) = delete_script(&user.domain, &user.handle, &keyword).await {
+                tracing::error!(?error, "failed to delete script");
             }
 
             let quotes = load_quotes(&user.domain, &user.handle)
@@ -1068,18 +3724,18 @@ async fn post_index(
                     tracing::error!(?error, "failed to load quotes");
                     BTreeMap::new()
                 });
-            let (cron_input, dedup_duration_minutes, suspend_schedule) =
+            let (cron_input, dedup_duration_minutes, dedup_fallback, suspend_schedule) =
                 load_cronjob(&user.domain, &user.handle)
                     .await
                     .unwrap_or_else(|error| {
                         tracing::error!(?error, "failed to load schedule");
-                        (String::new(), 0, false)
+                        (String::new(), 0, String::new(), false)
                     });
             let reply_map = load_replies(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
                     tracing::error!(?error, "failed to load replies");
-                    BTreeMap::new()
+                    Vec::new()
                 });
             let enable_reply = get_reply_enabled(&user.domain, &user.handle)
                 .await
@@ -1087,11 +3743,53 @@ async fn post_index(
                     tracing::error!(?error, "failed to get reply enabled");
                     false
                 });
-            let enable_dice_feature = get_dice_feature_enabled(&user.domain, &user.handle)
+            let enabled_responders = get_enabled_responders(&user.domain, &user.handle)
                 .await
                 .unwrap_or_else(|error| {
-                    tracing::error!(?error, "failed to get dice feature enabled");
-                    false
+                    tracing::error!(?error, "failed to get enabled responders");
+                    Vec::new()
+                });
+            let reply_settings = get_reply_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get reply settings");
+                    ReplySettings::default()
+                });
+            let generation_settings = get_generation_settings(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get generation settings");
+                    GenerationSettings::default()
+                });
+            let streaming_status = get_streaming_status(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to get streaming status");
+                    None
+                });
+            let reactions = load_reactions(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load reactions");
+                    Reactions::default()
+                });
+            let scripts = load_scripts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scripts");
+                    Vec::new()
+                });
+            let scheduled_posts = load_scheduled_posts(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load scheduled posts");
+                    Vec::new()
+                });
+            let post_history = load_post_history(&user.domain, &user.handle)
+                .await
+                .unwrap_or_else(|error| {
+                    tracing::error!(?error, "failed to load post history");
+                    PostHistory::default()
                 });
 
             Ok(Html(
@@ -1100,22 +3798,43 @@ async fn post_index(
                     user,
                     quote_mode_selected: false,
                     quotes,
+                    quote_query,
                     is_quote_bulk_selected: false,
                     quote_input: String::new(),
                     quote_bulk_input: String::new(),
                     quote_error: None,
+                    quote_report: AddQuotesReport::default(),
                     cron_input,
                     cron_error: None,
                     dedup_duration_minutes,
+                    dedup_fallback,
                     suspend_schedule,
+                    generation_settings,
+                    generation_error: None,
+                    import_candidates: Vec::new(),
+                    import_error: None,
                     enable_reply,
                     is_reply_bulk_selected: false,
                     reply_map,
                     reply_keyword_input: String::new(),
+                    reply_match_type_input: String::new(),
                     reply_input: String::new(),
                     reply_bulk_input: String::new(),
                     reply_error: None,
-                    enable_dice_feature,
+                    enabled_responders,
+                    reply_settings,
+                    streaming_status,
+                    reactions,
+                    scripts,
+                    script_keyword_input: String::new(),
+                    script_steps_input: String::new(),
+                    script_error: None,
+                    post_history,
+                    scheduled_posts,
+                    scheduled_at_input: String::new(),
+                    scheduled_text_input: String::new(),
+                    post_now_error: None,
+                    scheduled_post_error: None,
                 }
                 .render()
                 .unwrap(),
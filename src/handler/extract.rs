@@ -35,3 +35,55 @@ where
         Ok(Self(LanguageIdentifier::from_str("ko-KR").unwrap()))
     }
 }
+
+#[derive(Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum QuoteSort {
+    #[default]
+    Newest,
+    Oldest,
+    RecentlyPosted,
+    Text,
+}
+
+impl QuoteSort {
+    pub const ALL: [Self; 4] = [Self::Newest, Self::Oldest, Self::RecentlyPosted, Self::Text];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Newest => "newest",
+            Self::Oldest => "oldest",
+            Self::RecentlyPosted => "recently-posted",
+            Self::Text => "text",
+        }
+    }
+}
+
+/// Search, sort and page of the quote list, kept in the query string so that the forms of the
+/// page, which post to the current URL, come back to the same view.
+#[derive(Default, Deserialize)]
+pub struct QuoteQuery {
+    #[serde(default)]
+    pub q: String,
+    #[serde(default)]
+    pub sort: QuoteSort,
+    #[serde(default)]
+    pub page: usize,
+    pub lang: Option<String>,
+}
+
+impl<S> FromRequestParts<S> for QuoteQuery
+where
+    S: Send + Sync,
+{
+    type Rejection = ();
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        Ok(
+            axum::extract::Query::<QuoteQuery>::from_request_parts(parts, state)
+                .await
+                .map(|axum::extract::Query(query)| query)
+                .unwrap_or_default(),
+        )
+    }
+}
@@ -2,18 +2,145 @@ use std::collections::BTreeMap;
 
 use askama::Template;
 use fluent_templates::{LanguageIdentifier, Loader};
+use time::OffsetDateTime;
 use ulid::Ulid;
 
-use crate::internationalization::LOCALES;
+use crate::{
+    api::kube::{
+        AddQuotesReport, GenerationSettings, MarkovTokenizer, MatchType, PostHistory, Quote,
+        Reactions, ReplyRule, ReplySettings, Responder, ResponseMode, ScheduledPost, Script, Stats,
+        StreamingState, StreamingStatus, Visibility,
+    },
+    cron::Schedule,
+    internationalization::LOCALES,
+};
 
-use super::auth::FediverseUser;
+use super::{
+    auth::FediverseUser,
+    extract::{QuoteQuery, QuoteSort},
+};
 
 mod filters;
 
+const QUOTES_PER_PAGE: usize = 50;
+
 fn t(language: &LanguageIdentifier, text_id: &str) -> String {
     LOCALES.lookup(language, text_id)
 }
 
+fn match_type_text_id(match_type: &MatchType) -> &'static str {
+    match match_type {
+        MatchType::Substring => "match-type-substring",
+        MatchType::WholeWord => "match-type-whole-word",
+        MatchType::CaseInsensitive => "match-type-case-insensitive",
+        MatchType::Regex => "match-type-regex",
+    }
+}
+
+fn join_lines(lines: &[String]) -> String {
+    lines.join("\n")
+}
+
+fn response_mode_text_id(mode: &ResponseMode) -> &'static str {
+    match mode {
+        ResponseMode::Off => "response-mode-off",
+        ResponseMode::Reply => "response-mode-reply",
+        ResponseMode::React => "response-mode-react",
+    }
+}
+
+fn responder_text_id(responder: &Responder) -> &'static str {
+    match responder {
+        Responder::Dice => "dice-feature",
+        Responder::Coin => "coin-feature",
+        Responder::Choose => "choose-feature",
+        Responder::EightBall => "eight-ball-feature",
+        Responder::Tarot => "tarot-feature",
+        Responder::RandomNumber => "random-number-feature",
+    }
+}
+
+fn responder_guide_text_id(responder: &Responder) -> &'static str {
+    match responder {
+        Responder::Dice => "dice-feature-guide",
+        Responder::Coin => "coin-feature-guide",
+        Responder::Choose => "choose-feature-guide",
+        Responder::EightBall => "eight-ball-feature-guide",
+        Responder::Tarot => "tarot-feature-guide",
+        Responder::RandomNumber => "random-number-feature-guide",
+    }
+}
+
+fn quote_sort_text_id(sort: &QuoteSort) -> &'static str {
+    match sort {
+        QuoteSort::Newest => "quote-sort-newest",
+        QuoteSort::Oldest => "quote-sort-oldest",
+        QuoteSort::RecentlyPosted => "quote-sort-recently-posted",
+        QuoteSort::Text => "quote-sort-text",
+    }
+}
+
+/// Whether the quote matches the search. A search starting with `#` matches hashtags of the
+/// quote as a whole, and anything else is a case-insensitive substring.
+fn quote_matches(quote: &str, search: &str) -> bool {
+    let search = search.trim().to_lowercase();
+    match search.strip_prefix('#') {
+        Some(tag) if !tag.is_empty() => quote
+            .split_whitespace()
+            .flat_map(|word| word.split('#').skip(1))
+            .any(|hashtag| {
+                hashtag
+                    .trim_end_matches(|c: char| !c.is_alphanumeric() && c != '_')
+                    .to_lowercase()
+                    == tag
+            }),
+        _ => quote.to_lowercase().contains(&search),
+    }
+}
+
+pub struct QuotePage<'a> {
+    pub quotes: Vec<(&'a Ulid, &'a Quote)>,
+    /// 1-based.
+    pub page: usize,
+    pub page_count: usize,
+    pub match_count: usize,
+}
+
+fn markov_tokenizer_text_id(tokenizer: &MarkovTokenizer) -> &'static str {
+    match tokenizer {
+        MarkovTokenizer::Character => "markov-tokenizer-character",
+        MarkovTokenizer::Morpheme => "markov-tokenizer-morpheme",
+    }
+}
+
+fn visibility_text_id(visibility: &Visibility) -> &'static str {
+    match visibility {
+        Visibility::Public => "visibility-public",
+        Visibility::Unlisted => "visibility-unlisted",
+        Visibility::Private => "visibility-private",
+        Visibility::Direct => "visibility-direct",
+    }
+}
+
+fn streaming_state_text_id(state: &StreamingState) -> &'static str {
+    match state {
+        StreamingState::Connecting => "streaming-state-connecting",
+        StreamingState::Connected => "streaming-state-connected",
+        StreamingState::Reconnecting => "streaming-state-reconnecting",
+        StreamingState::Stopped => "streaming-state-stopped",
+        StreamingState::NotRunning => "streaming-state-not-running",
+    }
+}
+
+fn streaming_state_badge_class(state: &StreamingState) -> &'static str {
+    match state {
+        StreamingState::Connected => "badge-success",
+        StreamingState::Connecting => "badge-info",
+        StreamingState::Reconnecting => "badge-warning",
+        StreamingState::Stopped | StreamingState::NotRunning => "badge-error",
+    }
+}
+
 // fn ta<'a>(
 //     language: &'a LanguageIdentifier,
 //     text_id: &'a str,
@@ -40,22 +167,136 @@ pub struct IndexLoginTemplate {
     pub user: FediverseUser,
     pub quote_mode_selected: bool,
     pub is_quote_bulk_selected: bool,
-    pub quotes: BTreeMap<Ulid, (String, bool)>,
+    pub quotes: BTreeMap<Ulid, Quote>,
+    pub quote_query: QuoteQuery,
     pub quote_input: String,
     pub quote_bulk_input: String,
     pub quote_error: Option<TemplateError>,
+    pub quote_report: AddQuotesReport,
+    /// Posts of another account fetched for review, newest first.
+    pub import_candidates: Vec<String>,
+    pub import_error: Option<TemplateError>,
     pub cron_input: String,
     pub cron_error: Option<TemplateError>,
     pub dedup_duration_minutes: u32,
+    pub dedup_fallback: String,
     pub suspend_schedule: bool,
+    pub generation_settings: GenerationSettings,
+    pub generation_error: Option<TemplateError>,
     pub enable_reply: bool,
     pub is_reply_bulk_selected: bool,
-    pub reply_map: BTreeMap<String, BTreeMap<Ulid, String>>,
+    pub reply_map: Vec<ReplyRule>,
     pub reply_keyword_input: String,
+    pub reply_match_type_input: String,
     pub reply_input: String,
     pub reply_bulk_input: String,
     pub reply_error: Option<TemplateError>,
-    pub enable_dice_feature: bool,
+    pub enabled_responders: Vec<Responder>,
+    pub reply_settings: ReplySettings,
+    pub streaming_status: Option<StreamingStatus>,
+    pub reactions: Reactions,
+    pub scripts: Vec<Script>,
+    pub script_keyword_input: String,
+    pub script_steps_input: String,
+    pub script_error: Option<TemplateError>,
+    pub scheduled_posts: Vec<ScheduledPost>,
+    pub scheduled_at_input: String,
+    pub scheduled_text_input: String,
+    pub post_now_error: Option<TemplateError>,
+    pub scheduled_post_error: Option<TemplateError>,
+    pub post_history: PostHistory,
+}
+
+impl IndexLoginTemplate {
+    fn match_types(&self) -> [MatchType; 4] {
+        MatchType::ALL
+    }
+
+    fn response_modes(&self) -> [ResponseMode; 3] {
+        ResponseMode::ALL
+    }
+
+    fn responders(&self) -> [Responder; 6] {
+        Responder::ALL
+    }
+
+    fn quote_sorts(&self) -> [QuoteSort; 4] {
+        QuoteSort::ALL
+    }
+
+    fn quote_page(&self) -> QuotePage<'_> {
+        let mut quotes = self
+            .quotes
+            .iter()
+            .rev()
+            .filter(|(_, quote)| quote_matches(&quote.text, &self.quote_query.q))
+            .collect::<Vec<_>>();
+        match self.quote_query.sort {
+            QuoteSort::Newest => {}
+            QuoteSort::Oldest => quotes.reverse(),
+            QuoteSort::RecentlyPosted => {
+                quotes.sort_by_key(|(_, quote)| std::cmp::Reverse(quote.last_posted_at))
+            }
+            QuoteSort::Text => quotes.sort_by(|(_, a), (_, b)| a.text.cmp(&b.text)),
+        }
+        let match_count = quotes.len();
+        let page_count = match_count.div_ceil(QUOTES_PER_PAGE).max(1);
+        let page = self.quote_query.page.clamp(1, page_count);
+        let quotes = quotes
+            .into_iter()
+            .skip((page - 1) * QUOTES_PER_PAGE)
+            .take(QUOTES_PER_PAGE)
+            .collect();
+        QuotePage {
+            quotes,
+            page,
+            page_count,
+            match_count,
+        }
+    }
+
+    /// Link to a page of the quote list with the current search and sort.
+    fn quote_page_href(&self, page: usize) -> String {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(lang) = &self.quote_query.lang {
+            query.append_pair("lang", lang);
+        }
+        if !self.quote_query.q.is_empty() {
+            query.append_pair("q", &self.quote_query.q);
+        }
+        if self.quote_query.sort != QuoteSort::default() {
+            query.append_pair("sort", self.quote_query.sort.as_str());
+        }
+        query.append_pair("page", &page.to_string());
+        format!("?{}#quote-list", query.finish())
+    }
+
+    fn markov_tokenizers(&self) -> [MarkovTokenizer; 2] {
+        MarkovTokenizer::ALL
+    }
+
+    fn visibilities(&self) -> [Visibility; 4] {
+        Visibility::ALL
+    }
+
+    /// Returns how many posts the schedule makes within a dedup window, if that outnumbers the
+    /// quotes so the pool is bound to run dry.
+    fn posts_per_dedup_window_exceeding_quotes(&self) -> Option<u64> {
+        if self.dedup_duration_minutes == 0 || self.suspend_schedule {
+            return None;
+        }
+        let schedule = Schedule::parse(&self.cron_input)?;
+        let posts = schedule.count_within(OffsetDateTime::now_utc(), self.dedup_duration_minutes);
+        (posts > self.quotes.len() as u64).then_some(posts)
+    }
+}
+
+#[derive(Template)]
+#[template(path = "stats.html")]
+pub struct StatsTemplate {
+    pub language: LanguageIdentifier,
+    pub user: FediverseUser,
+    pub stats: Stats,
 }
 
 #[derive(Template)]
@@ -1,4 +1,5 @@
 use fluent_templates::LanguageIdentifier;
+use time::{macros::format_description, OffsetDateTime};
 
 pub fn t(
     language: &LanguageIdentifier,
@@ -8,6 +9,14 @@ pub fn t(
     Ok(super::t(language, text_id))
 }
 
+pub fn datetime(value: &OffsetDateTime, _: &dyn askama::Values) -> askama::Result<String> {
+    value
+        .format(format_description!(
+            "[year]-[month]-[day] [hour]:[minute] UTC"
+        ))
+        .map_err(|error| askama::Error::Custom(Box::new(error)))
+}
+
 // pub fn ta<'a>(
 //     language: &'a LanguageIdentifier,
 //     text_id: &'a str,
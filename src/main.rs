@@ -1,7 +1,10 @@
 mod api;
 mod config;
+mod cron;
 mod handler;
 mod internationalization;
+mod normalize;
+mod similarity;
 
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
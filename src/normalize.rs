@@ -0,0 +1,236 @@
+use std::sync::LazyLock;
+
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source",
+    "track", "wbr",
+];
+
+struct Tag<'a> {
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    attributes: &'a str,
+}
+
+impl Tag<'_> {
+    fn class(&self) -> Option<&str> {
+        let mut rest = self.attributes;
+        while let Some(index) = rest.find("class=") {
+            let before = rest[..index].chars().last();
+            rest = &rest[index + "class=".len()..];
+            if before.is_some_and(|c| !c.is_whitespace()) {
+                continue;
+            }
+            let quote = rest.chars().next()?;
+            if quote == '"' || quote == '\'' {
+                let value = &rest[1..];
+                return Some(&value[..value.find(quote)?]);
+            }
+            return Some(rest.split_whitespace().next().unwrap_or_default());
+        }
+        None
+    }
+
+    fn has_class(&self, class: &str) -> bool {
+        self.class()
+            .is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+    }
+
+    fn is_void(&self) -> bool {
+        self.self_closing || VOID_ELEMENTS.contains(&self.name.as_str())
+    }
+}
+
+/// Parses the inside of `<...>`. Returns `None` for comments, doctypes and the like.
+fn parse_tag(inner: &str) -> Option<Tag<'_>> {
+    let (closing, inner) = match inner.strip_prefix('/') {
+        Some(inner) => (true, inner),
+        None => (false, inner),
+    };
+    let (self_closing, inner) = match inner.strip_suffix('/') {
+        Some(inner) => (true, inner),
+        None => (false, inner),
+    };
+    let name_end = inner
+        .find(|c: char| c.is_whitespace())
+        .unwrap_or(inner.len());
+    let name = &inner[..name_end];
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return None;
+    }
+    Some(Tag {
+        name: name.to_ascii_lowercase(),
+        closing,
+        self_closing,
+        attributes: &inner[name_end..],
+    })
+}
+
+/// Finds the `>` closing a tag that starts right after `<`, skipping quoted attribute values.
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    for (index, c) in s.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(index),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn decode_entity(entity: &str) -> Option<char> {
+    if let Some(number) = entity.strip_prefix('#') {
+        let code = match number.strip_prefix(['x', 'X']) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse::<u32>().ok()?,
+        };
+        return char::from_u32(code);
+    }
+    match entity {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        "nbsp" => Some(' '),
+        _ => None,
+    }
+}
+
+fn decode_entities(text: &str, output: &mut String) {
+    let mut rest = text;
+    while let Some(index) = rest.find('&') {
+        output.push_str(&rest[..index]);
+        rest = &rest[index..];
+        let decoded = rest
+            .find(';')
+            .filter(|end| *end <= 10)
+            .and_then(|end| Some((decode_entity(&rest[1..end])?, end)));
+        if let Some((c, end)) = decoded {
+            output.push(c);
+            rest = &rest[end + 1..];
+        } else {
+            output.push('&');
+            rest = &rest[1..];
+        }
+    }
+    output.push_str(rest);
+}
+
+/// Collapses runs of spaces, trims every line and drops leading and trailing blank lines.
+fn tidy_whitespace(text: &str) -> String {
+    text.lines()
+        .map(|line| line.split_whitespace().collect::<Vec<_>>().join(" "))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_matches('\n')
+        .to_string()
+}
+
+/// Converts Mastodon status HTML into the plain text a user would read, leaving out mentions and
+/// links so that keywords only match what was actually written.
+pub fn html_to_text(html: &str) -> String {
+    let mut output = String::new();
+    // Number of elements open inside a mention or link being skipped.
+    let mut skip_depth = 0usize;
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        if skip_depth == 0 {
+            decode_entities(&rest[..start], &mut output);
+        }
+        rest = &rest[start + 1..];
+        let Some(end) = find_tag_end(rest) else {
+            if skip_depth == 0 {
+                output.push('<');
+            }
+            continue;
+        };
+        let inner = &rest[..end];
+        rest = &rest[end + 1..];
+        let Some(tag) = parse_tag(inner.trim()) else {
+            continue;
+        };
+
+        if skip_depth > 0 {
+            if tag.closing {
+                skip_depth -= 1;
+            } else if !tag.is_void() {
+                skip_depth += 1;
+            }
+            continue;
+        }
+
+        match (tag.name.as_str(), tag.closing) {
+            ("br", _) => output.push('\n'),
+            ("p", true) => output.push_str("\n\n"),
+            ("span", false) if tag.has_class("h-card") => skip_depth = 1,
+            ("a", false) if !tag.has_class("hashtag") => skip_depth = 1,
+            _ => {}
+        }
+    }
+    if skip_depth == 0 {
+        decode_entities(rest, &mut output);
+    }
+    tidy_whitespace(&output)
+}
+
+/// Removes `@user` and `@user@host` mentions from Misskey note text.
+pub fn strip_mentions(text: &str) -> String {
+    static MENTION_REGEX: LazyLock<regex::Regex> = LazyLock::new(|| {
+        regex::Regex::new(r"(^|\s)@[\w.-]+(@[\w.-]+)?").expect("failed to build regex")
+    });
+    tidy_whitespace(&MENTION_REGEX.replace_all(text, "$1"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn strips_mention_from_mastodon_status() {
+        let content = r#"<p><span class="h-card" translate="no"><a href="https://mastodon.social/@fediq" class="u-url mention">@<span>fediq</span></a></span> 1d6 굴려줘</p>"#;
+        assert_eq!(html_to_text(content), "1d6 굴려줘");
+    }
+
+    #[test]
+    fn strips_multiple_mentions() {
+        let content = r#"<p><span class="h-card" translate="no"><a href="https://planet.moe/@bot" class="u-url mention">@<span>bot</span></a></span> <span class="h-card" translate="no"><a href="https://mastodon.social/@alice" class="u-url mention">@<span>alice</span></a></span> 고양이 어디있어?</p>"#;
+        assert_eq!(html_to_text(content), "고양이 어디있어?");
+    }
+
+    #[test]
+    fn keeps_hashtags_and_drops_links() {
+        let content = r#"<p>cat pics <a href="https://mastodon.social/tags/cat" class="mention hashtag" rel="tag">#<span>cat</span></a> <a href="https://example.com/category/cats" target="_blank" rel="nofollow noopener" translate="no"><span class="invisible">https://</span><span class="ellipsis">example.com/category/ca</span><span class="invisible">ts</span></a></p>"#;
+        assert_eq!(html_to_text(content), "cat pics #cat");
+    }
+
+    #[test]
+    fn converts_line_breaks_and_paragraphs() {
+        let content = r#"<p><span class="h-card"><a href="https://a.example/@bot" class="u-url mention">@<span>bot</span></a></span> first<br />second</p><p>third</p>"#;
+        assert_eq!(html_to_text(content), "first\nsecond\n\nthird");
+    }
+
+    #[test]
+    fn decodes_entities() {
+        let content = r#"<p>&lt;3 &amp; &quot;hi&quot; it&#39;s &#x1F600; &unknown; AT&T</p>"#;
+        assert_eq!(html_to_text(content), r#"<3 & "hi" it's 😀 &unknown; AT&T"#);
+    }
+
+    #[test]
+    fn does_not_match_inside_attributes() {
+        let content = r#"<p><span class="h-card"><a href="https://cat.example/@bot" class="u-url mention">@<span>bot</span></a></span> hello</p>"#;
+        assert!(!html_to_text(content).contains("cat"));
+    }
+
+    #[test]
+    fn strips_misskey_mentions() {
+        assert_eq!(strip_mentions("@fediq@example.com 1d20"), "1d20");
+        assert_eq!(strip_mentions("@fediq 안녕 @other 반가워"), "안녕 반가워");
+        assert_eq!(
+            strip_mentions("mail me at user@example.com"),
+            "mail me at user@example.com"
+        );
+    }
+}
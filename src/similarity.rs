@@ -0,0 +1,126 @@
+//! Duplicate detection for quotes being added, so that pasting a list twice does not double the
+//! pool.
+
+use std::collections::HashSet;
+
+/// Trigram similarity from which two quotes are near duplicates.
+const NEAR_DUPLICATE_SIMILARITY: f64 = 0.8;
+
+pub enum Duplicate<'a> {
+    /// The same quote, up to whitespace.
+    Exact,
+    /// A quote similar enough to be a variation of this one.
+    Near(&'a str),
+}
+
+fn normalize_whitespace(quote: &str) -> String {
+    quote.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn trigrams(normalized: &str) -> HashSet<[char; 3]> {
+    let chars = format!(" {} ", normalized.to_lowercase())
+        .chars()
+        .collect::<Vec<_>>();
+    chars
+        .windows(3)
+        .map(|window| [window[0], window[1], window[2]])
+        .collect()
+}
+
+/// Sørensen–Dice coefficient of the trigrams.
+fn similarity(a: &HashSet<[char; 3]>, b: &HashSet<[char; 3]>) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    2.0 * a.intersection(b).count() as f64 / (a.len() + b.len()) as f64
+}
+
+struct Entry {
+    quote: String,
+    normalized: String,
+    trigrams: HashSet<[char; 3]>,
+}
+
+impl Entry {
+    fn new(quote: &str) -> Self {
+        let normalized = normalize_whitespace(quote);
+        let trigrams = trigrams(&normalized);
+        Self {
+            quote: quote.to_string(),
+            normalized,
+            trigrams,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct DuplicateChecker {
+    entries: Vec<Entry>,
+}
+
+impl DuplicateChecker {
+    pub fn new<'a>(quotes: impl IntoIterator<Item = &'a str>) -> Self {
+        Self {
+            entries: quotes.into_iter().map(Entry::new).collect(),
+        }
+    }
+
+    /// Returns the most similar quote if the quote duplicates one, exact duplicates first.
+    pub fn check(&self, quote: &str) -> Option<Duplicate<'_>> {
+        let entry = Entry::new(quote);
+        if self
+            .entries
+            .iter()
+            .any(|other| other.normalized == entry.normalized)
+        {
+            return Some(Duplicate::Exact);
+        }
+        self.entries
+            .iter()
+            .map(|other| (other, similarity(&entry.trigrams, &other.trigrams)))
+            .filter(|(_, similarity)| *similarity >= NEAR_DUPLICATE_SIMILARITY)
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(other, _)| Duplicate::Near(&other.quote))
+    }
+
+    pub fn insert(&mut self, quote: &str) {
+        self.entries.push(Entry::new(quote));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(checker: &DuplicateChecker, quote: &str) -> Option<Option<String>> {
+        checker.check(quote).map(|duplicate| match duplicate {
+            Duplicate::Exact => None,
+            Duplicate::Near(other) => Some(other.to_string()),
+        })
+    }
+
+    #[test]
+    fn finds_exact_duplicates_up_to_whitespace() {
+        let checker = DuplicateChecker::new(["오늘도 좋은 하루 되세요", "안녕"]);
+        assert_eq!(check(&checker, "  오늘도  좋은\n하루 되세요 "), Some(None));
+        assert_eq!(check(&checker, "안녕"), Some(None));
+        assert_eq!(check(&checker, "잘 자"), None);
+    }
+
+    #[test]
+    fn finds_near_duplicates() {
+        let mut checker = DuplicateChecker::new(["The quick brown fox jumps over the lazy dog"]);
+        assert_eq!(
+            check(&checker, "The quick brown fox jumped over the lazy dog!"),
+            Some(Some(
+                "The quick brown fox jumps over the lazy dog".to_string()
+            ))
+        );
+        assert_eq!(check(&checker, "A slow red fox naps under the tree"), None);
+        checker.insert("A slow red fox naps under the tree");
+        assert_eq!(
+            check(&checker, "a slow red fox naps under the tree"),
+            Some(Some("A slow red fox naps under the tree".to_string()))
+        );
+    }
+}